@@ -0,0 +1,7 @@
+-- Align product_listings with the listing API fields
+ALTER TABLE product_listings RENAME COLUMN quantity TO quantity_available;
+ALTER TABLE product_listings ADD COLUMN category VARCHAR(100) NOT NULL DEFAULT 'Other';
+ALTER TABLE product_listings ADD COLUMN unit_of_measure VARCHAR(50) NOT NULL DEFAULT 'unit';
+
+-- Create index for category filtering
+CREATE INDEX idx_product_listings_category ON product_listings(category);
@@ -0,0 +1,9 @@
+-- Add a full-text search vector over listing name and description
+ALTER TABLE product_listings ADD COLUMN search_vector tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', coalesce(name, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(description, '')), 'B')
+    ) STORED;
+
+-- Create GIN index for full-text queries
+CREATE INDEX idx_product_listings_search_vector ON product_listings USING GIN (search_vector);
@@ -0,0 +1,12 @@
+-- Create order_status_history table
+CREATE TABLE order_status_history (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    from_status VARCHAR(50) NOT NULL,
+    to_status VARCHAR(50) NOT NULL,
+    actor_id UUID NOT NULL REFERENCES members(id),
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index for per-order history lookups
+CREATE INDEX idx_order_status_history_order_id ON order_status_history(order_id, changed_at);
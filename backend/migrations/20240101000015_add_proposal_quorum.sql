@@ -0,0 +1,3 @@
+-- Add quorum to proposals as a fraction of all members
+ALTER TABLE proposals ADD COLUMN quorum DECIMAL(5,4) NOT NULL DEFAULT 0
+    CHECK (quorum >= 0 AND quorum <= 1);
@@ -0,0 +1,5 @@
+-- Aggregate each member's received ratings for reputation-based sorting
+CREATE VIEW member_reputation AS
+SELECT rated_id, AVG(score) AS avg_score, COUNT(*) AS rating_count
+FROM ratings
+GROUP BY rated_id;
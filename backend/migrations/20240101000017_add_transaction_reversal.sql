@@ -0,0 +1,3 @@
+-- Record why and when a completed transaction was reversed
+ALTER TABLE transactions ADD COLUMN reversal_reason TEXT;
+ALTER TABLE transactions ADD COLUMN reversed_at TIMESTAMPTZ;
@@ -0,0 +1,4 @@
+-- Add profile details collected at registration
+ALTER TABLE members ADD COLUMN name VARCHAR(255) NOT NULL DEFAULT '';
+ALTER TABLE members ADD COLUMN farm_name VARCHAR(255);
+ALTER TABLE members ADD COLUMN location VARCHAR(255);
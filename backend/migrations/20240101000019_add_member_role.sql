@@ -0,0 +1,2 @@
+-- Add member roles; admins are promoted manually
+ALTER TABLE members ADD COLUMN role VARCHAR(50) NOT NULL DEFAULT 'Member';
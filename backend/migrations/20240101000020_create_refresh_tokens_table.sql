@@ -0,0 +1,12 @@
+-- Create refresh tokens table; only a hash of each token is stored
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY,
+    member_id UUID NOT NULL REFERENCES members(id),
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for common queries
+CREATE INDEX idx_refresh_tokens_member_id ON refresh_tokens(member_id);
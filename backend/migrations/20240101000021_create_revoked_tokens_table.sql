@@ -0,0 +1,9 @@
+-- Create revoked tokens table; entries can be purged once the token has expired
+CREATE TABLE revoked_tokens (
+    jti UUID PRIMARY KEY,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create indexes for common queries
+CREATE INDEX idx_revoked_tokens_expires_at ON revoked_tokens(expires_at);
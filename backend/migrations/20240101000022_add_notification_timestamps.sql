@@ -0,0 +1,5 @@
+-- Track when notifications were created and read
+ALTER TABLE notifications ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE notifications ADD COLUMN read_at TIMESTAMPTZ;
+
+CREATE INDEX idx_notifications_recipient_created_at ON notifications(recipient_id, created_at DESC);
@@ -0,0 +1,4 @@
+-- Trigram index so prefix matches on listing names can use an index
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX idx_product_listings_name_trgm ON product_listings USING GIN (name gin_trgm_ops);
@@ -0,0 +1,2 @@
+-- Record when a member's personal data was erased
+ALTER TABLE members ADD COLUMN deleted_at TIMESTAMPTZ;
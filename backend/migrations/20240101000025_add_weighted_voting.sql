@@ -0,0 +1,8 @@
+-- Optional reputation-weighted tallies kept alongside the raw vote counts
+ALTER TABLE proposals
+    ADD COLUMN weighted_voting BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN weighted_for DECIMAL(12, 2) NOT NULL DEFAULT 0,
+    ADD COLUMN weighted_against DECIMAL(12, 2) NOT NULL DEFAULT 0;
+
+-- Existing proposals were decided by one member, one vote
+UPDATE proposals SET weighted_for = votes_for, weighted_against = votes_against;
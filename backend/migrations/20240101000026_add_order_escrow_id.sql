@@ -0,0 +1,5 @@
+-- Link an order to its escrow order on the NEAR contract, once funded
+ALTER TABLE orders ADD COLUMN escrow_order_id VARCHAR(128);
+
+-- An on-chain escrow order backs at most one backend order
+CREATE UNIQUE INDEX idx_orders_escrow_order_id ON orders(escrow_order_id);
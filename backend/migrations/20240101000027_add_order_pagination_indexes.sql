@@ -0,0 +1,3 @@
+-- Serve keyset pagination of a member's orders by (created_at, id) without sorting
+CREATE INDEX idx_orders_buyer_created ON orders(buyer_id, created_at DESC, id DESC);
+CREATE INDEX idx_orders_seller_created ON orders(seller_id, created_at DESC, id DESC);
@@ -0,0 +1,11 @@
+-- Fold free-form categories into the fixed set so search isn't fragmented
+UPDATE product_listings SET category = 'Vegetables' WHERE category IN ('Vegetable', 'vegetable', 'vegetables');
+UPDATE product_listings SET category = 'Fruits' WHERE category IN ('Fruit', 'fruit', 'fruits');
+UPDATE product_listings SET category = 'Grains' WHERE category IN ('Grain', 'grain', 'grains');
+UPDATE product_listings SET category = 'Dairy' WHERE category = 'dairy';
+UPDATE product_listings SET category = 'Livestock' WHERE category = 'livestock';
+UPDATE product_listings SET category = 'Other'
+WHERE category NOT IN ('Vegetables', 'Fruits', 'Grains', 'Dairy', 'Livestock', 'Other');
+
+ALTER TABLE product_listings ADD CONSTRAINT product_listings_category_check
+    CHECK (category IN ('Vegetables', 'Fruits', 'Grains', 'Dairy', 'Livestock', 'Other'));
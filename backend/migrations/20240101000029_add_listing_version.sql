@@ -0,0 +1,2 @@
+-- Row version for optimistic concurrency on listing edits
+ALTER TABLE product_listings ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,11 @@
+-- Create proposal comments table for discussing governance proposals
+CREATE TABLE proposal_comments (
+    id UUID PRIMARY KEY,
+    proposal_id UUID NOT NULL REFERENCES proposals(id),
+    member_id UUID NOT NULL REFERENCES members(id),
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index for per-proposal threads
+CREATE INDEX idx_proposal_comments_proposal_id ON proposal_comments(proposal_id, created_at);
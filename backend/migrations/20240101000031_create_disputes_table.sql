@@ -0,0 +1,15 @@
+-- Create disputes table mirroring the escrow contract's dispute flow
+CREATE TABLE disputes (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    raised_by UUID NOT NULL REFERENCES members(id),
+    reason TEXT NOT NULL,
+    resolution VARCHAR(50),
+    buyer_bps INTEGER CHECK (buyer_bps BETWEEN 0 AND 10000),
+    resolved_by UUID REFERENCES members(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ
+);
+
+-- An order can only have one open dispute at a time
+CREATE UNIQUE INDEX idx_disputes_open_order_id ON disputes(order_id) WHERE resolved_at IS NULL;
@@ -0,0 +1,11 @@
+-- Store emails lowercased; rows that would collide are left for an operator to merge,
+-- and the index below fails until they are
+UPDATE members m SET email = LOWER(TRIM(m.email))
+WHERE m.email <> LOWER(TRIM(m.email))
+  AND NOT EXISTS (
+      SELECT 1 FROM members other
+      WHERE other.id <> m.id AND LOWER(TRIM(other.email)) = LOWER(TRIM(m.email))
+  );
+
+-- Enforce uniqueness regardless of case, which also serves login lookups
+CREATE UNIQUE INDEX idx_members_email_lower ON members (LOWER(email));
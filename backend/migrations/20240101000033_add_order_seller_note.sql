@@ -0,0 +1,2 @@
+-- Note from the seller when accepting or rejecting an order
+ALTER TABLE orders ADD COLUMN seller_note TEXT;
@@ -0,0 +1,8 @@
+-- Per-category overrides of the global cooperative fee
+CREATE TABLE category_fees (
+    category VARCHAR(50) PRIMARY KEY
+        CHECK (category IN ('Vegetables', 'Fruits', 'Grains', 'Dairy', 'Livestock', 'Other')),
+    fee_percentage DECIMAL(5,4) NOT NULL CHECK (fee_percentage BETWEEN 0 AND 1),
+    updated_by UUID NOT NULL REFERENCES members(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,11 @@
+-- Trail of actions taken through admin-only endpoints
+CREATE TABLE admin_audit_log (
+    id UUID PRIMARY KEY,
+    actor_id UUID NOT NULL REFERENCES members(id),
+    action VARCHAR(50) NOT NULL,
+    target TEXT NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_admin_audit_log_created_at ON admin_audit_log(created_at);
@@ -0,0 +1,9 @@
+-- Encrypted farm name and location, replacing the plaintext columns for new writes.
+-- Rows written before this keep their plaintext values, which are still read
+-- until the member's profile is next stored.
+ALTER TABLE members
+    ADD COLUMN farm_name_ciphertext BYTEA,
+    ADD COLUMN farm_name_nonce BYTEA,
+    ADD COLUMN location_ciphertext BYTEA,
+    ADD COLUMN location_nonce BYTEA,
+    ADD COLUMN profile_key_id VARCHAR(64);
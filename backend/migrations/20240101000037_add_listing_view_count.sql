@@ -0,0 +1,2 @@
+-- Number of times a listing was viewed by someone other than its owner
+ALTER TABLE product_listings ADD COLUMN view_count BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- What a proposal does when it passes; General proposals have no effect
+ALTER TABLE proposals ADD COLUMN kind JSONB NOT NULL DEFAULT '"General"';
+
+-- Cooperative-wide fee set by governance, overriding COOPERATIVE_FEE_PERCENTAGE.
+-- There is at most one row.
+CREATE TABLE fee_settings (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    fee_percentage DECIMAL(5,4) NOT NULL CHECK (fee_percentage BETWEEN 0 AND 1),
+    proposal_id UUID REFERENCES proposals(id),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,11 @@
+-- Messages between an order's buyer and seller, kept after the order settles
+CREATE TABLE order_messages (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES orders(id),
+    sender_id UUID NOT NULL REFERENCES members(id),
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index for per-order threads
+CREATE INDEX idx_order_messages_order_id ON order_messages(order_id, created_at);
@@ -0,0 +1,5 @@
+-- Seasonal listings can expire; a periodic sweep archives them
+ALTER TABLE product_listings ADD COLUMN expires_at TIMESTAMPTZ;
+
+CREATE INDEX idx_product_listings_expires_at ON product_listings(expires_at)
+    WHERE expires_at IS NOT NULL;
@@ -0,0 +1,12 @@
+-- Listing searches a member has named so they can run them again
+CREATE TABLE saved_searches (
+    id UUID PRIMARY KEY,
+    member_id UUID NOT NULL REFERENCES members(id),
+    name VARCHAR(100) NOT NULL,
+    filters JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create index for listing a member's saved searches
+CREATE INDEX idx_saved_searches_member_id ON saved_searches(member_id, created_at);
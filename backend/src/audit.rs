@@ -0,0 +1,109 @@
+use crate::error::AuditError;
+use crate::models::{AdminAction, AdminAuditEntry};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use sqlx::{types::Json, PgPool};
+use uuid::Uuid;
+
+/// Most entries returned by one audit log query
+pub const MAX_AUDIT_ENTRIES: i64 = 500;
+
+/// Record an admin action in the audit log
+pub async fn record_admin_action(
+    pool: &PgPool,
+    actor_id: Uuid,
+    action: AdminAction,
+    target: &str,
+    metadata: Value,
+) -> Result<AdminAuditEntry, AuditError> {
+    let entry = sqlx::query_as::<_, AdminAuditEntry>(
+        "INSERT INTO admin_audit_log (id, actor_id, action, target, metadata, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, actor_id, action, target, metadata, created_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(actor_id)
+    .bind(action.to_string())
+    .bind(target)
+    .bind(Json(metadata))
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(entry)
+}
+
+/// Record an admin action that has already taken effect
+///
+/// The action can't be undone at this point, so a failed write is logged as a
+/// warning rather than turned into an error for the caller.
+pub async fn log_admin_action(
+    pool: &PgPool,
+    actor_id: Uuid,
+    action: AdminAction,
+    target: &str,
+    metadata: Value,
+) {
+    if let Err(e) = record_admin_action(pool, actor_id, action, target, metadata).await {
+        tracing::warn!("Failed to audit {} on {} by {}: {}", action, target, actor_id, e);
+    }
+}
+
+/// Get audit log entries recorded between `from` and `to`, newest first
+///
+/// Either bound may be omitted; at most [`MAX_AUDIT_ENTRIES`] are returned.
+pub async fn get_audit_log(
+    pool: &PgPool,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<AdminAuditEntry>, AuditError> {
+    if matches!((from, to), (Some(from), Some(to)) if from > to) {
+        return Err(AuditError::InvalidDateRange);
+    }
+    
+    let entries = sqlx::query_as::<_, AdminAuditEntry>(
+        "SELECT id, actor_id, action, target, metadata, created_at
+         FROM admin_audit_log
+         WHERE ($1::TIMESTAMPTZ IS NULL OR created_at >= $1)
+           AND ($2::TIMESTAMPTZ IS NULL OR created_at <= $2)
+         ORDER BY created_at DESC, id
+         LIMIT $3"
+    )
+    .bind(from)
+    .bind(to)
+    .bind(MAX_AUDIT_ENTRIES)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::insert_test_member;
+    use chrono::Duration;
+    use serde_json::json;
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_audit_log_date_filter(pool: PgPool) {
+        let admin = insert_test_member(&pool).await;
+        let entry = record_admin_action(&pool, admin, AdminAction::ProposalFinalized, "proposal", json!({}))
+            .await
+            .unwrap();
+        
+        let entries = get_audit_log(&pool, None, None).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, "ProposalFinalized");
+        
+        let before = entry.created_at - Duration::minutes(1);
+        let after = entry.created_at + Duration::minutes(1);
+        assert_eq!(get_audit_log(&pool, Some(before), Some(after)).await.unwrap().len(), 1);
+        assert!(get_audit_log(&pool, Some(after), None).await.unwrap().is_empty());
+        assert!(get_audit_log(&pool, None, Some(before)).await.unwrap().is_empty());
+        
+        let result = get_audit_log(&pool, Some(after), Some(before)).await;
+        assert!(matches!(result, Err(AuditError::InvalidDateRange)));
+    }
+}
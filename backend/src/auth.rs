@@ -0,0 +1,625 @@
+use crate::config::Config;
+use crate::error::{validation_result, AuthError, ValidationError};
+use crate::models::{Member, Role};
+use crate::security::{self, FieldKeys};
+use argon2::{
+    password_hash::{
+        rand_core::{OsRng, RngCore},
+        PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+    },
+    Argon2,
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{
+    decode, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Minimum accepted password length
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// JWT claims identifying an authenticated member
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// Member ID
+    pub sub: Uuid,
+    /// Member role
+    pub role: Role,
+    /// Unique token ID, used for revocation
+    pub jti: Uuid,
+    /// Issued at, as a Unix timestamp
+    pub iat: i64,
+    /// Expiry, as a Unix timestamp
+    pub exp: i64,
+}
+
+/// Register a new member with a hashed password
+///
+/// The farm name and location are stored encrypted with `keys`; the returned
+/// member carries them in plaintext.
+pub async fn register_member(
+    pool: &PgPool,
+    email: &str,
+    password: &str,
+    name: &str,
+    farm_name: Option<&str>,
+    location: Option<&str>,
+    keys: &FieldKeys,
+) -> Result<Member, AuthError> {
+    let email = normalize_email(email);
+    validate_registration(&email, password, name)?;
+    
+    let password_hash = hash_password(password)?;
+    let encrypted_farm_name = farm_name.map(|value| security::encrypt_field(keys, value)).transpose()?;
+    let encrypted_location = location.map(|value| security::encrypt_field(keys, value)).transpose()?;
+    let key_id = (farm_name.is_some() || location.is_some()).then(|| keys.current_id());
+    
+    let mut member = sqlx::query_as::<_, Member>(
+        "INSERT INTO members (id, email, password_hash, name, farm_name_ciphertext, farm_name_nonce, location_ciphertext, location_nonce, profile_key_id, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+         RETURNING id, email, password_hash, name, role, created_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(&email)
+    .bind(&password_hash)
+    .bind(name.trim())
+    .bind(encrypted_farm_name.as_ref().map(|field| &field.ciphertext))
+    .bind(encrypted_farm_name.as_ref().map(|field| &field.nonce))
+    .bind(encrypted_location.as_ref().map(|field| &field.ciphertext))
+    .bind(encrypted_location.as_ref().map(|field| &field.nonce))
+    .bind(key_id)
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => {
+            AuthError::RegistrationFailed("Email is already registered".to_string())
+        }
+        e => AuthError::Database(e),
+    })?;
+    
+    member.farm_name = farm_name.map(str::to_string);
+    member.location = location.map(str::to_string);
+    
+    Ok(member)
+}
+
+/// Authenticate a member by email and password
+pub async fn authenticate_member(
+    pool: &PgPool,
+    email: &str,
+    password: &str,
+) -> Result<Member, AuthError> {
+    let member = sqlx::query_as::<_, Member>(
+        "SELECT id, email, password_hash, name, role, created_at
+         FROM members
+         WHERE LOWER(email) = $1"
+    )
+    .bind(normalize_email(email))
+    .fetch_optional(pool)
+    .await?
+    // An unknown email is reported the same way as a wrong password
+    .ok_or(AuthError::InvalidCredentials)?;
+    
+    verify_password(password, &member.password_hash)?;
+    
+    Ok(member)
+}
+
+/// Hash a password with Argon2 and a random salt
+pub fn hash_password(password: &str) -> Result<String, AuthError> {
+    let salt = SaltString::generate(&mut OsRng);
+    
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|_| AuthError::HashingFailed)
+}
+
+/// Verify a password against a stored Argon2 hash in constant time
+pub fn verify_password(password: &str, password_hash: &str) -> Result<(), AuthError> {
+    let parsed_hash = PasswordHash::new(password_hash).map_err(|_| AuthError::HashingFailed)?;
+    
+    Argon2::default()
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .map_err(|e| match e {
+            argon2::password_hash::Error::Password => AuthError::InvalidCredentials,
+            _ => AuthError::HashingFailed,
+        })
+}
+
+/// Validate registration input before hashing, reporting every invalid field
+fn validate_registration(email: &str, password: &str, name: &str) -> Result<(), AuthError> {
+    let mut errors = Vec::new();
+    
+    if email.is_empty() || !email.contains('@') {
+        errors.push(ValidationError::new("email", "Invalid email format"));
+    }
+    
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        errors.push(ValidationError::new(
+            "password",
+            format!("Password must be at least {} characters", MIN_PASSWORD_LENGTH),
+        ));
+    }
+    
+    if name.trim().is_empty() {
+        errors.push(ValidationError::new("name", "Name cannot be empty"));
+    }
+    
+    validation_result(errors).map_err(AuthError::Validation)
+}
+
+/// Emails are matched case-insensitively
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Generate a signed JWT for a member that expires after `config.jwt_expiry_seconds`
+pub fn generate_token(member: &Member, config: &Config) -> Result<String, AuthError> {
+    // An unrecognized role never grants more than member access
+    let role = member.role.parse().unwrap_or(Role::Member);
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: member.id,
+        role,
+        jti: Uuid::new_v4(),
+        iat: now,
+        exp: now + config.jwt_expiry_seconds,
+    };
+    
+    encode_claims(&claims, &config.jwt_secret)
+}
+
+/// Sign claims with the given secret
+fn encode_claims(claims: &Claims, jwt_secret: &str) -> Result<String, AuthError> {
+    encode(
+        &Header::new(Algorithm::HS256),
+        claims,
+        &EncodingKey::from_secret(jwt_secret.as_bytes()),
+    )
+    .map_err(|_| AuthError::InvalidToken)
+}
+
+/// Validate a JWT and return its claims, rejecting revoked tokens
+///
+/// Every token of an erased member counts as revoked, so closing an account or
+/// removing a member signs them out everywhere at once.
+pub async fn validate_token(pool: &PgPool, token: &str, jwt_secret: &str) -> Result<Claims, AuthError> {
+    let claims = decode_token(token, jwt_secret)?;
+    
+    let (revoked,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1)
+             OR EXISTS(SELECT 1 FROM members WHERE id = $2 AND deleted_at IS NOT NULL)"
+    )
+    .bind(claims.jti)
+    .bind(claims.sub)
+    .fetch_one(pool)
+    .await?;
+    
+    if revoked {
+        return Err(AuthError::TokenRevoked);
+    }
+    
+    Ok(claims)
+}
+
+/// Verify a JWT's signature and expiry and return its claims
+///
+/// Returns `TokenExpired` for a well-formed token past its `exp`, and
+/// `InvalidToken` for anything else that fails to verify.
+pub fn decode_token(token: &str, jwt_secret: &str) -> Result<Claims, AuthError> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.set_required_spec_claims(&["exp", "sub", "jti"]);
+    
+    decode::<Claims>(token, &DecodingKey::from_secret(jwt_secret.as_bytes()), &validation)
+        .map(|data| data.claims)
+        .map_err(|e| match e.kind() {
+            ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+            _ => AuthError::InvalidToken,
+        })
+}
+
+/// Log a member out by revoking the access token and all of their refresh tokens
+pub async fn logout(pool: &PgPool, claims: &Claims) -> Result<(), AuthError> {
+    let mut tx = pool.begin().await?;
+    
+    sqlx::query(
+        "INSERT INTO revoked_tokens (jti, expires_at, revoked_at)
+         VALUES ($1, to_timestamp($2), $3)
+         ON CONFLICT (jti) DO NOTHING"
+    )
+    .bind(claims.jti)
+    .bind(claims.exp as f64)
+    .bind(Utc::now())
+    .execute(&mut *tx)
+    .await?;
+    
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = $1 WHERE member_id = $2 AND revoked_at IS NULL")
+        .bind(Utc::now())
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    
+    tx.commit().await?;
+    
+    Ok(())
+}
+
+/// Delete revocation entries for tokens that have since expired
+pub async fn purge_expired_revocations(pool: &PgPool) -> Result<u64, AuthError> {
+    let result = sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < $1")
+        .bind(Utc::now())
+        .execute(pool)
+        .await?;
+    
+    Ok(result.rows_affected())
+}
+
+/// A new access token together with the refresh token that replaces the one used
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshedTokens {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+/// Issue a new refresh token for a member
+///
+/// The token is returned once; only its SHA-256 hash is stored.
+pub async fn generate_refresh_token(
+    pool: &PgPool,
+    member_id: Uuid,
+    config: &Config,
+) -> Result<String, AuthError> {
+    let mut conn = pool.acquire().await?;
+    store_refresh_token(&mut conn, member_id, config).await
+}
+
+/// Exchange a refresh token for a new access token, rotating the refresh token
+///
+/// The presented token is revoked so it cannot be replayed.
+pub async fn refresh_access_token(
+    pool: &PgPool,
+    refresh_token: &str,
+    config: &Config,
+) -> Result<RefreshedTokens, AuthError> {
+    let mut tx = pool.begin().await?;
+    
+    let (token_id, member_id, expires_at, revoked_at): (Uuid, Uuid, DateTime<Utc>, Option<DateTime<Utc>>) =
+        sqlx::query_as(
+            "SELECT id, member_id, expires_at, revoked_at
+             FROM refresh_tokens
+             WHERE token_hash = $1
+             FOR UPDATE"
+        )
+        .bind(hash_refresh_token(refresh_token))
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AuthError::InvalidToken)?;
+    
+    if revoked_at.is_some() {
+        return Err(AuthError::InvalidToken);
+    }
+    
+    if expires_at <= Utc::now() {
+        return Err(AuthError::TokenExpired);
+    }
+    
+    sqlx::query("UPDATE refresh_tokens SET revoked_at = $1 WHERE id = $2")
+        .bind(Utc::now())
+        .bind(token_id)
+        .execute(&mut *tx)
+        .await?;
+    
+    let member = sqlx::query_as::<_, Member>(
+        "SELECT id, email, password_hash, name, role, created_at
+         FROM members
+         WHERE id = $1"
+    )
+    .bind(member_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AuthError::MemberNotFound)?;
+    
+    let token = generate_token(&member, config)?;
+    let refresh_token = store_refresh_token(&mut tx, member_id, config).await?;
+    
+    tx.commit().await?;
+    
+    Ok(RefreshedTokens { token, refresh_token })
+}
+
+/// Generate a random refresh token and store its hash
+async fn store_refresh_token(
+    conn: &mut PgConnection,
+    member_id: Uuid,
+    config: &Config,
+) -> Result<String, AuthError> {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    
+    let now = Utc::now();
+    sqlx::query(
+        "INSERT INTO refresh_tokens (id, member_id, token_hash, expires_at, created_at)
+         VALUES ($1, $2, $3, $4, $5)"
+    )
+    .bind(Uuid::new_v4())
+    .bind(member_id)
+    .bind(hash_refresh_token(&token))
+    .bind(now + Duration::seconds(config.refresh_token_expiry_seconds))
+    .bind(now)
+    .execute(conn)
+    .await?;
+    
+    Ok(token)
+}
+
+/// Hex-encoded SHA-256 of a refresh token; tokens are random, so a fast hash suffices
+fn hash_refresh_token(token: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, token.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{insert_test_member, test_config, test_field_keys, test_member};
+    
+    #[test]
+    fn test_hash_and_verify_password() {
+        let hash = hash_password("correct horse battery").unwrap();
+        
+        assert_ne!(hash, "correct horse battery");
+        assert!(hash.starts_with("$argon2"));
+        assert!(verify_password("correct horse battery", &hash).is_ok());
+        assert!(matches!(
+            verify_password("wrong password", &hash),
+            Err(AuthError::InvalidCredentials)
+        ));
+    }
+    
+    #[test]
+    fn test_hash_password_uses_random_salt() {
+        let first = hash_password("correct horse battery").unwrap();
+        let second = hash_password("correct horse battery").unwrap();
+        
+        assert_ne!(first, second);
+    }
+    
+    #[test]
+    fn test_verify_password_malformed_hash() {
+        assert!(matches!(
+            verify_password("anything", "not-a-hash"),
+            Err(AuthError::HashingFailed)
+        ));
+    }
+    
+    #[test]
+    fn test_validate_registration() {
+        assert!(validate_registration("farmer@example.com", "long enough", "Ada").is_ok());
+        assert!(validate_registration("not-an-email", "long enough", "Ada").is_err());
+        assert!(validate_registration("farmer@example.com", "short", "Ada").is_err());
+        assert!(validate_registration("farmer@example.com", "long enough", "  ").is_err());
+        
+        match validate_registration("not-an-email", "short", "Ada") {
+            Err(AuthError::Validation(errors)) => {
+                let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(fields, vec!["email", "password"]);
+            }
+            other => panic!("expected field errors, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_token_round_trip() {
+        let config = test_config();
+        let member = test_member(Role::Admin);
+        let token = generate_token(&member, &config).unwrap();
+        
+        let claims = decode_token(&token, &config.jwt_secret).unwrap();
+        
+        assert_eq!(claims.sub, member.id);
+        assert_eq!(claims.role, Role::Admin);
+        assert_eq!(claims.exp - claims.iat, config.jwt_expiry_seconds);
+    }
+    
+    #[test]
+    fn test_validate_token_rejects_malformed_and_wrong_secret() {
+        let config = test_config();
+        let token = generate_token(&test_member(Role::Member), &config).unwrap();
+        
+        assert!(matches!(decode_token("garbage", &config.jwt_secret), Err(AuthError::InvalidToken)));
+        assert!(matches!(decode_token(&token, "another-secret"), Err(AuthError::InvalidToken)));
+    }
+    
+    #[test]
+    fn test_validate_token_expired() {
+        let config = test_config();
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: Uuid::new_v4(),
+            role: Role::Member,
+            jti: Uuid::new_v4(),
+            iat: now - 7200,
+            exp: now - 3600,
+        };
+        let token = encode_claims(&claims, &config.jwt_secret).unwrap();
+        
+        assert!(matches!(decode_token(&token, &config.jwt_secret), Err(AuthError::TokenExpired)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_register_and_authenticate(pool: PgPool) {
+        let member = register_member(
+            &pool,
+            "Farmer@Example.com",
+            "correct horse battery",
+            "Ada",
+            Some("Green Acres"),
+            None,
+            &test_field_keys(),
+        )
+        .await
+        .unwrap();
+        
+        assert_eq!(member.email, "farmer@example.com");
+        assert_eq!(member.role, Role::Member.to_string());
+        assert_ne!(member.password_hash, "correct horse battery");
+        
+        let authenticated = authenticate_member(&pool, "farmer@example.com", "correct horse battery")
+            .await
+            .unwrap();
+        assert_eq!(authenticated.id, member.id);
+        
+        let result = authenticate_member(&pool, "farmer@example.com", "wrong password").await;
+        assert!(matches!(result, Err(AuthError::InvalidCredentials)));
+        
+        let result = authenticate_member(&pool, "nobody@example.com", "correct horse battery").await;
+        assert!(matches!(result, Err(AuthError::InvalidCredentials)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_register_duplicate_email(pool: PgPool) {
+        register_member(&pool, "farmer@example.com", "correct horse battery", "Ada", None, None, &test_field_keys())
+            .await
+            .unwrap();
+        
+        let result = register_member(&pool, "FARMER@example.com", "another password", "Bob", None, None, &test_field_keys()).await;
+        
+        assert!(matches!(result, Err(AuthError::RegistrationFailed(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_authenticate_ignores_email_case(pool: PgPool) {
+        let member = register_member(&pool, "  Alice@Farm.com ", "correct horse battery", "Alice", None, None, &test_field_keys())
+            .await
+            .unwrap();
+        assert_eq!(member.email, "alice@farm.com");
+        
+        for email in ["alice@farm.com", "ALICE@FARM.COM", " Alice@farm.Com"] {
+            let authenticated = authenticate_member(&pool, email, "correct horse battery").await.unwrap();
+            assert_eq!(authenticated.id, member.id);
+        }
+        
+        // The database rejects a differently cased duplicate even if it bypasses normalization
+        let result = sqlx::query("INSERT INTO members (id, email, password_hash) VALUES ($1, $2, $3)")
+            .bind(Uuid::new_v4())
+            .bind("ALICE@farm.com")
+            .bind("hash")
+            .execute(&pool)
+            .await;
+        assert!(matches!(result, Err(sqlx::Error::Database(e)) if e.is_unique_violation()));
+    }
+    
+    #[test]
+    fn test_member_hides_password_hash() {
+        let member = test_member(Role::Member);
+        
+        assert!(!serde_json::to_string(&member).unwrap().contains("argon2"));
+        assert!(!format!("{:?}", member).contains("argon2"));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_refresh_rotates_token(pool: PgPool) {
+        let config = test_config();
+        let member_id = insert_test_member(&pool).await;
+        let refresh_token = generate_refresh_token(&pool, member_id, &config).await.unwrap();
+        
+        let refreshed = refresh_access_token(&pool, &refresh_token, &config).await.unwrap();
+        
+        assert_eq!(decode_token(&refreshed.token, &config.jwt_secret).unwrap().sub, member_id);
+        assert_ne!(refreshed.refresh_token, refresh_token);
+        
+        // The used token cannot be replayed, but its replacement works
+        let result = refresh_access_token(&pool, &refresh_token, &config).await;
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+        assert!(refresh_access_token(&pool, &refreshed.refresh_token, &config).await.is_ok());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_refresh_rejects_expired_and_unknown_tokens(pool: PgPool) {
+        let config = test_config();
+        let member_id = insert_test_member(&pool).await;
+        let refresh_token = generate_refresh_token(&pool, member_id, &config).await.unwrap();
+        sqlx::query("UPDATE refresh_tokens SET expires_at = NOW() - INTERVAL '1 hour'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        
+        let result = refresh_access_token(&pool, &refresh_token, &config).await;
+        assert!(matches!(result, Err(AuthError::TokenExpired)));
+        
+        let result = refresh_access_token(&pool, "not-a-refresh-token", &config).await;
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_refresh_token_stored_hashed(pool: PgPool) {
+        let config = test_config();
+        let member_id = insert_test_member(&pool).await;
+        let refresh_token = generate_refresh_token(&pool, member_id, &config).await.unwrap();
+        
+        let (stored,): (String,) = sqlx::query_as("SELECT token_hash FROM refresh_tokens")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        
+        assert_ne!(stored, refresh_token);
+        assert_eq!(stored, hash_refresh_token(&refresh_token));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_logout_revokes_tokens(pool: PgPool) {
+        let config = test_config();
+        let member_id = insert_test_member(&pool).await;
+        let mut member = test_member(Role::Member);
+        member.id = member_id;
+        let token = generate_token(&member, &config).unwrap();
+        let refresh_token = generate_refresh_token(&pool, member_id, &config).await.unwrap();
+        let claims = validate_token(&pool, &token, &config.jwt_secret).await.unwrap();
+        
+        logout(&pool, &claims).await.unwrap();
+        
+        let result = validate_token(&pool, &token, &config.jwt_secret).await;
+        assert!(matches!(result, Err(AuthError::TokenRevoked)));
+        let result = refresh_access_token(&pool, &refresh_token, &config).await;
+        assert!(matches!(result, Err(AuthError::InvalidToken)));
+        
+        // Other tokens stay valid, and logging out twice is harmless
+        let other = generate_token(&member, &config).unwrap();
+        assert!(validate_token(&pool, &other, &config.jwt_secret).await.is_ok());
+        assert!(logout(&pool, &claims).await.is_ok());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_purge_expired_revocations(pool: PgPool) {
+        let now = Utc::now().timestamp();
+        for exp in [now - 60, now + 3600] {
+            let claims = Claims {
+                sub: Uuid::new_v4(),
+                role: Role::Member,
+                jti: Uuid::new_v4(),
+                iat: now - 7200,
+                exp,
+            };
+            logout(&pool, &claims).await.unwrap();
+        }
+        
+        assert_eq!(purge_expired_revocations(&pool).await.unwrap(), 1);
+        assert_eq!(purge_expired_revocations(&pool).await.unwrap(), 0);
+    }
+}
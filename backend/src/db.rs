@@ -1,4 +1,8 @@
+use crate::config::Config;
 use sqlx::postgres::{PgPool, PgPoolOptions};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::future::Future;
 use std::time::Duration;
 
 /// Database connection pool wrapper
@@ -8,17 +12,26 @@ pub struct Database {
 }
 
 impl Database {
-    /// Create a new database connection pool
-    pub async fn new(database_url: &str) -> Result<Self, sqlx::Error> {
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .acquire_timeout(Duration::from_secs(3))
-            .connect(database_url)
-            .await?;
+    /// Create a new database connection pool sized by the configuration
+    ///
+    /// Connecting is retried with exponential backoff so a database that is
+    /// still starting up does not fail the whole application.
+    pub async fn new(config: &Config) -> Result<Self, sqlx::Error> {
+        let pool = retry_with_backoff(
+            config.db_connect_attempts,
+            Duration::from_millis(config.db_connect_retry_base_ms),
+            || pool_options(config).connect(&config.database_url),
+        )
+        .await?;
         
         Ok(Self { pool })
     }
     
+    /// Wrap an existing connection pool
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
     /// Get a reference to the connection pool
     pub fn pool(&self) -> &PgPool {
         &self.pool
@@ -33,6 +46,19 @@ impl Database {
         Ok(())
     }
     
+    /// Count embedded migrations that have not been successfully applied
+    pub async fn pending_migrations(&self) -> Result<usize, sqlx::Error> {
+        let applied: Vec<(i64,)> = sqlx::query_as("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(&self.pool)
+            .await?;
+        let applied: HashSet<i64> = applied.into_iter().map(|(version,)| version).collect();
+        
+        Ok(sqlx::migrate!("./migrations")
+            .iter()
+            .filter(|migration| !applied.contains(&migration.version))
+            .count())
+    }
+    
     /// Check if the database connection is healthy
     pub async fn health_check(&self) -> Result<(), sqlx::Error> {
         sqlx::query("SELECT 1")
@@ -42,3 +68,131 @@ impl Database {
         Ok(())
     }
 }
+
+/// Pool options built from the configured size and acquire timeout
+fn pool_options(config: &Config) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(config.db_max_connections)
+        .min_connections(config.db_min_connections)
+        .acquire_timeout(Duration::from_secs(config.db_acquire_timeout_secs))
+}
+
+/// Run `operation` up to `attempts` times, doubling the delay after each failure
+async fn retry_with_backoff<T, E, F, Fut>(attempts: u32, base_delay: Duration, mut operation: F) -> Result<T, E>
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < attempts => {
+                tracing::warn!(
+                    "Database connection attempt {}/{} failed: {}; retrying in {:?}",
+                    attempt,
+                    attempts,
+                    e,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_config;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    
+    #[test]
+    fn test_pool_options_use_config() {
+        let config = Config {
+            db_max_connections: 20,
+            db_min_connections: 2,
+            db_acquire_timeout_secs: 10,
+            ..test_config()
+        };
+        
+        let options = pool_options(&config);
+        
+        assert_eq!(options.get_max_connections(), 20);
+        assert_eq!(options.get_min_connections(), 2);
+        assert_eq!(options.get_acquire_timeout(), Duration::from_secs(10));
+    }
+    
+    #[tokio::test]
+    async fn test_unreachable_database_retried_then_fails() {
+        // Nothing listens on port 1, so every attempt is refused
+        let config = Config {
+            database_url: "postgres://postgres@127.0.0.1:1/dofta".to_string(),
+            db_acquire_timeout_secs: 1,
+            ..test_config()
+        };
+        let attempts = AtomicU32::new(0);
+        
+        let result = retry_with_backoff(3, Duration::from_millis(1), || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            pool_options(&config).connect(&config.database_url)
+        })
+        .await;
+        
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_retry_stops_after_success() {
+        let attempts = AtomicU32::new(0);
+        
+        let result: Result<u32, String> = retry_with_backoff(5, Duration::from_millis(1), || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err("not ready".to_string()),
+                n => Ok(n),
+            }
+        })
+        .await;
+        
+        assert_eq!(result, Ok(1));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+    
+    #[tokio::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_connect_with_overrides() {
+        let config = Config {
+            database_url: std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+            db_max_connections: 2,
+            db_min_connections: 1,
+            db_acquire_timeout_secs: 5,
+            ..test_config()
+        };
+        
+        let db = Database::new(&config).await.unwrap();
+        
+        db.health_check().await.unwrap();
+        assert_eq!(db.pool().options().get_max_connections(), 2);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_no_pending_migrations_after_migrate(pool: PgPool) {
+        let db = Database::from_pool(pool);
+        
+        assert_eq!(db.pending_migrations().await.unwrap(), 0);
+        
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(db.pending_migrations().await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::error::DisputeError;
+use crate::models::{Dispute, DisputeResolution, Order, OrderStatus};
+use crate::orders;
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Longest dispute reason accepted, matching the escrow contract
+pub const MAX_DISPUTE_REASON_LENGTH: usize = 500;
+
+/// Most basis points a split can give back to the buyer
+const MAX_BUYER_BPS: u16 = 10_000;
+
+/// Raise a dispute over an accepted or shipped order (buyer or seller action)
+///
+/// The order moves to `Disputed` in the same transaction the dispute is recorded.
+pub async fn raise_dispute(
+    pool: &PgPool,
+    order_id: Uuid,
+    member_id: Uuid,
+    reason: &str,
+) -> Result<Dispute, DisputeError> {
+    validate_reason(reason)?;
+    
+    let mut tx = pool.begin().await?;
+    
+    let order = sqlx::query_as::<_, Order>(
+        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at
+         FROM orders
+         WHERE id = $1
+         FOR UPDATE"
+    )
+    .bind(order_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(DisputeError::OrderNotFound)?;
+    
+    if member_id != order.buyer_id && member_id != order.seller_id {
+        return Err(DisputeError::Unauthorized);
+    }
+    
+    let current_status = order.status.parse::<OrderStatus>()
+        .map_err(|e| DisputeError::InvalidData(format!("Invalid order status: {}", e)))?;
+    
+    if !orders::is_valid_status_transition(&current_status, &OrderStatus::Disputed) {
+        return Err(DisputeError::InvalidStatusTransition(format!(
+            "Cannot dispute an order that is {}",
+            current_status
+        )));
+    }
+    
+    orders::record_status_change(&mut tx, order_id, current_status, OrderStatus::Disputed, member_id).await?;
+    
+    let dispute = sqlx::query_as::<_, Dispute>(
+        "INSERT INTO disputes (id, order_id, raised_by, reason, created_at)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, order_id, raised_by, reason, resolution, buyer_bps, resolved_by, created_at, resolved_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_id)
+    .bind(member_id)
+    .bind(reason.trim())
+    .bind(Utc::now())
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    tx.commit().await?;
+    
+    Ok(dispute)
+}
+
+/// Resolve an open dispute, moving its order to `Resolved` (admin action)
+///
+/// Only the decision is recorded here; the funds are settled by the escrow contract.
+pub async fn resolve_dispute(
+    pool: &PgPool,
+    dispute_id: Uuid,
+    admin_id: Uuid,
+    resolution: DisputeResolution,
+) -> Result<Dispute, DisputeError> {
+    if matches!(resolution.buyer_bps(), Some(bps) if bps > MAX_BUYER_BPS) {
+        return Err(DisputeError::InvalidData(format!(
+            "Buyer share cannot exceed {} basis points",
+            MAX_BUYER_BPS
+        )));
+    }
+    
+    let mut tx = pool.begin().await?;
+    
+    let dispute = sqlx::query_as::<_, Dispute>(
+        "SELECT id, order_id, raised_by, reason, resolution, buyer_bps, resolved_by, created_at, resolved_at
+         FROM disputes
+         WHERE id = $1
+         FOR UPDATE"
+    )
+    .bind(dispute_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(DisputeError::NotFound)?;
+    
+    if dispute.resolved_at.is_some() {
+        return Err(DisputeError::AlreadyResolved);
+    }
+    
+    orders::record_status_change(&mut tx, dispute.order_id, OrderStatus::Disputed, OrderStatus::Resolved, admin_id)
+        .await?;
+    
+    let dispute = sqlx::query_as::<_, Dispute>(
+        "UPDATE disputes SET resolution = $1, buyer_bps = $2, resolved_by = $3, resolved_at = $4
+         WHERE id = $5
+         RETURNING id, order_id, raised_by, reason, resolution, buyer_bps, resolved_by, created_at, resolved_at"
+    )
+    .bind(resolution.to_string())
+    .bind(resolution.buyer_bps().map(i32::from))
+    .bind(admin_id)
+    .bind(Utc::now())
+    .bind(dispute_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    tx.commit().await?;
+    
+    Ok(dispute)
+}
+
+/// Get a dispute by ID
+pub async fn get_dispute(pool: &PgPool, dispute_id: Uuid) -> Result<Dispute, DisputeError> {
+    let dispute = sqlx::query_as::<_, Dispute>(
+        "SELECT id, order_id, raised_by, reason, resolution, buyer_bps, resolved_by, created_at, resolved_at
+         FROM disputes
+         WHERE id = $1"
+    )
+    .bind(dispute_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(DisputeError::NotFound)?;
+    
+    Ok(dispute)
+}
+
+/// Validate a dispute reason before it is stored
+pub fn validate_reason(reason: &str) -> Result<(), DisputeError> {
+    let reason = reason.trim();
+    
+    if reason.is_empty() {
+        return Err(DisputeError::InvalidData("Dispute reason cannot be empty".to_string()));
+    }
+    
+    if reason.chars().count() > MAX_DISPUTE_REASON_LENGTH {
+        return Err(DisputeError::InvalidData(format!(
+            "Dispute reason cannot be longer than {} characters",
+            MAX_DISPUTE_REASON_LENGTH
+        )));
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orders::CreateOrderData;
+    use crate::test_utils::{insert_test_listing, insert_test_member, test_config};
+    use rust_decimal::Decimal;
+    
+    /// Place an order and have the seller accept it
+    async fn accepted_order(pool: &PgPool) -> Order {
+        let buyer = insert_test_member(pool).await;
+        let seller = insert_test_member(pool).await;
+        let listing_id = insert_test_listing(pool, seller).await;
+        let order = orders::create_order(
+            pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(2, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        
+        orders::accept_order(pool, order.id, seller, None).await.unwrap()
+    }
+    
+    #[test]
+    fn test_validate_reason() {
+        assert!(validate_reason("Crates arrived crushed").is_ok());
+        assert!(matches!(validate_reason("  "), Err(DisputeError::InvalidData(_))));
+        assert!(validate_reason(&"x".repeat(MAX_DISPUTE_REASON_LENGTH)).is_ok());
+        assert!(matches!(
+            validate_reason(&"x".repeat(MAX_DISPUTE_REASON_LENGTH + 1)),
+            Err(DisputeError::InvalidData(_))
+        ));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_raise_dispute(pool: PgPool) {
+        let order = accepted_order(&pool).await;
+        let outsider = insert_test_member(&pool).await;
+        
+        let result = raise_dispute(&pool, order.id, outsider, "Never arrived").await;
+        assert!(matches!(result, Err(DisputeError::Unauthorized)));
+        let result = raise_dispute(&pool, order.id, order.buyer_id, "").await;
+        assert!(matches!(result, Err(DisputeError::InvalidData(_))));
+        
+        let dispute = raise_dispute(&pool, order.id, order.buyer_id, " Never arrived ").await.unwrap();
+        assert_eq!(dispute.raised_by, order.buyer_id);
+        assert_eq!(dispute.reason, "Never arrived");
+        assert!(dispute.resolution.is_none());
+        
+        let order = orders::get_order(&pool, order.id).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Disputed.to_string());
+        let history = orders::get_order_history(&pool, order.id).await.unwrap();
+        assert_eq!(history.last().unwrap().actor_id, order.buyer_id);
+        
+        // The other party can't open a second dispute on the same order
+        let result = raise_dispute(&pool, order.id, order.seller_id, "Buyer is stalling").await;
+        assert!(matches!(result, Err(DisputeError::InvalidStatusTransition(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_resolve_dispute(pool: PgPool) {
+        let order = accepted_order(&pool).await;
+        let admin = insert_test_member(&pool).await;
+        let dispute = raise_dispute(&pool, order.id, order.seller_id, "Payment never locked").await.unwrap();
+        
+        let result = resolve_dispute(&pool, dispute.id, admin, DisputeResolution::Split { buyer_bps: 10_001 }).await;
+        assert!(matches!(result, Err(DisputeError::InvalidData(_))));
+        
+        let resolution = DisputeResolution::Split { buyer_bps: 2_500 };
+        let resolved = resolve_dispute(&pool, dispute.id, admin, resolution).await.unwrap();
+        assert_eq!(resolved.resolution.as_deref(), Some("Split"));
+        assert_eq!(resolved.buyer_bps, Some(2_500));
+        assert_eq!(resolved.resolved_by, Some(admin));
+        assert!(resolved.resolved_at.is_some());
+        
+        let order = orders::get_order(&pool, order.id).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Resolved.to_string());
+        
+        let result = resolve_dispute(&pool, dispute.id, admin, DisputeResolution::RefundBuyer).await;
+        assert!(matches!(result, Err(DisputeError::AlreadyResolved)));
+        let result = resolve_dispute(&pool, Uuid::new_v4(), admin, DisputeResolution::PaySeller).await;
+        assert!(matches!(result, Err(DisputeError::NotFound)));
+    }
+}
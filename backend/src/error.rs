@@ -1,3 +1,10 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
 use thiserror::Error;
 
 /// Main error type for the DOFTA system
@@ -12,9 +19,15 @@ pub enum DoftaError {
     #[error("Order error: {0}")]
     Order(#[from] OrderError),
     
+    #[error("Dispute error: {0}")]
+    Dispute(#[from] DisputeError),
+    
     #[error("Transaction error: {0}")]
     Transaction(#[from] TransactionError),
     
+    #[error("Fee error: {0}")]
+    Fee(#[from] FeeError),
+    
     #[error("Governance error: {0}")]
     Governance(#[from] GovernanceError),
     
@@ -33,13 +46,207 @@ pub enum DoftaError {
     #[error("Report error: {0}")]
     Report(#[from] ReportError),
     
+    #[error("Audit error: {0}")]
+    Audit(#[from] AuditError),
+    
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
     
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    
+    #[error("Not found: {0}")]
+    NotFound(String),
+    
+    #[error("Too many requests, retry in {0} seconds")]
+    TooManyRequests(u64),
+    
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+impl DoftaError {
+    /// HTTP status code returned to clients for this error
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            DoftaError::Auth(e) => match e {
+                AuthError::InvalidCredentials
+                | AuthError::InvalidToken
+                | AuthError::TokenExpired
+                | AuthError::TokenRevoked => StatusCode::UNAUTHORIZED,
+                AuthError::RegistrationFailed(_) | AuthError::Validation(_) => StatusCode::BAD_REQUEST,
+                AuthError::MemberNotFound => StatusCode::NOT_FOUND,
+                AuthError::HashingFailed | AuthError::Encryption(_) | AuthError::Database(_) => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            },
+            DoftaError::Listing(e) => match e {
+                ListingError::InvalidData(_)
+                | ListingError::Validation(_)
+                | ListingError::InvalidBatch(_) => StatusCode::BAD_REQUEST,
+                ListingError::NotFound | ListingError::SavedSearchNotFound => StatusCode::NOT_FOUND,
+                ListingError::Unauthorized => StatusCode::FORBIDDEN,
+                ListingError::AlreadyExists
+                | ListingError::Conflict
+                | ListingError::InvalidAvailabilityTransition(_) => StatusCode::CONFLICT,
+                ListingError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::Order(e) => match e {
+                OrderError::InvalidData(_) | OrderError::Validation(_) | OrderError::SelfOrder => {
+                    StatusCode::BAD_REQUEST
+                }
+                OrderError::NotFound => StatusCode::NOT_FOUND,
+                OrderError::ProductUnavailable
+                | OrderError::InsufficientQuantity
+                | OrderError::InvalidStatusTransition(_)
+                | OrderError::DuplicateOrder
+                | OrderError::EscrowAlreadyLinked => StatusCode::CONFLICT,
+                OrderError::Unauthorized => StatusCode::FORBIDDEN,
+                OrderError::Transaction(_) | OrderError::Notification(_) | OrderError::Database(_) => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            },
+            DoftaError::Dispute(e) => match e {
+                DisputeError::InvalidData(_) => StatusCode::BAD_REQUEST,
+                DisputeError::NotFound | DisputeError::OrderNotFound => StatusCode::NOT_FOUND,
+                DisputeError::Unauthorized => StatusCode::FORBIDDEN,
+                DisputeError::InvalidStatusTransition(_) | DisputeError::AlreadyResolved => StatusCode::CONFLICT,
+                DisputeError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::Transaction(e) => match e {
+                TransactionError::NotFound => StatusCode::NOT_FOUND,
+                TransactionError::InvalidAmount => StatusCode::BAD_REQUEST,
+                TransactionError::RollbackFailed(_) => StatusCode::CONFLICT,
+                TransactionError::Failed(_) | TransactionError::Database(_) => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            },
+            DoftaError::Fee(e) => match e {
+                FeeError::InvalidData(_) => StatusCode::BAD_REQUEST,
+                FeeError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::Governance(e) => match e {
+                GovernanceError::ProposalNotFound => StatusCode::NOT_FOUND,
+                GovernanceError::InvalidData(_) => StatusCode::BAD_REQUEST,
+                GovernanceError::VotingEnded
+                | GovernanceError::VotingInProgress
+                | GovernanceError::AlreadyFinalized
+                | GovernanceError::AlreadyVoted => StatusCode::CONFLICT,
+                GovernanceError::Unauthorized => StatusCode::FORBIDDEN,
+                GovernanceError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::Reputation(e) => match e {
+                ReputationError::NotFound | ReputationError::TransactionNotFound => StatusCode::NOT_FOUND,
+                ReputationError::InvalidRating(_) => StatusCode::BAD_REQUEST,
+                ReputationError::TransactionNotCompleted | ReputationError::AlreadyRated => {
+                    StatusCode::CONFLICT
+                }
+                ReputationError::NotParticipant => StatusCode::FORBIDDEN,
+                ReputationError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::Search(e) => match e {
+                SearchError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+                SearchError::Failed(_) | SearchError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::Notification(e) => match e {
+                NotificationError::InvalidType | NotificationError::InvalidData(_) => {
+                    StatusCode::BAD_REQUEST
+                }
+                NotificationError::RecipientNotFound | NotificationError::NotFound => {
+                    StatusCode::NOT_FOUND
+                }
+                NotificationError::SendFailed(_) | NotificationError::Database(_) => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            },
+            DoftaError::Security(e) => match e {
+                SecurityError::AccessDenied => StatusCode::FORBIDDEN,
+                SecurityError::MemberNotFound => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::Report(e) => match e {
+                ReportError::InvalidDateRange => StatusCode::BAD_REQUEST,
+                ReportError::Unauthorized => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::Audit(e) => match e {
+                AuditError::InvalidDateRange => StatusCode::BAD_REQUEST,
+                AuditError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            DoftaError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            DoftaError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            DoftaError::Forbidden(_) => StatusCode::FORBIDDEN,
+            DoftaError::NotFound(_) => StatusCode::NOT_FOUND,
+            DoftaError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            DoftaError::Database(_) | DoftaError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+    
+    /// Machine-readable error code returned to clients
+    pub fn error_code(&self) -> &'static str {
+        match self.status_code() {
+            StatusCode::BAD_REQUEST => "invalid_input",
+            StatusCode::UNAUTHORIZED => "unauthorized",
+            StatusCode::FORBIDDEN => "forbidden",
+            StatusCode::NOT_FOUND => "not_found",
+            StatusCode::CONFLICT => "conflict",
+            StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+            _ => "internal_error",
+        }
+    }
+    
+    /// Field-level problems behind a validation failure, if that's what this is
+    pub fn validation_errors(&self) -> Option<&[ValidationError]> {
+        match self {
+            DoftaError::Auth(AuthError::Validation(errors))
+            | DoftaError::Listing(ListingError::Validation(errors))
+            | DoftaError::Order(OrderError::Validation(errors)) => Some(errors),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for DoftaError {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+        
+        // Server-side failures may carry SQL or other internals; log them and
+        // return a generic message instead
+        let message = if status.is_server_error() {
+            tracing::error!("Request failed: {}", self);
+            "Internal server error".to_string()
+        } else {
+            self.to_string()
+        };
+        
+        // Validation failures also list each field so forms can highlight them
+        let body = match self.validation_errors() {
+            Some(errors) => Json(json!({
+                "error": message,
+                "code": self.error_code(),
+                "errors": errors,
+            })),
+            None => Json(json!({
+                "error": message,
+                "code": self.error_code(),
+            })),
+        };
+        
+        let mut response = (status, body).into_response();
+        if let DoftaError::TooManyRequests(retry_after) = self {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        
+        response
+    }
+}
+
 /// Authentication module errors
 #[derive(Debug, Error)]
 pub enum AuthError {
@@ -52,14 +259,26 @@ pub enum AuthError {
     #[error("Token expired")]
     TokenExpired,
     
+    #[error("Token revoked")]
+    TokenRevoked,
+    
     #[error("Registration failed: {0}")]
     RegistrationFailed(String),
     
+    #[error("Registration failed: {}", format_validation_errors(.0))]
+    Validation(Vec<ValidationError>),
+    
     #[error("Password hashing failed")]
     HashingFailed,
     
     #[error("Member not found")]
     MemberNotFound,
+    
+    #[error("Profile encryption failed: {0}")]
+    Encryption(#[from] SecurityError),
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
 }
 
 /// Product listing module errors
@@ -68,6 +287,9 @@ pub enum ListingError {
     #[error("Invalid listing data: {0}")]
     InvalidData(String),
     
+    #[error("Invalid listing data: {}", format_validation_errors(.0))]
+    Validation(Vec<ValidationError>),
+    
     #[error("Listing not found")]
     NotFound,
     
@@ -76,6 +298,71 @@ pub enum ListingError {
     
     #[error("Listing already exists")]
     AlreadyExists,
+    
+    #[error("Invalid availability transition: {0}")]
+    InvalidAvailabilityTransition(String),
+    
+    #[error("Listing was changed by someone else; reload and try again")]
+    Conflict,
+    
+    #[error("Invalid listings in batch: {}", format_batch_errors(.0))]
+    InvalidBatch(Vec<BatchRowError>),
+    
+    #[error("Saved search not found")]
+    SavedSearchNotFound,
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// A row of a bulk request that failed validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchRowError {
+    /// Position of the row in the request, from 0
+    pub index: usize,
+    pub message: String,
+}
+
+/// A problem with one field of a submitted form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationError {
+    /// Name of the request field, e.g. `unit_price`
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        ValidationError {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Every failing field, or `Ok` when there are none
+pub fn validation_result(errors: Vec<ValidationError>) -> std::result::Result<(), Vec<ValidationError>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn format_validation_errors(errors: &[ValidationError]) -> String {
+    errors
+        .iter()
+        .map(|error| format!("{}: {}", error.field, error.message))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn format_batch_errors(errors: &[BatchRowError]) -> String {
+    errors
+        .iter()
+        .map(|error| format!("[{}] {}", error.index, error.message))
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 /// Order processing module errors
@@ -84,6 +371,9 @@ pub enum OrderError {
     #[error("Invalid order data: {0}")]
     InvalidData(String),
     
+    #[error("Invalid order data: {}", format_validation_errors(.0))]
+    Validation(Vec<ValidationError>),
+    
     #[error("Order not found")]
     NotFound,
     
@@ -93,11 +383,66 @@ pub enum OrderError {
     #[error("Insufficient quantity")]
     InsufficientQuantity,
     
+    #[error("Cannot order your own listing")]
+    SelfOrder,
+    
+    #[error("An identical order was placed moments ago; check your pending orders before ordering again")]
+    DuplicateOrder,
+    
+    #[error("Order is already linked to a different escrow order")]
+    EscrowAlreadyLinked,
+    
     #[error("Invalid status transition: {0}")]
     InvalidStatusTransition(String),
     
     #[error("Unauthorized access")]
     Unauthorized,
+    
+    #[error("Transaction error: {0}")]
+    Transaction(#[from] TransactionError),
+    
+    #[error("Notification error: {0}")]
+    Notification(#[from] NotificationError),
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Dispute module errors
+#[derive(Debug, Error)]
+pub enum DisputeError {
+    #[error("Invalid dispute data: {0}")]
+    InvalidData(String),
+    
+    #[error("Dispute not found")]
+    NotFound,
+    
+    #[error("Order not found")]
+    OrderNotFound,
+    
+    #[error("Unauthorized access")]
+    Unauthorized,
+    
+    #[error("Invalid status transition: {0}")]
+    InvalidStatusTransition(String),
+    
+    #[error("Dispute already resolved")]
+    AlreadyResolved,
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<OrderError> for DisputeError {
+    fn from(e: OrderError) -> Self {
+        match e {
+            OrderError::NotFound => DisputeError::OrderNotFound,
+            OrderError::Unauthorized => DisputeError::Unauthorized,
+            OrderError::InvalidStatusTransition(message) => DisputeError::InvalidStatusTransition(message),
+            OrderError::Database(e) => DisputeError::Database(e),
+            other => DisputeError::InvalidData(other.to_string()),
+        }
+    }
 }
 
 /// Transaction module errors
@@ -114,6 +459,37 @@ pub enum TransactionError {
     
     #[error("Rollback failed: {0}")]
     RollbackFailed(String),
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Cooperative fee module errors
+#[derive(Debug, Error)]
+pub enum FeeError {
+    #[error("Invalid fee: {0}")]
+    InvalidData(String),
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<FeeError> for TransactionError {
+    fn from(e: FeeError) -> Self {
+        match e {
+            FeeError::Database(e) => TransactionError::Database(e),
+            other => TransactionError::Failed(other.to_string()),
+        }
+    }
+}
+
+impl From<FeeError> for GovernanceError {
+    fn from(e: FeeError) -> Self {
+        match e {
+            FeeError::InvalidData(message) => GovernanceError::InvalidData(message),
+            FeeError::Database(e) => GovernanceError::Database(e),
+        }
+    }
 }
 
 /// Governance module errors
@@ -128,11 +504,20 @@ pub enum GovernanceError {
     #[error("Voting period ended")]
     VotingEnded,
     
+    #[error("Voting period has not ended")]
+    VotingInProgress,
+    
+    #[error("Proposal already finalized")]
+    AlreadyFinalized,
+    
     #[error("Already voted")]
     AlreadyVoted,
     
     #[error("Unauthorized access")]
     Unauthorized,
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
 }
 
 /// Reputation module errors
@@ -149,6 +534,15 @@ pub enum ReputationError {
     
     #[error("Already rated")]
     AlreadyRated,
+    
+    #[error("Transaction not found")]
+    TransactionNotFound,
+    
+    #[error("Not a participant in this transaction")]
+    NotParticipant,
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
 }
 
 /// Search module errors
@@ -159,6 +553,9 @@ pub enum SearchError {
     
     #[error("Search failed: {0}")]
     Failed(String),
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
 }
 
 /// Notification module errors
@@ -172,6 +569,15 @@ pub enum NotificationError {
     
     #[error("Recipient not found")]
     RecipientNotFound,
+    
+    #[error("Invalid notification data: {0}")]
+    InvalidData(String),
+    
+    #[error("Notification not found")]
+    NotFound,
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
 }
 
 /// Security module errors
@@ -191,6 +597,12 @@ pub enum SecurityError {
     
     #[error("Data deletion failed: {0}")]
     DeletionFailed(String),
+    
+    #[error("Member not found")]
+    MemberNotFound,
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
 }
 
 /// Reporting module errors
@@ -207,7 +619,96 @@ pub enum ReportError {
     
     #[error("Unauthorized access")]
     Unauthorized,
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Admin audit log errors
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("Invalid date range")]
+    InvalidDateRange,
+    
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
 }
 
 /// Result type alias for DOFTA operations
 pub type Result<T> = std::result::Result<T, DoftaError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    async fn response_body(error: DoftaError) -> (StatusCode, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+    
+    #[test]
+    fn test_status_codes() {
+        assert_eq!(DoftaError::Unauthorized("x".to_string()).status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(DoftaError::Forbidden("x".to_string()).status_code(), StatusCode::FORBIDDEN);
+        assert_eq!(DoftaError::NotFound("x".to_string()).status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(DoftaError::InvalidInput("x".to_string()).status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(DoftaError::from(ListingError::NotFound).status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            DoftaError::from(ListingError::InvalidData("x".to_string())).status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(DoftaError::from(AuthError::InvalidCredentials).status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(DoftaError::Database(sqlx::Error::RowNotFound).status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    
+    #[tokio::test]
+    async fn test_client_error_body() {
+        let (status, body) = response_body(DoftaError::Forbidden("Not your listing".to_string())).await;
+        
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "forbidden");
+        assert_eq!(body["error"], "Forbidden: Not your listing");
+    }
+    
+    #[tokio::test]
+    async fn test_database_error_does_not_leak_details() {
+        let error = DoftaError::Database(sqlx::Error::Protocol(
+            "SELECT * FROM members WHERE password_hash = ...".to_string(),
+        ));
+        let (status, body) = response_body(error).await;
+        
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["code"], "internal_error");
+        assert_eq!(body["error"], "Internal server error");
+        
+        let (_, body) = response_body(ListingError::Database(sqlx::Error::PoolTimedOut).into()).await;
+        assert_eq!(body["error"], "Internal server error");
+    }
+    
+    #[tokio::test]
+    async fn test_validation_error_body_lists_fields() {
+        let error = ListingError::Validation(vec![
+            ValidationError::new("name", "Product name cannot be empty"),
+            ValidationError::new("unit_price", "Unit price must be positive"),
+        ]);
+        let (status, body) = response_body(error.into()).await;
+        
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "invalid_input");
+        assert_eq!(
+            body["errors"],
+            json!([
+                {"field": "name", "message": "Product name cannot be empty"},
+                {"field": "unit_price", "message": "Unit price must be positive"},
+            ])
+        );
+        
+        // Other errors carry no field list
+        let (_, body) = response_body(ListingError::NotFound.into()).await;
+        assert!(body.get("errors").is_none());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::error::FeeError;
+use crate::models::{Category, CategoryFee};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Fee percentage for a category
+///
+/// A category override wins, then the cooperative-wide fee set by governance,
+/// then `default_fee` from the configuration.
+pub async fn resolve_fee(
+    conn: &mut PgConnection,
+    category: Category,
+    default_fee: Decimal,
+) -> Result<Decimal, FeeError> {
+    let fee = sqlx::query_scalar::<_, Option<Decimal>>(
+        "SELECT COALESCE(
+             (SELECT fee_percentage FROM category_fees WHERE category = $1),
+             (SELECT fee_percentage FROM fee_settings)
+         )"
+    )
+    .bind(category.to_string())
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    Ok(fee.unwrap_or(default_fee))
+}
+
+/// Fee percentage for a listing, based on its category
+///
+/// Falls back to `default_fee` if the listing is gone or its category is unknown.
+pub async fn resolve_listing_fee(
+    conn: &mut PgConnection,
+    listing_id: Uuid,
+    default_fee: Decimal,
+) -> Result<Decimal, FeeError> {
+    let category = sqlx::query_scalar::<_, String>(
+        "SELECT category FROM product_listings WHERE id = $1"
+    )
+    .bind(listing_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    
+    match category.and_then(|category| category.parse::<Category>().ok()) {
+        Some(category) => resolve_fee(conn, category, default_fee).await,
+        None => Ok(default_fee),
+    }
+}
+
+/// Set the cooperative-wide fee, replacing the configured default
+///
+/// Takes a connection so a passed proposal can apply it in the same transaction
+/// that finalizes the vote.
+pub async fn set_global_fee(
+    conn: &mut PgConnection,
+    fee_percentage: Decimal,
+    proposal_id: Option<Uuid>,
+) -> Result<(), FeeError> {
+    validate_fee_percentage(fee_percentage)?;
+    
+    sqlx::query(
+        "INSERT INTO fee_settings (id, fee_percentage, proposal_id, updated_at)
+         VALUES (TRUE, $1, $2, $3)
+         ON CONFLICT (id) DO UPDATE
+         SET fee_percentage = EXCLUDED.fee_percentage,
+             proposal_id = EXCLUDED.proposal_id,
+             updated_at = EXCLUDED.updated_at"
+    )
+    .bind(fee_percentage)
+    .bind(proposal_id)
+    .bind(Utc::now())
+    .execute(conn)
+    .await?;
+    
+    Ok(())
+}
+
+/// Set or replace the fee override for a category (admin action)
+pub async fn set_category_fee(
+    pool: &PgPool,
+    category: Category,
+    fee_percentage: Decimal,
+    admin_id: Uuid,
+) -> Result<CategoryFee, FeeError> {
+    validate_fee_percentage(fee_percentage)?;
+    
+    let fee = sqlx::query_as::<_, CategoryFee>(
+        "INSERT INTO category_fees (category, fee_percentage, updated_by, updated_at)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (category) DO UPDATE
+         SET fee_percentage = EXCLUDED.fee_percentage,
+             updated_by = EXCLUDED.updated_by,
+             updated_at = EXCLUDED.updated_at
+         RETURNING category, fee_percentage, updated_by, updated_at"
+    )
+    .bind(category.to_string())
+    .bind(fee_percentage)
+    .bind(admin_id)
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(fee)
+}
+
+/// Validate a fee percentage, which must lie between 0 and 1 like the global fee
+pub fn validate_fee_percentage(fee_percentage: Decimal) -> Result<(), FeeError> {
+    if fee_percentage < Decimal::ZERO || fee_percentage > Decimal::ONE {
+        return Err(FeeError::InvalidData("Fee percentage must be between 0 and 1".to_string()));
+    }
+    
+    // Stored as DECIMAL(5,4)
+    if fee_percentage.normalize().scale() > 4 {
+        return Err(FeeError::InvalidData("Fee percentage cannot have more than 4 decimal places".to_string()));
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{insert_test_listing, insert_test_member, test_config};
+    
+    #[test]
+    fn test_validate_fee_percentage() {
+        assert!(validate_fee_percentage(Decimal::ZERO).is_ok());
+        assert!(validate_fee_percentage(Decimal::ONE).is_ok());
+        assert!(validate_fee_percentage(Decimal::new(25, 3)).is_ok());
+        assert!(matches!(validate_fee_percentage(Decimal::new(-1, 2)), Err(FeeError::InvalidData(_))));
+        assert!(matches!(validate_fee_percentage(Decimal::new(101, 2)), Err(FeeError::InvalidData(_))));
+        assert!(matches!(validate_fee_percentage(Decimal::new(12345, 5)), Err(FeeError::InvalidData(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_resolve_fee_override_and_fallback(pool: PgPool) {
+        let admin = insert_test_member(&pool).await;
+        let default_fee = test_config().cooperative_fee_percentage;
+        let staple_fee = Decimal::new(1, 2);
+        
+        set_category_fee(&pool, Category::Grains, Decimal::new(2, 2), admin).await.unwrap();
+        let fee = set_category_fee(&pool, Category::Grains, staple_fee, admin).await.unwrap();
+        assert_eq!(fee.category, "Grains");
+        assert_eq!(fee.updated_by, admin);
+        
+        let mut conn = pool.acquire().await.unwrap();
+        assert_eq!(resolve_fee(&mut conn, Category::Grains, default_fee).await.unwrap(), staple_fee);
+        assert_eq!(resolve_fee(&mut conn, Category::Fruits, default_fee).await.unwrap(), default_fee);
+        
+        let result = set_category_fee(&pool, Category::Fruits, Decimal::new(2, 0), admin).await;
+        assert!(matches!(result, Err(FeeError::InvalidData(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_global_fee_sits_between_override_and_default(pool: PgPool) {
+        let admin = insert_test_member(&pool).await;
+        let default_fee = test_config().cooperative_fee_percentage;
+        set_category_fee(&pool, Category::Grains, Decimal::new(1, 2), admin).await.unwrap();
+        
+        let mut conn = pool.acquire().await.unwrap();
+        set_global_fee(&mut conn, Decimal::new(3, 2), None).await.unwrap();
+        set_global_fee(&mut conn, Decimal::new(4, 2), None).await.unwrap();
+        
+        assert_eq!(resolve_fee(&mut conn, Category::Fruits, default_fee).await.unwrap(), Decimal::new(4, 2));
+        assert_eq!(resolve_fee(&mut conn, Category::Grains, default_fee).await.unwrap(), Decimal::new(1, 2));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_resolve_listing_fee(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, member).await;
+        let default_fee = test_config().cooperative_fee_percentage;
+        let mut conn = pool.acquire().await.unwrap();
+        
+        assert_eq!(resolve_listing_fee(&mut conn, listing_id, default_fee).await.unwrap(), default_fee);
+        
+        set_category_fee(&pool, Category::Vegetables, Decimal::ZERO, member).await.unwrap();
+        assert_eq!(resolve_listing_fee(&mut conn, listing_id, default_fee).await.unwrap(), Decimal::ZERO);
+        
+        assert_eq!(resolve_listing_fee(&mut conn, Uuid::new_v4(), default_fee).await.unwrap(), default_fee);
+    }
+}
@@ -0,0 +1,890 @@
+use crate::error::GovernanceError;
+use crate::fees;
+use crate::members;
+use crate::models::{Proposal, ProposalComment, ProposalKind, ProposalStatus, VoteType};
+use chrono::{Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Maximum length of a proposal comment, in characters
+pub const MAX_COMMENT_LENGTH: usize = 2000;
+
+/// Create a new governance proposal open for voting
+///
+/// `quorum` is the fraction of all members that must vote for the outcome to count.
+/// With `weighted_voting`, each vote counts in proportion to the voter's reputation.
+/// `kind` is the effect applied if the proposal passes.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_proposal(
+    pool: &PgPool,
+    creator_id: Uuid,
+    title: &str,
+    description: &str,
+    voting_duration: Duration,
+    quorum: Decimal,
+    weighted_voting: bool,
+    kind: ProposalKind,
+) -> Result<Proposal, GovernanceError> {
+    validate_proposal_data(title, description, voting_duration)?;
+    
+    if quorum < Decimal::ZERO || quorum > Decimal::ONE {
+        return Err(GovernanceError::InvalidData("Quorum must be between 0 and 1".to_string()));
+    }
+    
+    if let ProposalKind::FeeChange { new_fee } = kind {
+        fees::validate_fee_percentage(new_fee)?;
+    }
+    
+    let proposal_id = Uuid::new_v4();
+    let now = Utc::now();
+    let voting_ends_at = now + voting_duration;
+    let status = ProposalStatus::Active.to_string();
+    
+    let proposal = sqlx::query_as::<_, Proposal>(
+        "INSERT INTO proposals (id, creator_id, title, description, status, votes_for, votes_against, weighted_voting, quorum, kind, created_at, voting_ends_at)
+         VALUES ($1, $2, $3, $4, $5, 0, 0, $6, $7, $8, $9, $10)
+         RETURNING id, creator_id, title, description, status, votes_for, votes_against, weighted_voting, weighted_for, weighted_against, quorum, kind, created_at, voting_ends_at"
+    )
+    .bind(proposal_id)
+    .bind(creator_id)
+    .bind(title.trim())
+    .bind(description.trim())
+    .bind(&status)
+    .bind(weighted_voting)
+    .bind(quorum)
+    .bind(Json(kind))
+    .bind(now)
+    .bind(voting_ends_at)
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(proposal)
+}
+
+/// Get a proposal by ID
+pub async fn get_proposal(
+    pool: &PgPool,
+    proposal_id: Uuid,
+) -> Result<Proposal, GovernanceError> {
+    let proposal = sqlx::query_as::<_, Proposal>(
+        "SELECT id, creator_id, title, description, status, votes_for, votes_against, weighted_voting, weighted_for, weighted_against, quorum, kind, created_at, voting_ends_at
+         FROM proposals
+         WHERE id = $1"
+    )
+    .bind(proposal_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| GovernanceError::ProposalNotFound)?
+    .ok_or(GovernanceError::ProposalNotFound)?;
+    
+    Ok(proposal)
+}
+
+/// Get all proposals still open for voting, closing soonest first
+pub async fn get_active_proposals(pool: &PgPool) -> Result<Vec<Proposal>, GovernanceError> {
+    let proposals = sqlx::query_as::<_, Proposal>(
+        "SELECT id, creator_id, title, description, status, votes_for, votes_against, weighted_voting, weighted_for, weighted_against, quorum, kind, created_at, voting_ends_at
+         FROM proposals
+         WHERE status = $1
+         ORDER BY voting_ends_at ASC"
+    )
+    .bind(ProposalStatus::Active.to_string())
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(proposals)
+}
+
+/// Cast a member's vote on an active proposal
+///
+/// The vote insert and tally update happen in one transaction, and each member
+/// may vote only once per proposal. Weighted proposals add the voter's
+/// reputation weight to the weighted tally; otherwise every vote weighs 1.
+pub async fn cast_vote(
+    pool: &PgPool,
+    proposal_id: Uuid,
+    member_id: Uuid,
+    vote_type: VoteType,
+) -> Result<Proposal, GovernanceError> {
+    let mut tx = pool.begin().await?;
+    
+    // Lock the proposal row so concurrent votes serialize on the tally
+    let proposal = sqlx::query_as::<_, Proposal>(
+        "SELECT id, creator_id, title, description, status, votes_for, votes_against, weighted_voting, weighted_for, weighted_against, quorum, kind, created_at, voting_ends_at
+         FROM proposals
+         WHERE id = $1
+         FOR UPDATE"
+    )
+    .bind(proposal_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(GovernanceError::ProposalNotFound)?;
+    
+    if proposal.status != ProposalStatus::Active.to_string() || Utc::now() >= proposal.voting_ends_at {
+        return Err(GovernanceError::VotingEnded);
+    }
+    
+    sqlx::query(
+        "INSERT INTO votes (proposal_id, member_id, vote_type, created_at)
+         VALUES ($1, $2, $3, $4)"
+    )
+    .bind(proposal_id)
+    .bind(member_id)
+    .bind(vote_type.to_string())
+    .bind(Utc::now())
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => GovernanceError::AlreadyVoted,
+        e => GovernanceError::Database(e),
+    })?;
+    
+    let weight = if proposal.weighted_voting {
+        let (average,): (Option<Decimal>,) = sqlx::query_as("SELECT AVG(score) FROM ratings WHERE rated_id = $1")
+            .bind(member_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        vote_weight(average)
+    } else {
+        Decimal::ONE
+    };
+    
+    let (tally_column, weighted_column) = match vote_type {
+        VoteType::For => ("votes_for", "weighted_for"),
+        VoteType::Against => ("votes_against", "weighted_against"),
+    };
+    let query = format!(
+        "UPDATE proposals SET {column} = {column} + 1, {weighted} = {weighted} + $2 WHERE id = $1
+         RETURNING id, creator_id, title, description, status, votes_for, votes_against, weighted_voting, weighted_for, weighted_against, quorum, kind, created_at, voting_ends_at",
+        column = tally_column,
+        weighted = weighted_column
+    );
+    
+    let proposal = sqlx::query_as::<_, Proposal>(&query)
+        .bind(proposal_id)
+        .bind(weight)
+        .fetch_one(&mut *tx)
+        .await?;
+    
+    tx.commit().await?;
+    
+    Ok(proposal)
+}
+
+/// Close voting on a proposal once its deadline has passed and record the outcome
+pub async fn finalize_proposal(
+    pool: &PgPool,
+    proposal_id: Uuid,
+) -> Result<Proposal, GovernanceError> {
+    let mut tx = pool.begin().await?;
+    
+    let proposal = sqlx::query_as::<_, Proposal>(
+        "SELECT id, creator_id, title, description, status, votes_for, votes_against, weighted_voting, weighted_for, weighted_against, quorum, kind, created_at, voting_ends_at
+         FROM proposals
+         WHERE id = $1
+         FOR UPDATE"
+    )
+    .bind(proposal_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(GovernanceError::ProposalNotFound)?;
+    
+    if proposal.status != ProposalStatus::Active.to_string() {
+        return Err(GovernanceError::AlreadyFinalized);
+    }
+    
+    if Utc::now() < proposal.voting_ends_at {
+        return Err(GovernanceError::VotingInProgress);
+    }
+    
+    // Erased accounts can't vote, so they don't count towards the quorum
+    let (member_count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM members WHERE deleted_at IS NULL")
+        .fetch_one(&mut *tx)
+        .await?;
+    
+    // Quorum counts voters, so it always uses the raw tally
+    let outcome = weighted_proposal_outcome(
+        proposal.votes_for,
+        proposal.votes_against,
+        proposal.weighted_for,
+        proposal.weighted_against,
+        required_votes(proposal.quorum, member_count),
+    );
+    
+    if outcome == ProposalStatus::Passed {
+        apply_outcome(&mut tx, &proposal).await?;
+    }
+    
+    let proposal = sqlx::query_as::<_, Proposal>(
+        "UPDATE proposals SET status = $1 WHERE id = $2
+         RETURNING id, creator_id, title, description, status, votes_for, votes_against, weighted_voting, weighted_for, weighted_against, quorum, kind, created_at, voting_ends_at"
+    )
+    .bind(outcome.to_string())
+    .bind(proposal_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    
+    tx.commit().await?;
+    
+    Ok(proposal)
+}
+
+/// Carry out what a passed proposal asks for, in the finalizing transaction
+async fn apply_outcome(conn: &mut PgConnection, proposal: &Proposal) -> Result<(), GovernanceError> {
+    match proposal.kind.0 {
+        ProposalKind::General => {}
+        ProposalKind::FeeChange { new_fee } => {
+            fees::set_global_fee(&mut *conn, new_fee, Some(proposal.id)).await?;
+        }
+        ProposalKind::MemberRemoval { member_id } => {
+            // Nothing to do if the member already left on their own
+            if !members::erase_member(&mut *conn, member_id).await? {
+                tracing::info!("Member {} removed by proposal {} was already deleted", member_id, proposal.id);
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Number of votes needed to meet a quorum fraction of `member_count`, rounded up
+pub fn required_votes(quorum: Decimal, member_count: i64) -> i64 {
+    (quorum * Decimal::from(member_count))
+        .ceil()
+        .to_i64()
+        .unwrap_or(i64::MAX)
+}
+
+/// Vote weight for a member with the given average rating
+///
+/// The average is clamped to 1–5 and mapped linearly onto 0.5–1.5, so a
+/// middling rating of 3 weighs the same as a member without any ratings.
+pub fn vote_weight(average_rating: Option<Decimal>) -> Decimal {
+    match average_rating {
+        Some(average) => {
+            let average = average.clamp(Decimal::ONE, Decimal::from(5));
+            (Decimal::new(5, 1) + (average - Decimal::ONE) / Decimal::from(4)).round_dp(2)
+        }
+        None => Decimal::ONE,
+    }
+}
+
+/// Determine the final status of a proposal from its vote tally
+///
+/// Proposals without any votes expire; proposals below quorum are rejected even
+/// when the votes for lead.
+pub fn proposal_outcome(votes_for: i32, votes_against: i32, required_votes: i64) -> ProposalStatus {
+    weighted_proposal_outcome(
+        votes_for,
+        votes_against,
+        Decimal::from(votes_for),
+        Decimal::from(votes_against),
+        required_votes,
+    )
+}
+
+/// Determine the final status of a proposal from its raw and weighted tallies
+///
+/// The raw counts decide whether anyone voted and whether quorum was met; the
+/// weighted totals decide which side wins.
+pub fn weighted_proposal_outcome(
+    votes_for: i32,
+    votes_against: i32,
+    weighted_for: Decimal,
+    weighted_against: Decimal,
+    required_votes: i64,
+) -> ProposalStatus {
+    let total_votes = i64::from(votes_for) + i64::from(votes_against);
+    
+    if total_votes == 0 {
+        ProposalStatus::Expired
+    } else if total_votes >= required_votes && weighted_for > weighted_against {
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Rejected
+    }
+}
+
+/// Validate proposal data before creation
+pub fn validate_proposal_data(
+    title: &str,
+    description: &str,
+    voting_duration: Duration,
+) -> Result<(), GovernanceError> {
+    if title.trim().is_empty() {
+        return Err(GovernanceError::InvalidData("Proposal title cannot be empty".to_string()));
+    }
+    
+    if description.trim().is_empty() {
+        return Err(GovernanceError::InvalidData("Proposal description cannot be empty".to_string()));
+    }
+    
+    if voting_duration <= Duration::zero() {
+        return Err(GovernanceError::InvalidData("Voting duration must be positive".to_string()));
+    }
+    
+    Ok(())
+}
+
+/// Add a comment to a proposal's discussion thread
+///
+/// Comments are accepted whatever the proposal's status, so the discussion
+/// stays attached to the decision after voting closes.
+pub async fn add_comment(
+    pool: &PgPool,
+    proposal_id: Uuid,
+    member_id: Uuid,
+    body: &str,
+) -> Result<ProposalComment, GovernanceError> {
+    validate_comment_body(body)?;
+    get_proposal(pool, proposal_id).await?;
+    
+    let comment = sqlx::query_as::<_, ProposalComment>(
+        "INSERT INTO proposal_comments (id, proposal_id, member_id, body, created_at)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, proposal_id, member_id, body, created_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(proposal_id)
+    .bind(member_id)
+    .bind(body.trim())
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(comment)
+}
+
+/// Get a proposal's comments, oldest first
+pub async fn get_comments(
+    pool: &PgPool,
+    proposal_id: Uuid,
+) -> Result<Vec<ProposalComment>, GovernanceError> {
+    get_proposal(pool, proposal_id).await?;
+    
+    let comments = sqlx::query_as::<_, ProposalComment>(
+        "SELECT id, proposal_id, member_id, body, created_at
+         FROM proposal_comments
+         WHERE proposal_id = $1
+         ORDER BY created_at ASC, id"
+    )
+    .bind(proposal_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(comments)
+}
+
+/// Validate a comment body before it is stored
+pub fn validate_comment_body(body: &str) -> Result<(), GovernanceError> {
+    let body = body.trim();
+    
+    if body.is_empty() {
+        return Err(GovernanceError::InvalidData("Comment cannot be empty".to_string()));
+    }
+    
+    if body.chars().count() > MAX_COMMENT_LENGTH {
+        return Err(GovernanceError::InvalidData(format!(
+            "Comment cannot be longer than {} characters",
+            MAX_COMMENT_LENGTH
+        )));
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth;
+    use crate::models::{Category, Member, Role, TransactionStatus};
+    use crate::reputation;
+    use crate::test_utils::{insert_test_member, insert_test_transaction, test_config, test_member};
+    
+    #[test]
+    fn test_validate_proposal_data_valid() {
+        let result = validate_proposal_data("New storage shed", "Build a shared cold store", Duration::days(7));
+        
+        assert!(result.is_ok());
+    }
+    
+    #[test]
+    fn test_validate_proposal_data_empty_title() {
+        let result = validate_proposal_data("   ", "Build a shared cold store", Duration::days(7));
+        
+        assert!(matches!(result, Err(GovernanceError::InvalidData(_))));
+    }
+    
+    #[test]
+    fn test_validate_proposal_data_empty_description() {
+        let result = validate_proposal_data("New storage shed", "", Duration::days(7));
+        
+        assert!(matches!(result, Err(GovernanceError::InvalidData(_))));
+    }
+    
+    #[test]
+    fn test_validate_proposal_data_non_positive_duration() {
+        let result = validate_proposal_data("New storage shed", "Build a shared cold store", Duration::zero());
+        
+        assert!(matches!(result, Err(GovernanceError::InvalidData(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_proposal_is_active(pool: PgPool) {
+        let creator = insert_test_member(&pool).await;
+        
+        let proposal = create_proposal(
+            &pool,
+            creator,
+            "New storage shed",
+            "Build a shared cold store",
+            Duration::days(7),
+            Decimal::new(10, 2),
+            false,
+            ProposalKind::General,
+        )
+        .await
+        .unwrap();
+        
+        assert_eq!(proposal.status, ProposalStatus::Active.to_string());
+        assert_eq!(proposal.votes_for, 0);
+        assert_eq!(proposal.votes_against, 0);
+        assert_eq!(proposal.voting_ends_at - proposal.created_at, Duration::days(7));
+        
+        let active = get_active_proposals(&pool).await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, proposal.id);
+    }
+    
+    #[test]
+    fn test_proposal_outcome_passed() {
+        assert_eq!(proposal_outcome(3, 1, 0), ProposalStatus::Passed);
+    }
+    
+    #[test]
+    fn test_proposal_outcome_rejected() {
+        assert_eq!(proposal_outcome(1, 3, 0), ProposalStatus::Rejected);
+        // A tie does not pass
+        assert_eq!(proposal_outcome(2, 2, 0), ProposalStatus::Rejected);
+    }
+    
+    #[test]
+    fn test_proposal_outcome_expired_without_votes() {
+        assert_eq!(proposal_outcome(0, 0, 0), ProposalStatus::Expired);
+    }
+    
+    #[test]
+    fn test_proposal_outcome_depends_on_quorum() {
+        // Same tally, different quorum
+        assert_eq!(proposal_outcome(2, 0, 2), ProposalStatus::Passed);
+        assert_eq!(proposal_outcome(2, 0, 3), ProposalStatus::Rejected);
+    }
+    
+    #[test]
+    fn test_required_votes_rounds_up() {
+        assert_eq!(required_votes(Decimal::new(10, 2), 200), 20);
+        assert_eq!(required_votes(Decimal::new(10, 2), 15), 2);
+        assert_eq!(required_votes(Decimal::ZERO, 500), 0);
+        assert_eq!(required_votes(Decimal::ONE, 7), 7);
+    }
+    
+    #[test]
+    fn test_vote_weight() {
+        assert_eq!(vote_weight(None), Decimal::ONE);
+        assert_eq!(vote_weight(Some(Decimal::from(3))), Decimal::ONE);
+        assert_eq!(vote_weight(Some(Decimal::ONE)), Decimal::new(5, 1));
+        assert_eq!(vote_weight(Some(Decimal::from(5))), Decimal::new(15, 1));
+        assert_eq!(vote_weight(Some(Decimal::new(450, 2))), Decimal::new(138, 2));
+        // Out-of-range averages are clamped
+        assert_eq!(vote_weight(Some(Decimal::ZERO)), Decimal::new(5, 1));
+        assert_eq!(vote_weight(Some(Decimal::from(9))), Decimal::new(15, 1));
+    }
+    
+    #[test]
+    fn test_weighted_outcome_uses_weighted_totals() {
+        // One vote each way, but the supporter is better regarded
+        assert_eq!(
+            weighted_proposal_outcome(1, 1, Decimal::new(15, 1), Decimal::new(5, 1), 0),
+            ProposalStatus::Passed
+        );
+        assert_eq!(
+            weighted_proposal_outcome(1, 1, Decimal::new(5, 1), Decimal::new(15, 1), 0),
+            ProposalStatus::Rejected
+        );
+        // Quorum is still judged on the raw count
+        assert_eq!(
+            weighted_proposal_outcome(1, 0, Decimal::new(15, 1), Decimal::ZERO, 2),
+            ProposalStatus::Rejected
+        );
+    }
+    
+    async fn end_voting(pool: &PgPool, proposal_id: Uuid) {
+        sqlx::query("UPDATE proposals SET voting_ends_at = $1 WHERE id = $2")
+            .bind(Utc::now() - Duration::hours(1))
+            .bind(proposal_id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+    
+    async fn create_test_proposal(pool: &PgPool) -> Proposal {
+        create_test_proposal_with_quorum(pool, Decimal::ZERO).await
+    }
+    
+    async fn create_weighted_test_proposal(pool: &PgPool) -> Proposal {
+        let creator = insert_test_member(pool).await;
+        create_proposal(
+            pool,
+            creator,
+            "New storage shed",
+            "Build a shared cold store",
+            Duration::days(7),
+            Decimal::ZERO,
+            true,
+            ProposalKind::General,
+        )
+        .await
+        .unwrap()
+    }
+    
+    /// Give `member` a single rating of `score`, from a fresh rater and transaction
+    async fn rate_member(pool: &PgPool, member: Uuid, score: i32) {
+        let rater = insert_test_member(pool).await;
+        let transaction_id = insert_test_transaction(pool, rater, member, TransactionStatus::Completed).await;
+        reputation::create_rating(pool, transaction_id, rater, member, score)
+            .await
+            .unwrap();
+    }
+    
+    async fn create_kind_test_proposal(pool: &PgPool, kind: ProposalKind) -> Proposal {
+        let creator = insert_test_member(pool).await;
+        create_proposal(
+            pool,
+            creator,
+            "Cooperative change",
+            "Put this change to a vote",
+            Duration::days(7),
+            Decimal::ZERO,
+            false,
+            kind,
+        )
+        .await
+        .unwrap()
+    }
+    
+    async fn create_test_proposal_with_quorum(pool: &PgPool, quorum: Decimal) -> Proposal {
+        let creator = insert_test_member(pool).await;
+        create_proposal(
+            pool,
+            creator,
+            "New storage shed",
+            "Build a shared cold store",
+            Duration::days(7),
+            quorum,
+            false,
+            ProposalKind::General,
+        )
+        .await
+        .unwrap()
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_cast_vote_updates_tally(pool: PgPool) {
+        let proposal = create_test_proposal(&pool).await;
+        let alice = insert_test_member(&pool).await;
+        let bob = insert_test_member(&pool).await;
+        
+        cast_vote(&pool, proposal.id, alice, VoteType::For).await.unwrap();
+        let updated = cast_vote(&pool, proposal.id, bob, VoteType::Against).await.unwrap();
+        
+        assert_eq!(updated.votes_for, 1);
+        assert_eq!(updated.votes_against, 1);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_cast_vote_already_voted(pool: PgPool) {
+        let proposal = create_test_proposal(&pool).await;
+        let member = insert_test_member(&pool).await;
+        
+        cast_vote(&pool, proposal.id, member, VoteType::For).await.unwrap();
+        let result = cast_vote(&pool, proposal.id, member, VoteType::Against).await;
+        
+        assert!(matches!(result, Err(GovernanceError::AlreadyVoted)));
+        
+        // The rejected vote must not change the tally
+        let proposal = get_proposal(&pool, proposal.id).await.unwrap();
+        assert_eq!(proposal.votes_for, 1);
+        assert_eq!(proposal.votes_against, 0);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_cast_vote_after_voting_ended(pool: PgPool) {
+        let proposal = create_test_proposal(&pool).await;
+        let member = insert_test_member(&pool).await;
+        
+        end_voting(&pool, proposal.id).await;
+        
+        let result = cast_vote(&pool, proposal.id, member, VoteType::For).await;
+        
+        assert!(matches!(result, Err(GovernanceError::VotingEnded)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_finalize_proposal_passed(pool: PgPool) {
+        let proposal = create_test_proposal(&pool).await;
+        for vote_type in [VoteType::For, VoteType::For, VoteType::Against] {
+            let member = insert_test_member(&pool).await;
+            cast_vote(&pool, proposal.id, member, vote_type).await.unwrap();
+        }
+        end_voting(&pool, proposal.id).await;
+        
+        let finalized = finalize_proposal(&pool, proposal.id).await.unwrap();
+        
+        assert_eq!(finalized.status, ProposalStatus::Passed.to_string());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_finalize_proposal_rejected(pool: PgPool) {
+        let proposal = create_test_proposal(&pool).await;
+        let member = insert_test_member(&pool).await;
+        cast_vote(&pool, proposal.id, member, VoteType::Against).await.unwrap();
+        end_voting(&pool, proposal.id).await;
+        
+        let finalized = finalize_proposal(&pool, proposal.id).await.unwrap();
+        
+        assert_eq!(finalized.status, ProposalStatus::Rejected.to_string());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_passed_fee_change_applies_and_rejected_does_not(pool: PgPool) {
+        let default_fee = Decimal::new(5, 2);
+        let rejected = create_kind_test_proposal(&pool, ProposalKind::FeeChange { new_fee: Decimal::new(9, 2) }).await;
+        let member = insert_test_member(&pool).await;
+        cast_vote(&pool, rejected.id, member, VoteType::Against).await.unwrap();
+        end_voting(&pool, rejected.id).await;
+        finalize_proposal(&pool, rejected.id).await.unwrap();
+        
+        let mut conn = pool.acquire().await.unwrap();
+        assert_eq!(fees::resolve_fee(&mut conn, Category::Fruits, default_fee).await.unwrap(), default_fee);
+        
+        let passed = create_kind_test_proposal(&pool, ProposalKind::FeeChange { new_fee: Decimal::new(3, 2) }).await;
+        cast_vote(&pool, passed.id, member, VoteType::For).await.unwrap();
+        end_voting(&pool, passed.id).await;
+        let finalized = finalize_proposal(&pool, passed.id).await.unwrap();
+        
+        assert_eq!(finalized.kind.0, ProposalKind::FeeChange { new_fee: Decimal::new(3, 2) });
+        assert_eq!(fees::resolve_fee(&mut conn, Category::Fruits, default_fee).await.unwrap(), Decimal::new(3, 2));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_passed_member_removal_erases_member(pool: PgPool) {
+        let config = test_config();
+        let target = insert_test_member(&pool).await;
+        let token = auth::generate_token(&Member { id: target, ..test_member(Role::Member) }, &config).unwrap();
+        assert!(auth::validate_token(&pool, &token, &config.jwt_secret).await.is_ok());
+        let proposal = create_kind_test_proposal(&pool, ProposalKind::MemberRemoval { member_id: target }).await;
+        let member = insert_test_member(&pool).await;
+        cast_vote(&pool, proposal.id, member, VoteType::For).await.unwrap();
+        end_voting(&pool, proposal.id).await;
+        
+        finalize_proposal(&pool, proposal.id).await.unwrap();
+        
+        let deleted: bool = sqlx::query_scalar("SELECT deleted_at IS NOT NULL FROM members WHERE id = $1")
+            .bind(target)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(deleted);
+        
+        // The removed member's tokens stop working straight away
+        let result = auth::validate_token(&pool, &token, &config.jwt_secret).await;
+        assert!(matches!(result, Err(crate::error::AuthError::TokenRevoked)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_finalize_proposal_expired_and_not_refinalized(pool: PgPool) {
+        let proposal = create_test_proposal(&pool).await;
+        end_voting(&pool, proposal.id).await;
+        
+        let finalized = finalize_proposal(&pool, proposal.id).await.unwrap();
+        assert_eq!(finalized.status, ProposalStatus::Expired.to_string());
+        
+        let result = finalize_proposal(&pool, proposal.id).await;
+        assert!(matches!(result, Err(GovernanceError::AlreadyFinalized)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_finalize_proposal_quorum(pool: PgPool) {
+        // Identical tallies of 2-0 among the same membership
+        let low = create_test_proposal_with_quorum(&pool, Decimal::new(25, 2)).await;
+        let high = create_test_proposal_with_quorum(&pool, Decimal::new(75, 2)).await;
+        for _ in 0..2 {
+            let member = insert_test_member(&pool).await;
+            cast_vote(&pool, low.id, member, VoteType::For).await.unwrap();
+            cast_vote(&pool, high.id, member, VoteType::For).await.unwrap();
+        }
+        end_voting(&pool, low.id).await;
+        end_voting(&pool, high.id).await;
+        
+        // 4 members in total: 2 creators and 2 voters
+        let low = finalize_proposal(&pool, low.id).await.unwrap();
+        let high = finalize_proposal(&pool, high.id).await.unwrap();
+        
+        assert_eq!(low.status, ProposalStatus::Passed.to_string());
+        assert_eq!(high.status, ProposalStatus::Rejected.to_string());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_quorum_ignores_erased_members(pool: PgPool) {
+        let proposal = create_test_proposal_with_quorum(&pool, Decimal::new(50, 2)).await;
+        let voter = insert_test_member(&pool).await;
+        cast_vote(&pool, proposal.id, voter, VoteType::For).await.unwrap();
+        for _ in 0..2 {
+            let leaver = insert_test_member(&pool).await;
+            members::delete_member_data(&pool, leaver).await.unwrap();
+        }
+        end_voting(&pool, proposal.id).await;
+        
+        // 1 of the 2 remaining members meets a 50% quorum; the 2 erased ones don't count
+        let finalized = finalize_proposal(&pool, proposal.id).await.unwrap();
+        assert_eq!(finalized.status, ProposalStatus::Passed.to_string());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_finalize_proposal_before_deadline(pool: PgPool) {
+        let proposal = create_test_proposal(&pool).await;
+        
+        let result = finalize_proposal(&pool, proposal.id).await;
+        
+        assert!(matches!(result, Err(GovernanceError::VotingInProgress)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_proposal_empty_title_rejected(pool: PgPool) {
+        let creator = insert_test_member(&pool).await;
+        
+        let result = create_proposal(
+            &pool,
+            creator,
+            "",
+            "Build a shared cold store",
+            Duration::days(7),
+            Decimal::ZERO,
+            false,
+            ProposalKind::General,
+        )
+        .await;
+        
+        assert!(matches!(result, Err(GovernanceError::InvalidData(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_weighted_voting_decides_equal_raw_votes(pool: PgPool) {
+        let trusted = insert_test_member(&pool).await;
+        let unproven = insert_test_member(&pool).await;
+        rate_member(&pool, trusted, 5).await;
+        rate_member(&pool, unproven, 1).await;
+        
+        // The same 1-1 split on a weighted and an unweighted proposal
+        let weighted = create_weighted_test_proposal(&pool).await;
+        let unweighted = create_test_proposal(&pool).await;
+        for proposal in [&weighted, &unweighted] {
+            cast_vote(&pool, proposal.id, trusted, VoteType::For).await.unwrap();
+            cast_vote(&pool, proposal.id, unproven, VoteType::Against).await.unwrap();
+            end_voting(&pool, proposal.id).await;
+        }
+        
+        let weighted = finalize_proposal(&pool, weighted.id).await.unwrap();
+        assert_eq!((weighted.votes_for, weighted.votes_against), (1, 1));
+        assert_eq!(weighted.weighted_for, Decimal::new(15, 1));
+        assert_eq!(weighted.weighted_against, Decimal::new(5, 1));
+        assert_eq!(weighted.status, ProposalStatus::Passed.to_string());
+        
+        let unweighted = finalize_proposal(&pool, unweighted.id).await.unwrap();
+        assert_eq!((unweighted.weighted_for, unweighted.weighted_against), (Decimal::ONE, Decimal::ONE));
+        assert_eq!(unweighted.status, ProposalStatus::Rejected.to_string());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_weighted_voting_reputation_flips_outcome(pool: PgPool) {
+        // Two proposals with the same 1-1 split, where only the reputations differ
+        let first = create_weighted_test_proposal(&pool).await;
+        let (supporter, opponent) = (insert_test_member(&pool).await, insert_test_member(&pool).await);
+        rate_member(&pool, supporter, 2).await;
+        rate_member(&pool, opponent, 4).await;
+        cast_vote(&pool, first.id, supporter, VoteType::For).await.unwrap();
+        cast_vote(&pool, first.id, opponent, VoteType::Against).await.unwrap();
+        end_voting(&pool, first.id).await;
+        
+        let second = create_weighted_test_proposal(&pool).await;
+        let (supporter, opponent) = (insert_test_member(&pool).await, insert_test_member(&pool).await);
+        rate_member(&pool, supporter, 4).await;
+        rate_member(&pool, opponent, 2).await;
+        cast_vote(&pool, second.id, supporter, VoteType::For).await.unwrap();
+        cast_vote(&pool, second.id, opponent, VoteType::Against).await.unwrap();
+        end_voting(&pool, second.id).await;
+        
+        let first = finalize_proposal(&pool, first.id).await.unwrap();
+        let second = finalize_proposal(&pool, second.id).await.unwrap();
+        
+        assert_eq!(first.status, ProposalStatus::Rejected.to_string());
+        assert_eq!(second.status, ProposalStatus::Passed.to_string());
+    }
+    
+    #[test]
+    fn test_validate_comment_body() {
+        assert!(validate_comment_body("Could we share the shed with the next village?").is_ok());
+        assert!(matches!(validate_comment_body(""), Err(GovernanceError::InvalidData(_))));
+        assert!(matches!(validate_comment_body(" \n\t "), Err(GovernanceError::InvalidData(_))));
+        
+        // The cap counts characters, not bytes
+        assert!(validate_comment_body(&"é".repeat(MAX_COMMENT_LENGTH)).is_ok());
+        assert!(matches!(
+            validate_comment_body(&"a".repeat(MAX_COMMENT_LENGTH + 1)),
+            Err(GovernanceError::InvalidData(_))
+        ));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_comments_in_order_and_kept_after_finalize(pool: PgPool) {
+        let proposal = create_test_proposal(&pool).await;
+        let (first, second) = (insert_test_member(&pool).await, insert_test_member(&pool).await);
+        
+        let result = add_comment(&pool, proposal.id, first, "   ").await;
+        assert!(matches!(result, Err(GovernanceError::InvalidData(_))));
+        let result = add_comment(&pool, Uuid::new_v4(), first, "Hello").await;
+        assert!(matches!(result, Err(GovernanceError::ProposalNotFound)));
+        
+        let opening = add_comment(&pool, proposal.id, first, "  Who maintains it?  ").await.unwrap();
+        assert_eq!(opening.body, "Who maintains it?");
+        add_comment(&pool, proposal.id, second, "A rota of members").await.unwrap();
+        add_comment(&pool, proposal.id, first, "Works for me").await.unwrap();
+        
+        end_voting(&pool, proposal.id).await;
+        finalize_proposal(&pool, proposal.id).await.unwrap();
+        
+        let comments = get_comments(&pool, proposal.id).await.unwrap();
+        let bodies: Vec<&str> = comments.iter().map(|c| c.body.as_str()).collect();
+        assert_eq!(bodies, ["Who maintains it?", "A rota of members", "Works for me"]);
+        assert_eq!(comments[1].member_id, second);
+    }
+}
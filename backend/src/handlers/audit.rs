@@ -0,0 +1,27 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{audit, error::DoftaError, middleware::auth::AdminClaims};
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Get the admin audit log, newest first (admin only)
+pub async fn get_audit_log(
+    State(pool): State<PgPool>,
+    _admin: AdminClaims,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let entries = audit::get_audit_log(&pool, query.from, query.to).await?;
+
+    Ok(Json(entries))
+}
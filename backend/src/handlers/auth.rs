@@ -1,17 +1,19 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::State,
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use uuid::Uuid;
 
 use crate::{
     auth::{self, Claims},
+    config::Config,
     error::DoftaError,
+    members,
     models::Member,
+    security::FieldKeys,
 };
 
 #[derive(Debug, Deserialize)]
@@ -27,6 +29,7 @@ pub struct RegisterRequest {
 pub struct AuthResponse {
     pub member: Member,
     pub token: String,
+    pub refresh_token: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -38,6 +41,7 @@ pub struct LoginRequest {
 /// Register a new member
 pub async fn register(
     State(pool): State<PgPool>,
+    State(config): State<Config>,
     Json(payload): Json<RegisterRequest>,
 ) -> Result<impl IntoResponse, DoftaError> {
     // Register member
@@ -48,42 +52,87 @@ pub async fn register(
         &payload.name,
         payload.farm_name.as_deref(),
         payload.location.as_deref(),
+        &FieldKeys::from_config(&config)?,
     )
     .await?;
 
     // Generate JWT token
-    let token = auth::generate_token(&member.id)?;
+    let token = auth::generate_token(&member, &config)?;
+    let refresh_token = auth::generate_refresh_token(&pool, member.id, &config).await?;
 
     Ok((
         StatusCode::CREATED,
-        Json(AuthResponse { member, token }),
+        Json(AuthResponse {
+            member,
+            token,
+            refresh_token,
+        }),
     ))
 }
 
 /// Login existing member
 pub async fn login(
     State(pool): State<PgPool>,
+    State(config): State<Config>,
     Json(payload): Json<LoginRequest>,
 ) -> Result<impl IntoResponse, DoftaError> {
     // Authenticate member
-    let member = auth::authenticate_member(&pool, &payload.email, &payload.password).await?;
+    let mut member = auth::authenticate_member(&pool, &payload.email, &payload.password).await?;
+    members::decrypt_profile(&pool, &FieldKeys::from_config(&config)?, &mut member).await?;
 
     // Generate JWT token
-    let token = auth::generate_token(&member.id)?;
+    let token = auth::generate_token(&member, &config)?;
+    let refresh_token = auth::generate_refresh_token(&pool, member.id, &config).await?;
 
-    Ok(Json(AuthResponse { member, token }))
+    Ok(Json(AuthResponse {
+        member,
+        token,
+        refresh_token,
+    }))
 }
 
-/// Get current member profile
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Exchange a refresh token for a new access token
+pub async fn refresh(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let tokens = auth::refresh_access_token(&pool, &payload.refresh_token, &config).await?;
+
+    Ok(Json(tokens))
+}
+
+/// Get current member profile, with the farm name and location decrypted
 pub async fn get_profile(
     State(pool): State<PgPool>,
+    State(config): State<Config>,
     claims: Claims,
 ) -> Result<impl IntoResponse, DoftaError> {
-    let member = sqlx::query_as::<_, Member>("SELECT * FROM members WHERE id = $1")
-        .bind(claims.sub)
-        .fetch_one(&pool)
-        .await
-        .map_err(|_| DoftaError::Unauthorized("Member not found".to_string()))?;
+    let mut member = sqlx::query_as::<_, Member>(
+        "SELECT id, email, password_hash, name, role, created_at
+         FROM members
+         WHERE id = $1"
+    )
+    .bind(claims.sub)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| DoftaError::Unauthorized("Member not found".to_string()))?;
+    members::decrypt_profile(&pool, &FieldKeys::from_config(&config)?, &mut member).await?;
 
     Ok(Json(member))
 }
+
+/// Log out, revoking the current access token and the member's refresh tokens
+pub async fn logout(
+    State(pool): State<PgPool>,
+    claims: Claims,
+) -> Result<impl IntoResponse, DoftaError> {
+    auth::logout(&pool, &claims).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    audit,
+    auth::Claims,
+    disputes,
+    error::DoftaError,
+    middleware::auth::AdminClaims,
+    models::{AdminAction, DisputeResolution},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct RaiseDisputeRequest {
+    pub reason: String,
+}
+
+/// Raise a dispute over an order (buyer or seller)
+pub async fn raise_dispute(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(order_id): Path<Uuid>,
+    Json(payload): Json<RaiseDisputeRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let dispute = disputes::raise_dispute(&pool, order_id, claims.sub, &payload.reason).await?;
+
+    Ok((StatusCode::CREATED, Json(dispute)))
+}
+
+/// Get a dispute by ID (admin only)
+pub async fn get_dispute(
+    State(pool): State<PgPool>,
+    _admin: AdminClaims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let dispute = disputes::get_dispute(&pool, id).await?;
+
+    Ok(Json(dispute))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveDisputeRequest {
+    pub resolution: DisputeResolution,
+}
+
+/// Resolve a dispute (admin only)
+pub async fn resolve_dispute(
+    State(pool): State<PgPool>,
+    AdminClaims(claims): AdminClaims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ResolveDisputeRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let dispute = disputes::resolve_dispute(&pool, id, claims.sub, payload.resolution).await?;
+    audit::log_admin_action(
+        &pool,
+        claims.sub,
+        AdminAction::DisputeResolved,
+        &dispute.id.to_string(),
+        json!({
+            "order_id": dispute.order_id,
+            "resolution": dispute.resolution,
+            "buyer_bps": dispute.buyer_bps,
+        }),
+    )
+    .await;
+
+    Ok(Json(dispute))
+}
@@ -0,0 +1,91 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::{
+    audit,
+    error::DoftaError,
+    fees,
+    middleware::auth::AdminClaims,
+    models::{AdminAction, Category},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SetCategoryFeeRequest {
+    pub fee_percentage: Decimal,
+}
+
+/// Set the cooperative fee override for a category (admin only)
+pub async fn set_category_fee(
+    State(pool): State<PgPool>,
+    AdminClaims(claims): AdminClaims,
+    Path(category): Path<String>,
+    Json(payload): Json<SetCategoryFeeRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let category = category.parse::<Category>().map_err(DoftaError::InvalidInput)?;
+
+    let fee = fees::set_category_fee(&pool, category, payload.fee_percentage, claims.sub).await?;
+    audit::log_admin_action(
+        &pool,
+        claims.sub,
+        AdminAction::FeeChanged,
+        &fee.category,
+        json!({ "fee_percentage": fee.fee_percentage }),
+    )
+    .await;
+
+    Ok(Json(fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        audit, auth,
+        models::{Member, Role},
+        routes,
+        test_utils::{insert_test_member, test_config, test_member},
+    };
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::Service;
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_fee_change_is_audited(pool: PgPool) {
+        let config = test_config();
+        let admin_id = insert_test_member(&pool).await;
+        let admin = Member {
+            id: admin_id,
+            ..test_member(Role::Admin)
+        };
+        let token = auth::generate_token(&admin, &config).unwrap();
+        let mut app = routes::create_router(pool.clone(), config, Default::default());
+
+        let request = Request::builder()
+            .method("PUT")
+            .uri("/api/admin/fees/Grains")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"fee_percentage": "0.02"}"#))
+            .unwrap();
+        std::future::poll_fn(|cx| Service::<Request<Body>>::poll_ready(&mut app, cx)).await.unwrap();
+        let response = app.call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let entries = audit::get_audit_log(&pool, None, None).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor_id, admin_id);
+        assert_eq!(entries[0].action, "FeeChanged");
+        assert_eq!(entries[0].target, "Grains");
+        assert_eq!(entries[0].metadata.0["fee_percentage"], "0.0200");
+    }
+}
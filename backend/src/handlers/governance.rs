@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Duration;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    audit,
+    auth::Claims,
+    config::Config,
+    error::DoftaError,
+    governance,
+    middleware::auth::AdminClaims,
+    models::{AdminAction, ProposalKind, VoteType},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProposalRequest {
+    pub title: String,
+    pub description: String,
+    pub voting_duration_hours: i64,
+    /// Weight votes by each voter's reputation
+    #[serde(default)]
+    pub weighted_voting: bool,
+    /// What the proposal does once passed; a plain vote by default
+    #[serde(default)]
+    pub kind: ProposalKind,
+}
+
+/// Create a new proposal
+pub async fn create_proposal(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    claims: Claims,
+    Json(payload): Json<CreateProposalRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let voting_duration = Duration::try_hours(payload.voting_duration_hours)
+        .ok_or_else(|| DoftaError::InvalidInput("Voting duration is too long".to_string()))?;
+
+    let proposal = governance::create_proposal(
+        &pool,
+        claims.sub,
+        &payload.title,
+        &payload.description,
+        voting_duration,
+        config.proposal_quorum,
+        payload.weighted_voting,
+        payload.kind,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(proposal)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CastVoteRequest {
+    pub vote_type: String,
+}
+
+/// Vote on a proposal
+pub async fn cast_vote(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CastVoteRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let vote_type: VoteType = payload
+        .vote_type
+        .parse()
+        .map_err(|_| DoftaError::InvalidInput("Invalid vote type".to_string()))?;
+
+    let proposal = governance::cast_vote(&pool, id, claims.sub, vote_type).await?;
+
+    Ok(Json(proposal))
+}
+
+/// Finalize a proposal after its voting period (admin only)
+pub async fn finalize_proposal(
+    State(pool): State<PgPool>,
+    AdminClaims(claims): AdminClaims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let proposal = governance::finalize_proposal(&pool, id).await?;
+    audit::log_admin_action(
+        &pool,
+        claims.sub,
+        AdminAction::ProposalFinalized,
+        &proposal.id.to_string(),
+        json!({ "status": proposal.status, "kind": proposal.kind }),
+    )
+    .await;
+
+    Ok(Json(proposal))
+}
+
+/// List proposals open for voting
+pub async fn get_proposals(
+    State(pool): State<PgPool>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let proposals = governance::get_active_proposals(&pool).await?;
+
+    Ok(Json(proposals))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddCommentRequest {
+    pub body: String,
+}
+
+/// Comment on a proposal
+pub async fn add_comment(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AddCommentRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let comment = governance::add_comment(&pool, id, claims.sub, &payload.body).await?;
+
+    Ok((StatusCode::CREATED, Json(comment)))
+}
+
+/// List a proposal's comments, oldest first
+pub async fn get_comments(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let comments = governance::get_comments(&pool, id).await?;
+
+    Ok(Json(comments))
+}
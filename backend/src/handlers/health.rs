@@ -0,0 +1,140 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::db::Database;
+
+/// Result of probing the database and its schema
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    /// "ok" when the database answers, otherwise "unavailable"
+    pub status: &'static str,
+    pub db: &'static str,
+    /// "current", "pending" or "unknown" when the database can't be queried
+    pub migrations: &'static str,
+    pub pool: PoolStatus,
+}
+
+/// Connection pool utilization
+#[derive(Debug, Serialize)]
+pub struct PoolStatus {
+    pub size: u32,
+    pub idle: usize,
+    pub max_connections: u32,
+}
+
+impl HealthReport {
+    fn is_db_ok(&self) -> bool {
+        self.db == "ok"
+    }
+}
+
+async fn probe(pool: PgPool) -> HealthReport {
+    let db = Database::from_pool(pool);
+    let db_ok = match db.health_check().await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("Health check database probe failed: {}", e);
+            false
+        }
+    };
+    let migrations = if !db_ok {
+        "unknown"
+    } else {
+        match db.pending_migrations().await {
+            Ok(0) => "current",
+            Ok(_) => "pending",
+            Err(_) => "unknown",
+        }
+    };
+    let pool = db.pool();
+
+    HealthReport {
+        status: if db_ok { "ok" } else { "unavailable" },
+        db: if db_ok { "ok" } else { "unavailable" },
+        migrations,
+        pool: PoolStatus {
+            size: pool.size(),
+            idle: pool.num_idle(),
+            max_connections: pool.options().get_max_connections(),
+        },
+    }
+}
+
+/// Report database, migration and pool status, with a 503 when the database is unreachable
+pub async fn health(State(pool): State<PgPool>) -> impl IntoResponse {
+    let report = probe(pool).await;
+    let status = if report.is_db_ok() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(report))
+}
+
+/// Liveness probe that only shows the process is serving requests
+pub async fn live() -> &'static str {
+    "OK"
+}
+
+/// Readiness probe, which also requires all migrations to be applied
+pub async fn ready(State(pool): State<PgPool>) -> impl IntoResponse {
+    let report = probe(pool).await;
+    let status = if report.is_db_ok() && report.migrations == "current" {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::Response;
+    use serde_json::Value;
+    use sqlx::postgres::PgPoolOptions;
+    use std::time::Duration;
+
+    async fn json_body(response: Response) -> Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_database_is_unavailable() {
+        // Nothing listens on port 1, so the probe fails once the acquire times out
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_secs(1))
+            .connect_lazy("postgres://postgres@127.0.0.1:1/dofta")
+            .unwrap();
+
+        let response = health(State(pool.clone())).await.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body = json_body(response).await;
+        assert_eq!(body["status"], "unavailable");
+        assert_eq!(body["db"], "unavailable");
+        assert_eq!(body["migrations"], "unknown");
+
+        let response = ready(State(pool)).await.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_healthy_database(pool: PgPool) {
+        let response = health(State(pool.clone())).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json_body(response).await;
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["db"], "ok");
+        assert_eq!(body["migrations"], "current");
+        assert!(body["pool"]["size"].as_u64().unwrap() >= 1);
+        assert_eq!(body["pool"]["max_connections"], pool.options().get_max_connections());
+
+        let response = ready(State(pool)).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
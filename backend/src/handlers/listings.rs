@@ -4,15 +4,20 @@ use axum::{
     response::IntoResponse,
     Json,
 };
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
+    audit,
     auth::Claims,
-    error::DoftaError,
-    listings::{self, CreateListingData, ListingFilters, UpdateListingData},
-    models::ProductListing,
+    error::{BatchRowError, DoftaError, ListingError},
+    listings::{self, CreateListingData, ListingFilters, ListingSort, UpdateListingData, ViewCounter},
+    middleware::auth::AdminClaims,
+    models::{AdminAction, AvailabilityStatus, Category},
 };
 
 #[derive(Debug, Deserialize)]
@@ -23,6 +28,7 @@ pub struct CreateListingRequest {
     pub unit_price: String,
     pub quantity_available: i32,
     pub unit_of_measure: String,
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,6 +39,9 @@ pub struct UpdateListingRequest {
     pub unit_price: Option<String>,
     pub quantity_available: Option<i32>,
     pub unit_of_measure: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Version of the listing the client is editing
+    pub version: i32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,6 +50,50 @@ pub struct SearchQuery {
     pub min_price: Option<String>,
     pub max_price: Option<String>,
     pub available_only: Option<bool>,
+    pub min_quantity: Option<String>,
+    pub max_quantity: Option<String>,
+    pub sort: Option<ListingSort>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SavedSearchRequest {
+    pub name: String,
+    /// Filters as accepted by the listing search; omitted fields are unset
+    #[serde(default)]
+    pub filters: ListingFilters,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InventoryQuery {
+    pub low_stock_threshold: Option<String>,
+    /// List archived listings too; they never count towards the totals
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+impl TryFrom<CreateListingRequest> for CreateListingData {
+    type Error = DoftaError;
+
+    fn try_from(request: CreateListingRequest) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: request.name,
+            description: request.description,
+            category: request.category,
+            unit_price: request.unit_price.parse().map_err(|_| {
+                DoftaError::InvalidInput("Invalid unit price format".to_string())
+            })?,
+            quantity_available: Decimal::from(request.quantity_available),
+            unit_of_measure: request.unit_of_measure,
+            expires_at: request.expires_at,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkCreateListingsRequest {
+    pub listings: Vec<CreateListingRequest>,
 }
 
 /// Create a new listing
@@ -49,46 +102,139 @@ pub async fn create_listing(
     claims: Claims,
     Json(payload): Json<CreateListingRequest>,
 ) -> Result<impl IntoResponse, DoftaError> {
-    let data = CreateListingData {
-        member_id: claims.sub,
-        name: payload.name,
-        description: payload.description,
-        category: payload.category,
-        unit_price: payload.unit_price.parse().map_err(|_| {
-            DoftaError::InvalidInput("Invalid unit price format".to_string())
-        })?,
-        quantity_available: payload.quantity_available,
-        unit_of_measure: payload.unit_of_measure,
-    };
+    let data = CreateListingData::try_from(payload)?;
 
-    let listing = listings::create_listing(&pool, data).await?;
+    let listing = listings::create_listing(&pool, claims.sub, data).await?;
 
     Ok((StatusCode::CREATED, Json(listing)))
 }
 
+/// Create a batch of listings, all or none
+pub async fn create_listings_bulk(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Json(payload): Json<BulkCreateListingsRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let mut data = Vec::with_capacity(payload.listings.len());
+    let mut errors = Vec::new();
+    for (index, request) in payload.listings.into_iter().enumerate() {
+        match CreateListingData::try_from(request) {
+            Ok(listing) => data.push(listing),
+            Err(e) => errors.push(BatchRowError {
+                index,
+                message: e.to_string(),
+            }),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ListingError::InvalidBatch(errors).into());
+    }
+
+    let listings = listings::create_listings_bulk(&pool, claims.sub, data).await?;
+
+    Ok((StatusCode::CREATED, Json(listings)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchListingsRequest {
+    pub ids: Vec<Uuid>,
+}
+
+/// Get several listings by ID, skipping any that don't exist
+///
+/// As with a single read, archived listings are only included for their owner.
+pub async fn get_listings_batch(
+    State(pool): State<PgPool>,
+    claims: Option<Claims>,
+    Json(payload): Json<BatchListingsRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let viewer = claims.map(|claims| claims.sub);
+    let mut listings = listings::get_listings_by_ids(&pool, &payload.ids, viewer.is_some()).await?;
+    listings.retain(|listing| {
+        Some(listing.member_id) == viewer || listing.availability != AvailabilityStatus::Archived.to_string()
+    });
+
+    Ok(Json(listings))
+}
+
 /// Get all listings (with optional filters)
 pub async fn get_listings(
     State(pool): State<PgPool>,
     Query(query): Query<SearchQuery>,
 ) -> Result<impl IntoResponse, DoftaError> {
     let filters = ListingFilters {
+        search_term: None,
         category: query.category,
         min_price: query.min_price.and_then(|p| p.parse().ok()),
         max_price: query.max_price.and_then(|p| p.parse().ok()),
-        available_only: query.available_only.unwrap_or(true),
+        availability: query
+            .available_only
+            .unwrap_or(true)
+            .then_some(AvailabilityStatus::Available),
+        min_quantity: query.min_quantity.and_then(|q| q.parse().ok()),
+        max_quantity: query.max_quantity.and_then(|q| q.parse().ok()),
+        sort: query.sort.unwrap_or_default(),
+        limit: query.limit,
+        offset: query.offset,
     };
 
-    let listings = listings::search_listings(&pool, filters).await?;
+    let page = listings::search_listings_page(&pool, filters).await?;
+
+    Ok(Json(page))
+}
+
+/// List the valid listing categories
+pub async fn get_categories() -> impl IntoResponse {
+    Json(Category::ALL)
+}
+
+/// Get the current member's own listings, including archived ones
+pub async fn get_my_listings(
+    State(pool): State<PgPool>,
+    claims: Claims,
+) -> Result<impl IntoResponse, DoftaError> {
+    let listings = listings::get_listings_by_member(&pool, claims.sub).await?;
 
     Ok(Json(listings))
 }
 
+/// Get the current member's stock value and low-stock listings
+pub async fn get_my_inventory(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<InventoryQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let low_stock_threshold = match query.low_stock_threshold {
+        Some(threshold) => threshold.parse::<Decimal>().map_err(|_| {
+            ListingError::InvalidData("Low stock threshold must be a number".to_string())
+        })?,
+        None => listings::DEFAULT_LOW_STOCK_THRESHOLD,
+    };
+    let inventory = listings::seller_inventory(&pool, claims.sub, low_stock_threshold, query.include_archived).await?;
+
+    Ok(Json(inventory))
+}
+
 /// Get a single listing by ID
+///
+/// Archived listings are only visible to their owner; everyone else gets a 404.
+/// Views by anyone but the owner are counted, and only the owner sees the count.
 pub async fn get_listing(
     State(pool): State<PgPool>,
+    State(views): State<ViewCounter>,
+    claims: Option<Claims>,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, DoftaError> {
-    let listing = listings::get_listing(&pool, id).await?;
+    let viewer = claims.map(|claims| claims.sub);
+    let mut listing = listings::get_listing(&pool, id, viewer.is_some()).await?;
+
+    if Some(listing.member_id) == viewer {
+        listing.view_count = Some(listings::get_view_count(&pool, id).await?);
+    } else if listing.availability == AvailabilityStatus::Archived.to_string() {
+        return Err(ListingError::NotFound.into());
+    } else {
+        views.record(id);
+    }
 
     Ok(Json(listing))
 }
@@ -101,7 +247,7 @@ pub async fn update_listing(
     Json(payload): Json<UpdateListingRequest>,
 ) -> Result<impl IntoResponse, DoftaError> {
     // Verify ownership
-    let existing = listings::get_listing(&pool, id).await?;
+    let existing = listings::get_listing(&pool, id, true).await?;
     if existing.member_id != claims.sub {
         return Err(DoftaError::Forbidden(
             "You can only update your own listings".to_string(),
@@ -113,11 +259,25 @@ pub async fn update_listing(
         description: payload.description,
         category: payload.category,
         unit_price: payload.unit_price.and_then(|p| p.parse().ok()),
-        quantity_available: payload.quantity_available,
+        quantity_available: payload.quantity_available.map(Decimal::from),
         unit_of_measure: payload.unit_of_measure,
+        availability: None,
+        expires_at: payload.expires_at,
+        expected_version: Some(payload.version),
     };
 
-    let listing = listings::update_listing(&pool, id, data).await?;
+    let listing = listings::update_listing(&pool, id, claims.sub, data).await?;
+
+    Ok(Json(listing))
+}
+
+/// Restore an archived listing
+pub async fn restore_listing(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let listing = listings::restore_listing(&pool, id, claims.sub).await?;
 
     Ok(Json(listing))
 }
@@ -129,14 +289,245 @@ pub async fn delete_listing(
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, DoftaError> {
     // Verify ownership
-    let existing = listings::get_listing(&pool, id).await?;
+    let existing = listings::get_listing(&pool, id, true).await?;
     if existing.member_id != claims.sub {
         return Err(DoftaError::Forbidden(
             "You can only delete your own listings".to_string(),
         ));
     }
 
-    listings::delete_listing(&pool, id).await?;
+    listings::delete_listing(&pool, id, claims.sub).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[derive(Debug, Deserialize)]
+pub struct ArchiveListingRequest {
+    pub reason: String,
+}
+
+/// Archive any member's listing, e.g. to take down abuse (admin only)
+pub async fn admin_archive_listing(
+    State(pool): State<PgPool>,
+    AdminClaims(claims): AdminClaims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ArchiveListingRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let listing = listings::admin_archive_listing(&pool, id, &payload.reason).await?;
+    audit::log_admin_action(
+        &pool,
+        claims.sub,
+        AdminAction::ListingArchived,
+        &listing.id.to_string(),
+        json!({
+            "owner_id": listing.member_id,
+            "reason": payload.reason.trim(),
+        }),
+    )
+    .await;
+
+    Ok(Json(listing))
+}
+
+/// Get the current member's saved searches
+pub async fn get_saved_searches(
+    State(pool): State<PgPool>,
+    claims: Claims,
+) -> Result<impl IntoResponse, DoftaError> {
+    let saved_searches = listings::list_saved_searches(&pool, claims.sub).await?;
+
+    Ok(Json(saved_searches))
+}
+
+/// Save a search for the current member
+pub async fn save_search(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Json(payload): Json<SavedSearchRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let saved_search = listings::save_search(&pool, claims.sub, &payload.name, payload.filters).await?;
+
+    Ok((StatusCode::CREATED, Json(saved_search)))
+}
+
+/// Get one of the current member's saved searches
+pub async fn get_saved_search(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let saved_search = listings::get_saved_search(&pool, claims.sub, id).await?;
+
+    Ok(Json(saved_search))
+}
+
+/// Rename a saved search and replace its filters
+pub async fn update_saved_search(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SavedSearchRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let saved_search =
+        listings::update_saved_search(&pool, claims.sub, id, &payload.name, payload.filters).await?;
+
+    Ok(Json(saved_search))
+}
+
+/// Delete one of the current member's saved searches
+pub async fn delete_saved_search(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    listings::delete_saved_search(&pool, claims.sub, id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Run a saved search against the current listings
+pub async fn run_saved_search(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let listings = listings::run_saved_search(&pool, claims.sub, id).await?;
+
+    Ok(Json(listings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth;
+    use crate::models::{Member, Role};
+    use crate::notifications;
+    use crate::routes;
+    use crate::test_utils::{insert_test_listing, insert_test_member, test_config, test_member};
+    use axum::{body::Body, http::{header, Request}};
+    use tower::Service;
+
+    fn claims_for(member_id: Uuid) -> Claims {
+        let config = test_config();
+        let mut member = test_member(Role::Member);
+        member.id = member_id;
+        let token = auth::generate_token(&member, &config).unwrap();
+        auth::decode_token(&token, &config.jwt_secret).unwrap()
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_archived_listing_only_visible_to_owner(pool: PgPool) {
+        let owner = insert_test_member(&pool).await;
+        let other = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, owner).await;
+
+        // Live listings are public
+        let response = get_listing(State(pool.clone()), State(ViewCounter::default()), None, Path(listing_id)).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        listings::delete_listing(&pool, listing_id, owner).await.unwrap();
+
+        let response = get_listing(State(pool.clone()), State(ViewCounter::default()), None, Path(listing_id)).await.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let claims = claims_for(other);
+        let response = get_listing(State(pool.clone()), State(ViewCounter::default()), Some(claims), Path(listing_id)).await.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let claims = claims_for(owner);
+        let response = get_listing(State(pool.clone()), State(ViewCounter::default()), Some(claims), Path(listing_id)).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Public reads at the module level agree
+        let result = listings::get_listing(&pool, listing_id, false).await;
+        assert!(matches!(result, Err(ListingError::NotFound)));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_only_other_members_views_are_counted(pool: PgPool) {
+        let owner = insert_test_member(&pool).await;
+        let other = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, owner).await;
+        let views = ViewCounter::default();
+
+        let view = |claims: Option<Claims>| {
+            get_listing(State(pool.clone()), State(views.clone()), claims, Path(listing_id))
+        };
+        let body = |response: axum::response::Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let response = view(Some(claims_for(other))).await.into_response();
+        assert!(body(response).await.get("view_count").is_none());
+        assert!(view(None).await.is_ok());
+        assert!(view(Some(claims_for(owner))).await.is_ok());
+        assert_eq!(views.flush(&pool).await.unwrap(), 1);
+        assert_eq!(listings::get_view_count(&pool, listing_id).await.unwrap(), 2);
+
+        // The owner's previews don't count, but the owner sees the total
+        let response = view(Some(claims_for(owner))).await.into_response();
+        assert_eq!(body(response).await["view_count"], 2);
+        assert_eq!(views.flush(&pool).await.unwrap(), 0);
+        assert_eq!(listings::get_view_count(&pool, listing_id).await.unwrap(), 2);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_only_admins_can_archive_others_listings(pool: PgPool) {
+        let config = test_config();
+        let owner = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, owner).await;
+        let token_for = |member_id: Uuid, role: Role| {
+            let member = Member {
+                id: member_id,
+                ..test_member(role)
+            };
+            auth::generate_token(&member, &config).unwrap()
+        };
+        let mut app = routes::create_router(pool.clone(), config.clone(), Default::default());
+        let mut archive = |token: String| {
+            let request = Request::builder()
+                .method("POST")
+                .uri(format!("/api/admin/listings/{}/archive", listing_id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"reason": "Counterfeit organic label"}"#))
+                .unwrap();
+            app.call(request)
+        };
+
+        // Members can't use the admin route, even on their own listing
+        let member = insert_test_member(&pool).await;
+        let response = archive(token_for(member, Role::Member)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = archive(token_for(owner, Role::Member)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let listing = listings::get_listing(&pool, listing_id, true).await.unwrap();
+        assert_eq!(listing.availability, AvailabilityStatus::Available.to_string());
+
+        let admin = insert_test_member(&pool).await;
+        let response = archive(token_for(admin, Role::Admin)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let listing = listings::get_listing(&pool, listing_id, true).await.unwrap();
+        assert_eq!(listing.availability, AvailabilityStatus::Archived.to_string());
+
+        let entries = audit::get_audit_log(&pool, None, None).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor_id, admin);
+        assert_eq!(entries[0].action, "ListingArchived");
+        assert_eq!(entries[0].target, listing_id.to_string());
+        assert_eq!(entries[0].metadata.0["reason"], "Counterfeit organic label");
+
+        let notifications = notifications::get_notifications(&pool, owner).await.unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].notification_type, "ListingArchived");
+        assert!(notifications[0].message.contains("Counterfeit organic label"));
+
+        // Archiving twice is a conflict and isn't audited again
+        let response = archive(token_for(admin, Role::Admin)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(audit::get_audit_log(&pool, None, None).await.unwrap().len(), 1);
+    }
+}
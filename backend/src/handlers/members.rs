@@ -0,0 +1,19 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use sqlx::PgPool;
+
+use crate::{
+    auth::{self, Claims},
+    error::DoftaError,
+    members,
+};
+
+/// Erase the current member's personal data and revoke the token used
+pub async fn delete_me(
+    State(pool): State<PgPool>,
+    claims: Claims,
+) -> Result<impl IntoResponse, DoftaError> {
+    members::delete_member_data(&pool, claims.sub).await?;
+    auth::logout(&pool, &claims).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
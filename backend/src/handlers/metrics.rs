@@ -0,0 +1,12 @@
+use axum::{extract::State, http::header, response::IntoResponse};
+use sqlx::PgPool;
+
+use crate::metrics;
+
+/// Expose request and database pool metrics for Prometheus to scrape
+pub async fn metrics(State(pool): State<PgPool>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        metrics::render(&pool),
+    )
+}
@@ -1,3 +1,14 @@
 pub mod auth;
 pub mod listings;
 pub mod orders;
+pub mod disputes;
+pub mod fees;
+pub mod audit;
+pub mod governance;
+pub mod reputation;
+pub mod notifications;
+pub mod reports;
+pub mod metrics;
+pub mod health;
+pub mod search;
+pub mod members;
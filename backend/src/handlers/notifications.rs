@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+    Json,
+};
+use sqlx::PgPool;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+use crate::{auth::Claims, error::DoftaError, notifications};
+
+/// List the current member's notifications
+pub async fn get_notifications(
+    State(pool): State<PgPool>,
+    claims: Claims,
+) -> Result<impl IntoResponse, DoftaError> {
+    let notifications = notifications::get_notifications(&pool, claims.sub).await?;
+
+    Ok(Json(notifications))
+}
+
+/// Mark one of the current member's notifications as read
+pub async fn mark_read(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let notification = notifications::mark_read(&pool, id, claims.sub).await?;
+
+    Ok(Json(notification))
+}
+
+/// Stream the current member's new notifications as Server-Sent Events
+///
+/// Each event's ID is the notification ID, so a reconnecting client's
+/// `Last-Event-ID` header replays anything it missed.
+pub async fn stream_notifications(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, DoftaError> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Uuid>().ok());
+
+    let notifications = notifications::subscribe(&pool, claims.sub, last_event_id).await?;
+    let events = notifications.map(|notification| {
+        Event::default()
+            .id(notification.id.to_string())
+            .event("notification")
+            .json_data(&notification)
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
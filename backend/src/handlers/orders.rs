@@ -1,43 +1,92 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
-use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
     auth::Claims,
+    config::Config,
     error::DoftaError,
-    models::{Order, OrderStatus},
-    orders::{self, CreateOrderData},
+    models::OrderStatus,
+    notifications,
+    orders::{self, CancelOutcome, CreateOrderData, OrderCursor},
 };
 
 #[derive(Debug, Deserialize)]
 pub struct CreateOrderRequest {
     pub listing_id: Uuid,
-    pub quantity: i32,
+    /// Fractional quantities are allowed unless the listing is sold in whole units
+    pub quantity: Decimal,
+}
+
+impl From<CreateOrderRequest> for CreateOrderData {
+    fn from(request: CreateOrderRequest) -> Self {
+        Self {
+            listing_id: request.listing_id,
+            quantity: request.quantity,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrdersQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderPageQuery {
+    /// Opaque cursor from the previous page's `next_cursor`
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl OrderPageQuery {
+    fn cursor(&self) -> Result<Option<OrderCursor>, DoftaError> {
+        Ok(self.cursor.as_deref().map(OrderCursor::decode).transpose()?)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetEscrowRequest {
+    pub escrow_order_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendMessageRequest {
+    pub body: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateOrderStatusRequest {
     pub status: String,
+    /// Note for the buyer when accepting or rejecting
+    pub note: Option<String>,
 }
 
 /// Create a new order
 pub async fn create_order(
     State(pool): State<PgPool>,
+    State(config): State<Config>,
     claims: Claims,
     Json(payload): Json<CreateOrderRequest>,
 ) -> Result<impl IntoResponse, DoftaError> {
-    let data = CreateOrderData {
-        listing_id: payload.listing_id,
-        quantity: payload.quantity,
-    };
+    let data = CreateOrderData::from(payload);
 
-    let order = orders::create_order(&pool, claims.sub, data).await?;
+    let order = orders::create_order(
+        &pool,
+        claims.sub,
+        data,
+        config.max_order_quantity,
+        config.duplicate_order_window_secs,
+    )
+    .await?;
 
     Ok((StatusCode::CREATED, Json(order)))
 }
@@ -46,13 +95,41 @@ pub async fn create_order(
 pub async fn get_my_orders(
     State(pool): State<PgPool>,
     claims: Claims,
+    Query(query): Query<OrdersQuery>,
 ) -> Result<impl IntoResponse, DoftaError> {
-    let mut buyer_orders = orders::get_orders_by_buyer(&pool, claims.sub).await?;
-    let seller_orders = orders::get_orders_by_seller(&pool, claims.sub).await?;
+    let orders = match query.status {
+        Some(status) => {
+            let status = status
+                .parse::<OrderStatus>()
+                .map_err(DoftaError::InvalidInput)?;
+            orders::get_orders_for_member_and_status(&pool, claims.sub, status).await?
+        }
+        None => orders::get_orders_for_member(&pool, claims.sub).await?,
+    };
+
+    Ok(Json(orders))
+}
 
-    buyer_orders.extend(seller_orders);
+/// Page through the orders the current user placed, newest first
+pub async fn get_my_purchases(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<OrderPageQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let page = orders::get_orders_by_buyer(&pool, claims.sub, query.cursor()?, query.limit).await?;
 
-    Ok(Json(buyer_orders))
+    Ok(Json(page))
+}
+
+/// Page through the orders placed with the current user, newest first
+pub async fn get_my_sales(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<OrderPageQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let page = orders::get_orders_by_seller(&pool, claims.sub, query.cursor()?, query.limit).await?;
+
+    Ok(Json(page))
 }
 
 /// Get a single order by ID
@@ -60,6 +137,24 @@ pub async fn get_order(
     State(pool): State<PgPool>,
     claims: Claims,
     Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let order = orders::get_order_with_listing(&pool, id).await?;
+
+    // Verify user is buyer or seller
+    if order.order.buyer_id != claims.sub && order.order.seller_id != claims.sub {
+        return Err(DoftaError::Forbidden(
+            "You can only view your own orders".to_string(),
+        ));
+    }
+
+    Ok(Json(order))
+}
+
+/// Get the status history of an order
+pub async fn get_order_history(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, DoftaError> {
     let order = orders::get_order(&pool, id).await?;
 
@@ -70,12 +165,62 @@ pub async fn get_order(
         ));
     }
 
+    let history = orders::get_order_history(&pool, id).await?;
+
+    Ok(Json(history))
+}
+
+/// Post a message to the order's buyer/seller thread
+pub async fn send_order_message(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SendMessageRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let message = orders::send_order_message(&pool, id, claims.sub, &payload.body).await?;
+
+    Ok((StatusCode::CREATED, Json(message)))
+}
+
+/// Get the order's buyer/seller thread, oldest first
+pub async fn get_order_messages(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let messages = orders::get_order_messages(&pool, id, claims.sub).await?;
+
+    Ok(Json(messages))
+}
+
+/// Quote how much the buyer must lock in escrow for an order
+pub async fn get_order_quote(
+    State(pool): State<PgPool>,
+    State(config): State<Config>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let quote = orders::quote_order(&pool, id, claims.sub, config.cooperative_fee_percentage).await?;
+
+    Ok(Json(quote))
+}
+
+/// Link an order to the NEAR escrow order the buyer funded
+pub async fn set_escrow(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SetEscrowRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let order = orders::set_escrow_order_id(&pool, id, claims.sub, &payload.escrow_order_id).await?;
+
     Ok(Json(order))
 }
 
 /// Update order status
 pub async fn update_order_status(
     State(pool): State<PgPool>,
+    State(config): State<Config>,
     claims: Claims,
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateOrderStatusRequest>,
@@ -96,7 +241,7 @@ pub async fn update_order_status(
                     "Only seller can accept order".to_string(),
                 ));
             }
-            orders::accept_order(&pool, id, claims.sub).await?
+            orders::accept_order(&pool, id, claims.sub, payload.note.as_deref()).await?
         }
         OrderStatus::Rejected => {
             if order.seller_id != claims.sub {
@@ -104,10 +249,38 @@ pub async fn update_order_status(
                     "Only seller can reject order".to_string(),
                 ));
             }
-            orders::reject_order(&pool, id, claims.sub).await?
+            orders::reject_order(&pool, id, claims.sub, payload.note.as_deref()).await?
+        }
+        OrderStatus::Shipped => {
+            if order.seller_id != claims.sub {
+                return Err(DoftaError::Forbidden(
+                    "Only seller can ship order".to_string(),
+                ));
+            }
+            orders::ship_order(&pool, id, claims.sub).await?
+        }
+        OrderStatus::Delivered => {
+            if order.buyer_id != claims.sub {
+                return Err(DoftaError::Forbidden(
+                    "Only buyer can mark order delivered".to_string(),
+                ));
+            }
+            orders::mark_delivered(&pool, id, claims.sub).await?
         }
         OrderStatus::Completed => {
-            orders::complete_order(&pool, id).await?
+            if order.buyer_id != claims.sub {
+                return Err(DoftaError::Forbidden(
+                    "Only buyer can complete order".to_string(),
+                ));
+            }
+            let mut tx = pool.begin().await?;
+            let completed = orders::complete_order(&mut tx, id, claims.sub, config.cooperative_fee_percentage).await?;
+            tx.commit().await?;
+
+            for notification in &completed.notifications {
+                notifications::publish(notification);
+            }
+            completed.order
         }
         OrderStatus::Cancelled => {
             if order.buyer_id != claims.sub {
@@ -115,7 +288,17 @@ pub async fn update_order_status(
                     "Only buyer can cancel order".to_string(),
                 ));
             }
-            orders::cancel_order(&pool, id, claims.sub).await?
+            match orders::cancel_order(&pool, id, claims.sub).await? {
+                CancelOutcome::Cancelled(order) => order,
+                CancelOutcome::RefundRequired { order, escrow_order_id } => {
+                    // The seller or contract owner still has to call `refund_order` on chain
+                    let body = json!({
+                        "order": order,
+                        "refund_required": { "escrow_order_id": escrow_order_id },
+                    });
+                    return Ok((StatusCode::ACCEPTED, Json(body)).into_response());
+                }
+            }
         }
         _ => {
             return Err(DoftaError::InvalidInput(
@@ -124,5 +307,74 @@ pub async fn update_order_status(
         }
     };
 
-    Ok(Json(updated_order))
+    Ok(Json(updated_order).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth;
+    use crate::models::Role;
+    use crate::test_utils::{insert_test_listing, insert_test_member, test_config, test_member};
+
+    #[test]
+    fn test_create_order_request_integer_quantity() {
+        let listing_id = Uuid::new_v4();
+        let request: CreateOrderRequest = serde_json::from_value(serde_json::json!({
+            "listing_id": listing_id,
+            "quantity": 3,
+        }))
+        .unwrap();
+
+        let data = CreateOrderData::from(request);
+
+        assert_eq!(data.listing_id, listing_id);
+        assert_eq!(data.quantity, Decimal::new(3, 0));
+        assert_eq!(
+            orders::calculate_total_amount(Decimal::new(299, 2), data.quantity),
+            Some(Decimal::new(897, 2))
+        );
+    }
+
+    #[test]
+    fn test_create_order_request_fractional_quantity() {
+        let request: CreateOrderRequest = serde_json::from_value(serde_json::json!({
+            "listing_id": Uuid::new_v4(),
+            "quantity": 2.5,
+        }))
+        .unwrap();
+
+        assert_eq!(CreateOrderData::from(request).quantity, Decimal::new(25, 1));
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_order_rejects_fractional_pieces(pool: PgPool) {
+        let config = test_config();
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        sqlx::query("UPDATE product_listings SET unit_of_measure = 'piece' WHERE id = $1")
+            .bind(listing_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut member = test_member(Role::Member);
+        member.id = buyer;
+        let token = auth::generate_token(&member, &config).unwrap();
+        let claims = auth::decode_token(&token, &config.jwt_secret).unwrap();
+        let request = |quantity: serde_json::Value| -> CreateOrderRequest {
+            serde_json::from_value(json!({ "listing_id": listing_id, "quantity": quantity })).unwrap()
+        };
+
+        let response = create_order(State(pool.clone()), State(config.clone()), claims.clone(), Json(request(json!(1.5))))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = create_order(State(pool.clone()), State(config), claims, Json(request(json!(2))))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
 }
@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{auth::Claims, error::DoftaError, middleware::auth::AdminClaims, reports};
+
+#[derive(Debug, Deserialize)]
+pub struct SalesReportQuery {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// Get the current member's sales report for a date range
+pub async fn sales_report(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<SalesReportQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let report = reports::sales_report(&pool, claims.sub, query.from, query.to).await?;
+
+    Ok(Json(report))
+}
+
+/// Get the current member's earnings for a date range
+pub async fn earnings(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<SalesReportQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let earnings = reports::member_earnings(&pool, claims.sub, query.from, query.to).await?;
+
+    Ok(Json(earnings))
+}
+
+/// Get the platform's cooperative fee income by month (admin only)
+pub async fn platform_revenue(
+    State(pool): State<PgPool>,
+    _admin: AdminClaims,
+    Query(query): Query<SalesReportQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let revenue = reports::platform_revenue(&pool, query.from, query.to).await?;
+
+    Ok(Json(revenue))
+}
+
+/// Download the current member's orders for a date range as CSV
+pub async fn export_sales_csv(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Query(query): Query<SalesReportQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let csv = reports::export_sales_csv(&pool, claims.sub, query.from, query.to).await?;
+    let filename = format!(
+        "sales-{}-{}.csv",
+        query.from.format("%Y%m%d"),
+        query.to.format("%Y%m%d")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        csv,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{auth, models::Role, routes, test_utils::{test_config, test_member}};
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::Service;
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_platform_revenue_requires_admin(pool: PgPool) {
+        let config = test_config();
+        let mut app = routes::create_router(pool, config.clone(), Default::default());
+
+        for (role, expected) in [(Role::Member, StatusCode::FORBIDDEN), (Role::Admin, StatusCode::OK)] {
+            let token = auth::generate_token(&test_member(role), &config).unwrap();
+            let request = Request::builder()
+                .uri("/api/reports/revenue?from=2024-01-01T00:00:00Z&to=2024-12-31T23:59:59Z")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap();
+
+            std::future::poll_fn(|cx| Service::<Request<Body>>::poll_ready(&mut app, cx)).await.unwrap();
+            let response = app.call(request).await.unwrap();
+            assert_eq!(response.status(), expected);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::Claims, error::DoftaError, reputation};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRatingRequest {
+    pub transaction_id: Uuid,
+    pub rated_id: Uuid,
+    pub score: i32,
+}
+
+/// Rate the other party to a completed transaction
+pub async fn create_rating(
+    State(pool): State<PgPool>,
+    claims: Claims,
+    Json(payload): Json<CreateRatingRequest>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let rating = reputation::create_rating(
+        &pool,
+        payload.transaction_id,
+        claims.sub,
+        payload.rated_id,
+        payload.score,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(rating)))
+}
+
+/// Get a member's aggregated rating summary
+pub async fn get_member_reputation(
+    State(pool): State<PgPool>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let reputation = reputation::get_member_reputation(&pool, id).await?;
+
+    Ok(Json(reputation))
+}
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::{
+    error::DoftaError,
+    search::{self, SearchFilters},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub category: Option<String>,
+    pub include_proposals: Option<bool>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+/// Search listings, and optionally proposals, ranked by relevance
+pub async fn search(
+    State(pool): State<PgPool>,
+    Query(query): Query<SearchQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let filters = SearchFilters {
+        category: query.category,
+        include_proposals: query.include_proposals.unwrap_or(false),
+        limit: query.limit,
+    };
+
+    let results = search::search(&pool, &query.q, &filters).await?;
+
+    Ok(Json(results))
+}
+
+/// Suggest listing names starting with the typed prefix
+pub async fn suggest(
+    State(pool): State<PgPool>,
+    Query(query): Query<SuggestQuery>,
+) -> Result<impl IntoResponse, DoftaError> {
+    let limit = query.limit.unwrap_or(search::DEFAULT_SUGGESTION_LIMIT);
+    let names = search::suggest(&pool, &query.q, limit).await?;
+
+    Ok(Json(names))
+}
@@ -3,10 +3,28 @@ pub mod db;
 pub mod config;
 pub mod models;
 pub mod auth;
+pub mod members;
+pub mod security;
 pub mod listings;
 pub mod orders;
+pub mod disputes;
+pub mod transactions;
+pub mod money;
+pub mod units;
+pub mod fees;
+pub mod audit;
+pub mod governance;
+pub mod reputation;
+pub mod notifications;
+pub mod reports;
+pub mod search;
+pub mod metrics;
 pub mod handlers;
 pub mod middleware;
 pub mod routes;
+pub mod shutdown;
+
+#[cfg(test)]
+mod test_utils;
 
 pub use error::{DoftaError, Result};
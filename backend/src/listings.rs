@@ -1,37 +1,115 @@
-use crate::error::ListingError;
-use crate::models::{ProductListing, AvailabilityStatus};
-use chrono::Utc;
+use crate::error::{validation_result, BatchRowError, ListingError, ValidationError};
+use crate::models::{ProductListing, AvailabilityStatus, Category, NotificationType, Paginated, SavedSearch};
+use crate::notifications;
+use crate::money::round_money;
+use crate::units::UnitOfMeasure;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use sqlx::PgPool;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgArguments;
+use sqlx::query::QueryAs;
+use sqlx::{PgExecutor, PgPool, Postgres, QueryBuilder};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
+/// Default number of listings returned per search page
+pub const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+/// Maximum number of listings returned per search page
+pub const MAX_SEARCH_LIMIT: i64 = 100;
+
+/// Maximum number of listings created in one bulk request
+pub const MAX_BULK_LISTINGS: usize = 100;
+
+/// Maximum number of listings fetched in one batch read
+pub const MAX_BATCH_IDS: usize = 100;
+
+/// Quantity below which an inventory listing is flagged as low on stock
+pub const DEFAULT_LOW_STOCK_THRESHOLD: Decimal = Decimal::TEN;
+
+/// How often buffered listing views are written to the database
+pub const VIEW_FLUSH_INTERVAL_SECS: u64 = 30;
+
+/// How often listings past their expiry are archived
+pub const EXPIRY_SWEEP_INTERVAL_SECS: u64 = 900;
+
+/// Maximum length of a saved search's name in characters
+pub const MAX_SAVED_SEARCH_NAME_LENGTH: usize = 100;
+
+/// Maximum length of a moderator's reason for archiving a listing
+pub const MAX_ARCHIVE_REASON_LENGTH: usize = 500;
+
 /// Data for creating a new product listing
 #[derive(Debug, Clone)]
 pub struct CreateListingData {
     pub name: String,
     pub description: String,
-    pub quantity: Decimal,
+    pub category: String,
+    pub quantity_available: Decimal,
     pub unit_price: Decimal,
+    pub unit_of_measure: String,
+    /// When the listing should be archived, e.g. at the end of the season
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 /// Data for updating an existing product listing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct UpdateListingData {
     pub name: Option<String>,
     pub description: Option<String>,
-    pub quantity: Option<Decimal>,
+    pub category: Option<String>,
+    pub quantity_available: Option<Decimal>,
     pub unit_price: Option<Decimal>,
+    pub unit_of_measure: Option<String>,
     pub availability: Option<AvailabilityStatus>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Version the caller last read; the update fails with
+    /// [`ListingError::Conflict`] if the listing has changed since
+    pub expected_version: Option<i32>,
+}
+
+/// Ordering applied to listing search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListingSort {
+    /// Most recently created first, or by relevance when searching
+    #[default]
+    Newest,
+    /// Highest average seller rating first; unrated sellers sort last
+    ReputationDesc,
 }
 
 /// Search and filter criteria for product listings
-#[derive(Debug, Clone, Default)]
+///
+/// Serialized as JSON when a member saves a search; missing fields are unset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ListingFilters {
     pub search_term: Option<String>,
     pub category: Option<String>,
     pub min_price: Option<Decimal>,
     pub max_price: Option<Decimal>,
     pub availability: Option<AvailabilityStatus>,
+    /// Only listings with at least this quantity available
+    pub min_quantity: Option<Decimal>,
+    /// Only listings with at most this quantity available
+    pub max_quantity: Option<Decimal>,
+    pub sort: ListingSort,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl ListingFilters {
+    /// Resolve the page window, applying defaults and clamping out-of-range values
+    pub fn page_window(&self) -> (i64, i64) {
+        let limit = self
+            .limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_SEARCH_LIMIT);
+        let offset = self.offset.unwrap_or(0).max(0);
+        (limit, offset)
+    }
 }
 
 /// Create a new product listing
@@ -40,59 +118,130 @@ pub async fn create_listing(
     member_id: Uuid,
     data: CreateListingData,
 ) -> Result<ProductListing, ListingError> {
-    // Validate the data
-    if data.name.trim().is_empty() {
-        return Err(ListingError::InvalidData("Product name cannot be empty".to_string()));
+    validate_create_data(&data)?;
+    
+    insert_listing(pool, member_id, &data).await
+}
+
+/// Create many listings at once, all or nothing
+///
+/// Every row is validated before anything is written, and the inserts share
+/// one transaction. If any row is invalid, nothing is created and the error
+/// lists each failing row by its index in `listings`.
+pub async fn create_listings_bulk(
+    pool: &PgPool,
+    member_id: Uuid,
+    listings: Vec<CreateListingData>,
+) -> Result<Vec<ProductListing>, ListingError> {
+    if listings.is_empty() {
+        return Err(ListingError::InvalidData("Batch must contain at least one listing".to_string()));
     }
     
-    if data.description.trim().is_empty() {
-        return Err(ListingError::InvalidData("Product description cannot be empty".to_string()));
+    if listings.len() > MAX_BULK_LISTINGS {
+        return Err(ListingError::InvalidData(format!(
+            "Batch cannot contain more than {} listings",
+            MAX_BULK_LISTINGS
+        )));
     }
     
-    if data.quantity <= Decimal::ZERO {
-        return Err(ListingError::InvalidData("Quantity must be positive".to_string()));
+    let errors: Vec<BatchRowError> = listings
+        .iter()
+        .enumerate()
+        .filter_map(|(index, data)| match validate_create_data(data) {
+            Err(ListingError::Validation(errors)) => Some(BatchRowError {
+                index,
+                message: errors.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("; "),
+            }),
+            _ => None,
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(ListingError::InvalidBatch(errors));
     }
     
-    if data.unit_price <= Decimal::ZERO {
-        return Err(ListingError::InvalidData("Unit price must be positive".to_string()));
+    let mut tx = pool.begin().await?;
+    let mut created = Vec::with_capacity(listings.len());
+    for data in &listings {
+        created.push(insert_listing(&mut *tx, member_id, data).await?);
     }
+    tx.commit().await?;
+    
+    Ok(created)
+}
+
+/// Validate the data for a new listing, reporting every invalid field
+fn validate_create_data(data: &CreateListingData) -> Result<(), ListingError> {
+    let mut errors = listing_field_errors(
+        &data.name,
+        &data.description,
+        data.quantity_available,
+        data.unit_price,
+    );
     
+    push_field_error(&mut errors, "category", validate_category(&data.category));
+    push_field_error(&mut errors, "unit_of_measure", validate_unit_of_measure(&data.unit_of_measure));
+    
+    if let Some(expires_at) = data.expires_at {
+        push_field_error(&mut errors, "expires_at", validate_expires_at(expires_at));
+    }
+    
+    validation_result(errors).map_err(ListingError::Validation)
+}
+
+/// Insert a validated listing as available
+async fn insert_listing<'e, E>(
+    executor: E,
+    member_id: Uuid,
+    data: &CreateListingData,
+) -> Result<ProductListing, ListingError>
+where
+    E: PgExecutor<'e>,
+{
     let listing_id = Uuid::new_v4();
     let now = Utc::now();
     let availability = AvailabilityStatus::Available.to_string();
+    let unit_of_measure = validate_unit_of_measure(&data.unit_of_measure)?.to_string();
     
     let listing = sqlx::query_as::<_, ProductListing>(
-        "INSERT INTO product_listings (id, member_id, name, description, quantity, unit_price, availability, created_at, updated_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-         RETURNING id, member_id, name, description, quantity, unit_price, availability, created_at, updated_at"
+        "INSERT INTO product_listings (id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, expires_at, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+         RETURNING id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, version, expires_at, created_at, updated_at"
     )
     .bind(listing_id)
     .bind(member_id)
     .bind(&data.name)
     .bind(&data.description)
-    .bind(data.quantity)
+    .bind(&data.category)
+    .bind(data.quantity_available)
     .bind(data.unit_price)
+    .bind(&unit_of_measure)
     .bind(&availability)
+    .bind(data.expires_at)
     .bind(now)
     .bind(now)
-    .fetch_one(pool)
-    .await
-    .map_err(|e| ListingError::InvalidData(format!("Failed to create listing: {}", e)))?;
+    .fetch_one(executor)
+    .await?;
     
     Ok(listing)
 }
 
 /// Get a product listing by ID
+///
+/// Archived listings are only returned with `include_archived`, for owner
+/// views; public reads treat them as not found, matching search.
 pub async fn get_listing(
     pool: &PgPool,
     listing_id: Uuid,
+    include_archived: bool,
 ) -> Result<ProductListing, ListingError> {
     let listing = sqlx::query_as::<_, ProductListing>(
-        "SELECT id, member_id, name, description, quantity, unit_price, availability, created_at, updated_at
+        "SELECT id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, version, expires_at, created_at, updated_at
          FROM product_listings
-         WHERE id = $1"
+         WHERE id = $1 AND ($2 OR availability != $3)"
     )
     .bind(listing_id)
+    .bind(include_archived)
+    .bind(AvailabilityStatus::Archived.to_string())
     .fetch_optional(pool)
     .await
     .map_err(|_| ListingError::NotFound)?
@@ -101,6 +250,241 @@ pub async fn get_listing(
     Ok(listing)
 }
 
+/// Get several listings by ID in one query
+///
+/// IDs that don't match a listing are skipped rather than treated as errors.
+/// Archived listings follow the same `include_archived` rule as `get_listing`.
+pub async fn get_listings_by_ids(
+    pool: &PgPool,
+    ids: &[Uuid],
+    include_archived: bool,
+) -> Result<Vec<ProductListing>, ListingError> {
+    if ids.len() > MAX_BATCH_IDS {
+        return Err(ListingError::InvalidData(format!(
+            "Cannot fetch more than {} listings at once",
+            MAX_BATCH_IDS
+        )));
+    }
+    
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    let listings = sqlx::query_as::<_, ProductListing>(
+        "SELECT id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, version, expires_at, created_at, updated_at
+         FROM product_listings
+         WHERE id = ANY($1) AND ($2 OR availability != $3)
+         ORDER BY created_at, id"
+    )
+    .bind(ids)
+    .bind(include_archived)
+    .bind(AvailabilityStatus::Archived.to_string())
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(listings)
+}
+
+/// Get all of a member's listings, including out-of-stock and archived ones
+///
+/// These are owner views, so each listing carries its view count.
+pub async fn get_listings_by_member(
+    pool: &PgPool,
+    member_id: Uuid,
+) -> Result<Vec<ProductListing>, ListingError> {
+    let listings = sqlx::query_as::<_, ProductListing>(
+        "SELECT id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, version, expires_at, view_count, created_at, updated_at
+         FROM product_listings
+         WHERE member_id = $1
+         ORDER BY updated_at DESC, id"
+    )
+    .bind(member_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(listings)
+}
+
+/// One listing's stock in a seller's inventory
+#[derive(Debug, Clone, Serialize)]
+pub struct InventoryItem {
+    pub listing_id: Uuid,
+    pub name: String,
+    pub unit_of_measure: String,
+    pub quantity_available: Decimal,
+    pub unit_price: Decimal,
+    /// `quantity_available * unit_price`, rounded to the cent
+    pub stock_value: Decimal,
+    pub availability: String,
+    pub low_stock: bool,
+    pub archived: bool,
+}
+
+/// A seller's stock across their listings
+#[derive(Debug, Clone, Serialize)]
+pub struct SellerInventory {
+    pub seller_id: Uuid,
+    pub low_stock_threshold: Decimal,
+    pub items: Vec<InventoryItem>,
+    /// Totals cover active listings only, never archived ones
+    pub total_value: Decimal,
+    pub active_listings: i64,
+    pub low_stock_listings: i64,
+}
+
+/// Summarize a seller's stock and flag listings below `low_stock_threshold`
+///
+/// Archived listings never count towards the totals; with `include_archived`
+/// they are still listed, flagged as archived.
+pub async fn seller_inventory(
+    pool: &PgPool,
+    seller_id: Uuid,
+    low_stock_threshold: Decimal,
+    include_archived: bool,
+) -> Result<SellerInventory, ListingError> {
+    if low_stock_threshold < Decimal::ZERO {
+        return Err(ListingError::InvalidData("Low stock threshold cannot be negative".to_string()));
+    }
+    
+    let listings = get_listings_by_member(pool, seller_id).await?;
+    
+    Ok(build_inventory(seller_id, listings, low_stock_threshold, include_archived))
+}
+
+/// Aggregate listings into an inventory, see [`seller_inventory`]
+pub fn build_inventory(
+    seller_id: Uuid,
+    listings: Vec<ProductListing>,
+    low_stock_threshold: Decimal,
+    include_archived: bool,
+) -> SellerInventory {
+    let archived_status = AvailabilityStatus::Archived.to_string();
+    let mut inventory = SellerInventory {
+        seller_id,
+        low_stock_threshold,
+        items: Vec::new(),
+        total_value: Decimal::ZERO,
+        active_listings: 0,
+        low_stock_listings: 0,
+    };
+    
+    for listing in listings {
+        let archived = listing.availability == archived_status;
+        if archived && !include_archived {
+            continue;
+        }
+        
+        let stock_value = round_money(listing.quantity_available * listing.unit_price);
+        let low_stock = !archived && listing.quantity_available < low_stock_threshold;
+        
+        if !archived {
+            inventory.total_value += stock_value;
+            inventory.active_listings += 1;
+            if low_stock {
+                inventory.low_stock_listings += 1;
+            }
+        }
+        
+        inventory.items.push(InventoryItem {
+            listing_id: listing.id,
+            name: listing.name,
+            unit_of_measure: listing.unit_of_measure,
+            quantity_available: listing.quantity_available,
+            unit_price: listing.unit_price,
+            stock_value,
+            availability: listing.availability,
+            low_stock,
+            archived,
+        });
+    }
+    
+    inventory
+}
+
+/// Archive every listing whose expiry has passed, returning how many were archived
+///
+/// Meant to run periodically; search already hides expired listings in between.
+pub async fn expire_stale_listings(pool: &PgPool) -> Result<u64, ListingError> {
+    let now = Utc::now();
+    let result = sqlx::query(
+        "UPDATE product_listings SET availability = $1, updated_at = $2, version = version + 1
+         WHERE expires_at <= $2 AND availability != $1"
+    )
+    .bind(AvailabilityStatus::Archived.to_string())
+    .bind(now)
+    .execute(pool)
+    .await?;
+    
+    Ok(result.rows_affected())
+}
+
+/// Get how many times a listing has been viewed by other members
+///
+/// Views still buffered in a [`ViewCounter`] are not included.
+pub async fn get_view_count(pool: &PgPool, listing_id: Uuid) -> Result<i64, ListingError> {
+    let view_count = sqlx::query_scalar::<_, i64>("SELECT view_count FROM product_listings WHERE id = $1")
+        .bind(listing_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(ListingError::NotFound)?;
+    
+    Ok(view_count)
+}
+
+/// Buffers listing views in memory so each view doesn't cost a write
+///
+/// Clones share the same buffer. Views are written by [`ViewCounter::flush`],
+/// which the server runs every [`VIEW_FLUSH_INTERVAL_SECS`] and on shutdown.
+#[derive(Debug, Clone, Default)]
+pub struct ViewCounter {
+    pending: Arc<Mutex<HashMap<Uuid, i64>>>,
+}
+
+impl ViewCounter {
+    /// Count one view of a listing
+    pub fn record(&self, listing_id: Uuid) {
+        *self.lock().entry(listing_id).or_insert(0) += 1;
+    }
+    
+    /// Write buffered views to the database in one statement
+    ///
+    /// Returns the number of listings updated. On failure the views are put
+    /// back so the next flush can retry them.
+    pub async fn flush(&self, pool: &PgPool) -> Result<usize, ListingError> {
+        let pending = std::mem::take(&mut *self.lock());
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        
+        let (ids, counts): (Vec<Uuid>, Vec<i64>) = pending.iter().map(|(id, count)| (*id, *count)).unzip();
+        let result = sqlx::query(
+            "UPDATE product_listings p
+             SET view_count = p.view_count + v.views
+             FROM UNNEST($1::UUID[], $2::BIGINT[]) AS v(id, views)
+             WHERE p.id = v.id"
+        )
+        .bind(&ids)
+        .bind(&counts)
+        .execute(pool)
+        .await;
+        
+        match result {
+            Ok(_) => Ok(pending.len()),
+            Err(e) => {
+                let mut buffer = self.lock();
+                for (id, count) in pending {
+                    *buffer.entry(id).or_insert(0) += count;
+                }
+                Err(e.into())
+            }
+        }
+    }
+    
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, i64>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
 /// Update an existing product listing
 pub async fn update_listing(
     pool: &PgPool,
@@ -109,88 +493,247 @@ pub async fn update_listing(
     data: UpdateListingData,
 ) -> Result<ProductListing, ListingError> {
     // First, verify the listing exists and belongs to the member
-    let existing = get_listing(pool, listing_id).await?;
+    let existing = get_listing(pool, listing_id, true).await?;
     
     if existing.member_id != member_id {
         return Err(ListingError::Unauthorized);
     }
     
-    // Build update query dynamically based on what fields are provided
-    let mut updates = Vec::new();
-    let mut values: Vec<String> = Vec::new();
-    let mut param_count = 1;
+    validate_update_data(&data)?;
     
-    if let Some(name) = &data.name {
-        if name.trim().is_empty() {
-            return Err(ListingError::InvalidData("Product name cannot be empty".to_string()));
-        }
-        updates.push(format!("name = ${}", param_count));
-        values.push(name.clone());
-        param_count += 1;
+    if matches!(data.expected_version, Some(version) if version != existing.version) {
+        return Err(ListingError::Conflict);
     }
     
-    if let Some(description) = &data.description {
-        if description.trim().is_empty() {
-            return Err(ListingError::InvalidData("Product description cannot be empty".to_string()));
+    if let Some(to) = &data.availability {
+        let from = existing.availability.parse::<AvailabilityStatus>()
+            .map_err(|e| ListingError::InvalidData(format!("Invalid availability: {}", e)))?;
+        
+        if !is_valid_availability_transition(&from, to) {
+            return Err(ListingError::InvalidAvailabilityTransition(format!(
+                "Cannot change availability from {} to {}",
+                from, to
+            )));
         }
-        updates.push(format!("description = ${}", param_count));
-        values.push(description.clone());
-        param_count += 1;
     }
     
-    if let Some(quantity) = data.quantity {
-        if quantity <= Decimal::ZERO {
-            return Err(ListingError::InvalidData("Quantity must be positive".to_string()));
-        }
-        updates.push(format!("quantity = ${}", param_count));
-        values.push(quantity.to_string());
-        param_count += 1;
+    let mut query_builder = match build_update_query(listing_id, data) {
+        Some(query_builder) => query_builder,
+        // No updates provided, return existing listing
+        None => return Ok(existing),
+    };
+    
+    // A concurrent edit may have bumped the version since it was checked above
+    query_builder
+        .build_query_as::<ProductListing>()
+        .fetch_optional(pool)
+        .await?
+        .ok_or(ListingError::Conflict)
+}
+
+/// Validate that a category is one of the known [`Category`] values
+pub fn validate_category(category: &str) -> Result<(), ListingError> {
+    category.parse::<Category>().map(|_| ()).map_err(|_| {
+        let valid: Vec<String> = Category::ALL.iter().map(Category::to_string).collect();
+        ListingError::InvalidData(format!(
+            "Unknown category \"{}\"; expected one of {}",
+            category,
+            valid.join(", ")
+        ))
+    })
+}
+
+/// Check a unit of measure is one of the canonical units, in any accepted spelling
+pub fn validate_unit_of_measure(unit_of_measure: &str) -> Result<UnitOfMeasure, ListingError> {
+    unit_of_measure.parse::<UnitOfMeasure>().map_err(|_| {
+        let valid: Vec<String> = UnitOfMeasure::ALL.iter().map(UnitOfMeasure::to_string).collect();
+        ListingError::InvalidData(format!(
+            "Unknown unit of measure \"{}\"; expected one of {}",
+            unit_of_measure,
+            valid.join(", ")
+        ))
+    })
+}
+
+/// An expiry must still be ahead when it is set
+pub fn validate_expires_at(expires_at: DateTime<Utc>) -> Result<(), ListingError> {
+    if expires_at <= Utc::now() {
+        return Err(ListingError::InvalidData("Expiry must be in the future".to_string()));
+    }
+    
+    Ok(())
+}
+
+/// Validate the fields present in an update, reporting every invalid one
+fn validate_update_data(data: &UpdateListingData) -> Result<(), ListingError> {
+    let mut errors = Vec::new();
+    
+    if matches!(&data.name, Some(name) if name.trim().is_empty()) {
+        errors.push(ValidationError::new("name", "Product name cannot be empty"));
+    }
+    
+    if matches!(&data.description, Some(description) if description.trim().is_empty()) {
+        errors.push(ValidationError::new("description", "Product description cannot be empty"));
+    }
+    
+    if let Some(category) = &data.category {
+        push_field_error(&mut errors, "category", validate_category(category));
+    }
+    
+    if matches!(data.quantity_available, Some(quantity) if quantity <= Decimal::ZERO) {
+        errors.push(ValidationError::new("quantity_available", "Quantity must be positive"));
+    }
+    
+    if matches!(data.unit_price, Some(unit_price) if unit_price <= Decimal::ZERO) {
+        errors.push(ValidationError::new("unit_price", "Unit price must be positive"));
+    }
+    
+    if let Some(unit_of_measure) = &data.unit_of_measure {
+        push_field_error(&mut errors, "unit_of_measure", validate_unit_of_measure(unit_of_measure));
+    }
+    
+    if let Some(expires_at) = data.expires_at {
+        push_field_error(&mut errors, "expires_at", validate_expires_at(expires_at));
+    }
+    
+    validation_result(errors).map_err(ListingError::Validation)
+}
+
+/// Record a field's error from one of the single-value validators above
+fn push_field_error<T>(errors: &mut Vec<ValidationError>, field: &str, result: Result<T, ListingError>) {
+    if let Err(e) = result {
+        let message = match e {
+            ListingError::InvalidData(message) => message,
+            e => e.to_string(),
+        };
+        errors.push(ValidationError::new(field, message));
+    }
+}
+
+/// Build the UPDATE statement for the provided fields, binding each value with its
+/// column type. Returns `None` when there is nothing to update.
+fn build_update_query(
+    listing_id: Uuid,
+    data: UpdateListingData,
+) -> Option<QueryBuilder<'static, Postgres>> {
+    let mut query_builder = QueryBuilder::new("UPDATE product_listings SET ");
+    let mut fields = query_builder.separated(", ");
+    let mut has_updates = false;
+    
+    if let Some(name) = data.name {
+        fields.push("name = ").push_bind_unseparated(name);
+        has_updates = true;
+    }
+    
+    if let Some(description) = data.description {
+        fields.push("description = ").push_bind_unseparated(description);
+        has_updates = true;
+    }
+    
+    if let Some(category) = data.category {
+        fields.push("category = ").push_bind_unseparated(category);
+        has_updates = true;
+    }
+    
+    if let Some(quantity) = data.quantity_available {
+        fields.push("quantity_available = ").push_bind_unseparated(quantity);
+        has_updates = true;
     }
     
     if let Some(unit_price) = data.unit_price {
-        if unit_price <= Decimal::ZERO {
-            return Err(ListingError::InvalidData("Unit price must be positive".to_string()));
-        }
-        updates.push(format!("unit_price = ${}", param_count));
-        values.push(unit_price.to_string());
-        param_count += 1;
+        fields.push("unit_price = ").push_bind_unseparated(unit_price);
+        has_updates = true;
+    }
+    
+    if let Some(unit_of_measure) = data.unit_of_measure {
+        // Store the canonical spelling so listings compare by unit
+        let unit_of_measure = unit_of_measure
+            .parse::<UnitOfMeasure>()
+            .map(|unit| unit.to_string())
+            .unwrap_or(unit_of_measure);
+        fields.push("unit_of_measure = ").push_bind_unseparated(unit_of_measure);
+        has_updates = true;
     }
     
     if let Some(availability) = data.availability {
-        updates.push(format!("availability = ${}", param_count));
-        values.push(availability.to_string());
-        param_count += 1;
+        fields.push("availability = ").push_bind_unseparated(availability.to_string());
+        has_updates = true;
     }
     
-    if updates.is_empty() {
-        // No updates provided, return existing listing
-        return Ok(existing);
+    if let Some(expires_at) = data.expires_at {
+        fields.push("expires_at = ").push_bind_unseparated(expires_at);
+        has_updates = true;
     }
     
-    // Always update the updated_at timestamp
-    updates.push(format!("updated_at = ${}", param_count));
-    let now = Utc::now();
+    if !has_updates {
+        return None;
+    }
+    
+    // Always update the updated_at timestamp and bump the version
+    fields.push("updated_at = ").push_bind_unseparated(Utc::now());
+    fields.push("version = version + 1");
     
-    let query = format!(
-        "UPDATE product_listings SET {} WHERE id = ${} RETURNING id, member_id, name, description, quantity, unit_price, availability, created_at, updated_at",
-        updates.join(", "),
-        param_count + 1
+    query_builder.push(" WHERE id = ").push_bind(listing_id);
+    if let Some(version) = data.expected_version {
+        query_builder.push(" AND version = ").push_bind(version);
+    }
+    query_builder.push(
+        " RETURNING id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, version, expires_at, created_at, updated_at",
     );
     
-    // Note: This is a simplified version. In production, you'd use a query builder
-    // or handle the dynamic parameters more safely
-    let mut query_builder = sqlx::query_as::<_, ProductListing>(&query);
+    Some(query_builder)
+}
+
+/// Validate if an availability change is allowed in a regular update
+///
+/// Archived listings only come back through [`restore_listing`], so an edit can't
+/// un-archive one by accident.
+pub fn is_valid_availability_transition(from: &AvailabilityStatus, to: &AvailabilityStatus) -> bool {
+    match (from, to) {
+        // Setting the current value again is a no-op
+        (from, to) if from == to => true,
+        
+        (AvailabilityStatus::Available, AvailabilityStatus::OutOfStock) => true,
+        (AvailabilityStatus::OutOfStock, AvailabilityStatus::Available) => true,
+        (AvailabilityStatus::Available, AvailabilityStatus::Archived) => true,
+        (AvailabilityStatus::OutOfStock, AvailabilityStatus::Archived) => true,
+        
+        // Restoring an archived listing needs restore_listing
+        (AvailabilityStatus::Archived, _) => false,
+        
+        _ => false,
+    }
+}
+
+/// Restore an archived listing, making it available again
+pub async fn restore_listing(
+    pool: &PgPool,
+    listing_id: Uuid,
+    member_id: Uuid,
+) -> Result<ProductListing, ListingError> {
+    let existing = get_listing(pool, listing_id, true).await?;
+    
+    if existing.member_id != member_id {
+        return Err(ListingError::Unauthorized);
+    }
     
-    for value in values {
-        query_builder = query_builder.bind(value);
+    if existing.availability != AvailabilityStatus::Archived.to_string() {
+        return Err(ListingError::InvalidAvailabilityTransition(format!(
+            "Only archived listings can be restored, this one is {}",
+            existing.availability
+        )));
     }
     
-    let listing = query_builder
-        .bind(now)
-        .bind(listing_id)
-        .fetch_one(pool)
-        .await
-        .map_err(|e| ListingError::InvalidData(format!("Failed to update listing: {}", e)))?;
+    let listing = sqlx::query_as::<_, ProductListing>(
+        "UPDATE product_listings SET availability = $1, updated_at = $2, version = version + 1
+         WHERE id = $3
+         RETURNING id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, version, expires_at, created_at, updated_at"
+    )
+    .bind(AvailabilityStatus::Available.to_string())
+    .bind(Utc::now())
+    .bind(listing_id)
+    .fetch_one(pool)
+    .await?;
     
     Ok(listing)
 }
@@ -202,7 +745,7 @@ pub async fn delete_listing(
     member_id: Uuid,
 ) -> Result<(), ListingError> {
     // Verify the listing exists and belongs to the member
-    let existing = get_listing(pool, listing_id).await?;
+    let existing = get_listing(pool, listing_id, true).await?;
     
     if existing.member_id != member_id {
         return Err(ListingError::Unauthorized);
@@ -210,34 +753,147 @@ pub async fn delete_listing(
     
     // Soft delete by setting availability to Archived
     sqlx::query(
-        "UPDATE product_listings SET availability = $1, updated_at = $2 WHERE id = $3"
+        "UPDATE product_listings SET availability = $1, updated_at = $2, version = version + 1 WHERE id = $3"
     )
     .bind(AvailabilityStatus::Archived.to_string())
     .bind(Utc::now())
     .bind(listing_id)
     .execute(pool)
-    .await
-    .map_err(|e| ListingError::InvalidData(format!("Failed to delete listing: {}", e)))?;
+    .await?;
     
     Ok(())
 }
 
-/// Search and filter product listings
-pub async fn search_listings(
+/// Archive any member's listing on behalf of a moderator
+///
+/// Callers must check the actor is an admin. The owner is notified with the
+/// reason, but the listing stays archived even if that fails.
+pub async fn admin_archive_listing(
     pool: &PgPool,
-    filters: ListingFilters,
-) -> Result<Vec<ProductListing>, ListingError> {
+    listing_id: Uuid,
+    reason: &str,
+) -> Result<ProductListing, ListingError> {
+    validate_archive_reason(reason)?;
+    
+    let listing = sqlx::query_as::<_, ProductListing>(
+        "UPDATE product_listings SET availability = $1, updated_at = $2, version = version + 1
+         WHERE id = $3 AND availability != $1
+         RETURNING id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, version, expires_at, created_at, updated_at"
+    )
+    .bind(AvailabilityStatus::Archived.to_string())
+    .bind(Utc::now())
+    .bind(listing_id)
+    .fetch_optional(pool)
+    .await?;
+    
+    let Some(listing) = listing else {
+        // Either the listing doesn't exist or it was already archived
+        get_listing(pool, listing_id, true).await?;
+        return Err(ListingError::InvalidAvailabilityTransition(
+            "Listing is already archived".to_string(),
+        ));
+    };
+    
+    let message = format!("Your listing \"{}\" was archived by a moderator: {}", listing.name, reason.trim());
+    if let Err(e) = notifications::create_notification(pool, listing.member_id, NotificationType::ListingArchived, &message).await {
+        tracing::warn!("Failed to notify owner of archived listing {}: {}", listing_id, e);
+    }
+    
+    Ok(listing)
+}
+
+/// Validate a moderator's reason for archiving a listing
+pub fn validate_archive_reason(reason: &str) -> Result<(), ListingError> {
+    let reason = reason.trim();
+    
+    if reason.is_empty() {
+        return Err(ListingError::InvalidData("Archive reason cannot be empty".to_string()));
+    }
+    
+    if reason.chars().count() > MAX_ARCHIVE_REASON_LENGTH {
+        return Err(ListingError::InvalidData(format!(
+            "Archive reason cannot be longer than {} characters",
+            MAX_ARCHIVE_REASON_LENGTH
+        )));
+    }
+    
+    Ok(())
+}
+
+/// How the search term is matched against listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchMode {
+    /// Postgres full-text search over `search_vector`, ordered by relevance
+    FullText,
+    /// Substring match on name and description with `ILIKE`
+    Pattern,
+}
+
+/// Build the search SQL for the given filters.
+///
+/// Placeholders are numbered in the same order `run_search` binds values:
+/// archived status, search term, category, min price, max price, availability,
+/// min quantity, max quantity, then the page limit and offset.
+fn build_search_query(filters: &ListingFilters, mode: SearchMode) -> String {
     let mut query = String::from(
-        "SELECT id, member_id, name, description, quantity, unit_price, availability, created_at, updated_at
-         FROM product_listings
-         WHERE availability != $1"
+        "SELECT id, member_id, name, description, category, quantity_available, unit_price, unit_of_measure, availability, version, expires_at, created_at, updated_at
+         FROM product_listings"
     );
     
+    if filters.sort == ListingSort::ReputationDesc {
+        query.push_str(" LEFT JOIN member_reputation ON member_reputation.rated_id = member_id");
+    }
+    
+    let (conditions, rank_order, param_count) = search_conditions(filters, mode);
+    query.push_str(&conditions);
+    
+    let mut order_by = rank_order.unwrap_or_else(|| String::from("created_at DESC"));
+    if filters.sort == ListingSort::ReputationDesc {
+        order_by = format!("COALESCE(avg_score, 0) DESC, {}", order_by);
+    }
+    
+    query.push_str(&format!(" ORDER BY {}", order_by));
+    query.push_str(&format!(" LIMIT ${} OFFSET ${}", param_count, param_count + 1));
+    
+    query
+}
+
+/// Build the SQL counting every listing the search matches, ignoring the page window
+fn build_count_query(filters: &ListingFilters, mode: SearchMode) -> String {
+    let (conditions, _, _) = search_conditions(filters, mode);
+    
+    format!("SELECT COUNT(*) FROM product_listings{}", conditions)
+}
+
+/// Build the WHERE clause shared by the search and count queries
+///
+/// Also returns the relevance ordering for full-text searches and the number of
+/// the next free placeholder.
+fn search_conditions(filters: &ListingFilters, mode: SearchMode) -> (String, Option<String>, usize) {
+    let mut clause = String::from(" WHERE availability != $1 AND (expires_at IS NULL OR expires_at > NOW())");
+    
     let mut param_count = 2;
     let mut conditions = Vec::new();
+    let mut rank_order = None;
     
     if filters.search_term.is_some() {
-        conditions.push(format!("(name ILIKE ${} OR description ILIKE ${})", param_count, param_count));
+        match mode {
+            SearchMode::FullText => {
+                conditions.push(format!("search_vector @@ plainto_tsquery('english', ${})", param_count));
+                rank_order = Some(format!(
+                    "ts_rank(search_vector, plainto_tsquery('english', ${})) DESC, created_at DESC",
+                    param_count
+                ));
+            }
+            SearchMode::Pattern => {
+                conditions.push(format!("(name ILIKE ${} OR description ILIKE ${})", param_count, param_count));
+            }
+        }
+        param_count += 1;
+    }
+    
+    if filters.category.is_some() {
+        conditions.push(format!("category = ${}", param_count));
         param_count += 1;
     }
     
@@ -256,39 +912,278 @@ pub async fn search_listings(
         param_count += 1;
     }
     
+    if filters.min_quantity.is_some() {
+        conditions.push(format!("quantity_available >= ${}", param_count));
+        param_count += 1;
+    }
+    
+    if filters.max_quantity.is_some() {
+        conditions.push(format!("quantity_available <= ${}", param_count));
+        param_count += 1;
+    }
+    
     if !conditions.is_empty() {
-        query.push_str(" AND ");
-        query.push_str(&conditions.join(" AND "));
+        clause.push_str(" AND ");
+        clause.push_str(&conditions.join(" AND "));
     }
     
-    query.push_str(" ORDER BY created_at DESC");
+    (clause, rank_order, param_count)
+}
+
+/// Bind the filter values in the order `search_conditions` numbers their placeholders
+fn bind_search_filters<'q, O>(
+    mut query: QueryAs<'q, Postgres, O, PgArguments>,
+    filters: &ListingFilters,
+    mode: SearchMode,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    query = query.bind(AvailabilityStatus::Archived.to_string());
     
-    let mut query_builder = sqlx::query_as::<_, ProductListing>(&query)
-        .bind(AvailabilityStatus::Archived.to_string());
+    if let Some(search_term) = &filters.search_term {
+        match mode {
+            SearchMode::FullText => query = query.bind(search_term.clone()),
+            SearchMode::Pattern => query = query.bind(format!("%{}%", search_term)),
+        }
+    }
     
-    if let Some(search_term) = filters.search_term {
-        let search_pattern = format!("%{}%", search_term);
-        query_builder = query_builder.bind(search_pattern);
+    if let Some(category) = &filters.category {
+        query = query.bind(category.clone());
     }
     
     if let Some(min_price) = filters.min_price {
-        query_builder = query_builder.bind(min_price);
+        query = query.bind(min_price);
     }
     
     if let Some(max_price) = filters.max_price {
-        query_builder = query_builder.bind(max_price);
+        query = query.bind(max_price);
+    }
+    
+    if let Some(availability) = &filters.availability {
+        query = query.bind(availability.to_string());
+    }
+    
+    if let Some(min_quantity) = filters.min_quantity {
+        query = query.bind(min_quantity);
     }
     
-    if let Some(availability) = filters.availability {
-        query_builder = query_builder.bind(availability.to_string());
+    if let Some(max_quantity) = filters.max_quantity {
+        query = query.bind(max_quantity);
     }
     
-    let listings = query_builder
+    query
+}
+
+/// Execute a listing search in the given mode
+async fn run_search(
+    pool: &PgPool,
+    filters: &ListingFilters,
+    mode: SearchMode,
+) -> Result<Vec<ProductListing>, ListingError> {
+    let query = build_search_query(filters, mode);
+    let (limit, offset) = filters.page_window();
+    
+    let listings = bind_search_filters(sqlx::query_as::<_, ProductListing>(&query), filters, mode)
+        .bind(limit)
+        .bind(offset)
         .fetch_all(pool)
-        .await
-        .map_err(|e| ListingError::InvalidData(format!("Failed to search listings: {}", e)))?;
+        .await?;
+    
+    Ok(listings)
+}
+
+/// Count every listing a search matches
+async fn count_search(
+    pool: &PgPool,
+    filters: &ListingFilters,
+    mode: SearchMode,
+) -> Result<i64, ListingError> {
+    let query = build_count_query(filters, mode);
+    
+    let (total,): (i64,) = bind_search_filters(sqlx::query_as(&query), filters, mode)
+        .fetch_one(pool)
+        .await?;
+    
+    Ok(total)
+}
+
+/// Search listings with full-text matching on name and description, ranked by relevance
+pub async fn search_listings_fts(
+    pool: &PgPool,
+    filters: &ListingFilters,
+) -> Result<Vec<ProductListing>, ListingError> {
+    run_search(pool, filters, SearchMode::FullText).await
+}
+
+/// Search and filter product listings
+///
+/// A search term is matched with full-text search first; substring matching is
+/// only used when full-text search finds nothing.
+pub async fn search_listings(
+    pool: &PgPool,
+    filters: ListingFilters,
+) -> Result<Vec<ProductListing>, ListingError> {
+    if filters.search_term.is_some() {
+        let listings = search_listings_fts(pool, &filters).await?;
+        if !listings.is_empty() {
+            return Ok(listings);
+        }
+    }
+    
+    run_search(pool, &filters, SearchMode::Pattern).await
+}
+
+/// Search and filter product listings, with the total match count for paging
+///
+/// Falls back from full-text to substring matching like [`search_listings`],
+/// deciding on the total so every page of one search uses the same mode.
+pub async fn search_listings_page(
+    pool: &PgPool,
+    filters: ListingFilters,
+) -> Result<Paginated<ProductListing>, ListingError> {
+    let mut mode = SearchMode::Pattern;
+    let mut total = None;
+    if filters.search_term.is_some() {
+        let fts_total = count_search(pool, &filters, SearchMode::FullText).await?;
+        if fts_total > 0 {
+            mode = SearchMode::FullText;
+            total = Some(fts_total);
+        }
+    }
+    let total = match total {
+        Some(total) => total,
+        None => count_search(pool, &filters, mode).await?,
+    };
+    
+    let items = run_search(pool, &filters, mode).await?;
+    let (limit, offset) = filters.page_window();
+    
+    Ok(Paginated::new(items, total, limit, offset))
+}
+
+/// Save a member's search filters under a name
+pub async fn save_search(
+    pool: &PgPool,
+    member_id: Uuid,
+    name: &str,
+    filters: ListingFilters,
+) -> Result<SavedSearch, ListingError> {
+    validate_saved_search_name(name)?;
+    
+    let saved_search = sqlx::query_as::<_, SavedSearch>(
+        "INSERT INTO saved_searches (id, member_id, name, filters, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $5)
+         RETURNING id, member_id, name, filters, created_at, updated_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(member_id)
+    .bind(name.trim())
+    .bind(sqlx::types::Json(filters))
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(saved_search)
+}
+
+/// Get a member's saved searches, oldest first
+pub async fn list_saved_searches(pool: &PgPool, member_id: Uuid) -> Result<Vec<SavedSearch>, ListingError> {
+    let saved_searches = sqlx::query_as::<_, SavedSearch>(
+        "SELECT id, member_id, name, filters, created_at, updated_at
+         FROM saved_searches
+         WHERE member_id = $1
+         ORDER BY created_at, id"
+    )
+    .bind(member_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(saved_searches)
+}
+
+/// Get one of a member's saved searches
+///
+/// Other members' searches are reported as not found.
+pub async fn get_saved_search(
+    pool: &PgPool,
+    member_id: Uuid,
+    search_id: Uuid,
+) -> Result<SavedSearch, ListingError> {
+    sqlx::query_as::<_, SavedSearch>(
+        "SELECT id, member_id, name, filters, created_at, updated_at
+         FROM saved_searches
+         WHERE id = $1 AND member_id = $2"
+    )
+    .bind(search_id)
+    .bind(member_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ListingError::SavedSearchNotFound)
+}
+
+/// Rename a saved search and replace its filters
+pub async fn update_saved_search(
+    pool: &PgPool,
+    member_id: Uuid,
+    search_id: Uuid,
+    name: &str,
+    filters: ListingFilters,
+) -> Result<SavedSearch, ListingError> {
+    validate_saved_search_name(name)?;
+    
+    sqlx::query_as::<_, SavedSearch>(
+        "UPDATE saved_searches SET name = $1, filters = $2, updated_at = $3
+         WHERE id = $4 AND member_id = $5
+         RETURNING id, member_id, name, filters, created_at, updated_at"
+    )
+    .bind(name.trim())
+    .bind(sqlx::types::Json(filters))
+    .bind(Utc::now())
+    .bind(search_id)
+    .bind(member_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ListingError::SavedSearchNotFound)
+}
+
+/// Delete one of a member's saved searches
+pub async fn delete_saved_search(pool: &PgPool, member_id: Uuid, search_id: Uuid) -> Result<(), ListingError> {
+    let result = sqlx::query("DELETE FROM saved_searches WHERE id = $1 AND member_id = $2")
+        .bind(search_id)
+        .bind(member_id)
+        .execute(pool)
+        .await?;
+    
+    if result.rows_affected() == 0 {
+        return Err(ListingError::SavedSearchNotFound);
+    }
+    
+    Ok(())
+}
+
+/// Run a member's saved search against the current listings
+pub async fn run_saved_search(
+    pool: &PgPool,
+    member_id: Uuid,
+    search_id: Uuid,
+) -> Result<Vec<ProductListing>, ListingError> {
+    let saved_search = get_saved_search(pool, member_id, search_id).await?;
+    
+    search_listings(pool, saved_search.filters.0).await
+}
+
+/// Validate that a saved search name is non-empty and not too long
+pub fn validate_saved_search_name(name: &str) -> Result<(), ListingError> {
+    if name.trim().is_empty() {
+        return Err(ListingError::InvalidData("Saved search name cannot be empty".to_string()));
+    }
     
-    Ok(listings)
+    if name.trim().chars().count() > MAX_SAVED_SEARCH_NAME_LENGTH {
+        return Err(ListingError::InvalidData(format!(
+            "Saved search name cannot exceed {} characters",
+            MAX_SAVED_SEARCH_NAME_LENGTH
+        )));
+    }
+    
+    Ok(())
 }
 
 /// Mark a listing as out of stock
@@ -304,9 +1199,13 @@ pub async fn mark_out_of_stock(
         UpdateListingData {
             name: None,
             description: None,
-            quantity: None,
+            category: None,
+            quantity_available: None,
             unit_price: None,
+            unit_of_measure: None,
             availability: Some(AvailabilityStatus::OutOfStock),
+            expires_at: None,
+            expected_version: None,
         },
     )
     .await
@@ -325,9 +1224,13 @@ pub async fn mark_available(
         UpdateListingData {
             name: None,
             description: None,
-            quantity: None,
+            category: None,
+            quantity_available: None,
             unit_price: None,
+            unit_of_measure: None,
             availability: Some(AvailabilityStatus::Available),
+            expires_at: None,
+            expected_version: None,
         },
     )
     .await
@@ -336,7 +1239,8 @@ pub async fn mark_available(
 /// Check if a listing is available for purchase
 pub fn is_available_for_purchase(listing: &ProductListing) -> bool {
     listing.availability == AvailabilityStatus::Available.to_string()
-        && listing.quantity > Decimal::ZERO
+        && listing.quantity_available > Decimal::ZERO
+        && listing.expires_at.is_none_or(|expires_at| expires_at > Utc::now())
 }
 
 /// Validate listing data before creation or update
@@ -346,29 +1250,45 @@ pub fn validate_listing_data(
     quantity: Decimal,
     unit_price: Decimal,
 ) -> Result<(), ListingError> {
+    let errors = listing_field_errors(name, description, quantity, unit_price);
+    validation_result(errors).map_err(ListingError::Validation)
+}
+
+/// Problems with the fields every listing needs, in form order
+fn listing_field_errors(
+    name: &str,
+    description: &str,
+    quantity: Decimal,
+    unit_price: Decimal,
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    
     if name.trim().is_empty() {
-        return Err(ListingError::InvalidData("Product name cannot be empty".to_string()));
+        errors.push(ValidationError::new("name", "Product name cannot be empty"));
     }
     
     if description.trim().is_empty() {
-        return Err(ListingError::InvalidData("Product description cannot be empty".to_string()));
+        errors.push(ValidationError::new("description", "Product description cannot be empty"));
     }
     
     if quantity <= Decimal::ZERO {
-        return Err(ListingError::InvalidData("Quantity must be positive".to_string()));
+        errors.push(ValidationError::new("quantity_available", "Quantity must be positive"));
     }
     
     if unit_price <= Decimal::ZERO {
-        return Err(ListingError::InvalidData("Unit price must be positive".to_string()));
+        errors.push(ValidationError::new("unit_price", "Unit price must be positive"));
     }
     
-    Ok(())
+    errors
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::TransactionStatus;
+    use crate::reputation;
+    use crate::test_utils::{insert_test_listing, insert_test_member, insert_test_transaction};
     use proptest::prelude::*;
     
     // Unit tests
@@ -379,12 +1299,15 @@ mod tests {
         let data = CreateListingData {
             name: "Organic Tomatoes".to_string(),
             description: "Fresh organic tomatoes".to_string(),
-            quantity: Decimal::new(100, 0),
+            category: "Vegetables".to_string(),
+            quantity_available: Decimal::new(100, 0),
             unit_price: Decimal::new(299, 2), // $2.99
+            unit_of_measure: "kg".to_string(),
+            expires_at: None,
         };
         
         assert_eq!(data.name, "Organic Tomatoes");
-        assert!(data.quantity > Decimal::ZERO);
+        assert!(data.quantity_available > Decimal::ZERO);
         assert!(data.unit_price > Decimal::ZERO);
     }
     
@@ -394,14 +1317,429 @@ mod tests {
         let data = UpdateListingData {
             name: Some("Updated Name".to_string()),
             description: None,
-            quantity: Some(Decimal::new(50, 0)),
+            category: None,
+            quantity_available: Some(Decimal::new(50, 0)),
             unit_price: None,
+            unit_of_measure: None,
             availability: None,
+            expires_at: None,
+            expected_version: None,
         };
         
         assert!(data.name.is_some());
         assert!(data.description.is_none());
-        assert!(data.quantity.is_some());
+        assert!(data.quantity_available.is_some());
+    }
+    
+    #[test]
+    fn test_build_update_query_empty() {
+        assert!(build_update_query(Uuid::new_v4(), UpdateListingData::default()).is_none());
+    }
+    
+    #[test]
+    fn test_build_update_query_binds_only_provided_fields() {
+        let data = UpdateListingData {
+            unit_price: Some(Decimal::new(1234, 2)),
+            ..Default::default()
+        };
+        
+        let query_builder = build_update_query(Uuid::new_v4(), data).unwrap();
+        let sql = query_builder.sql();
+        
+        assert!(sql.starts_with("UPDATE product_listings SET unit_price = $1, updated_at = $2, version = version + 1 WHERE id = $3 RETURNING"));
+        assert!(!sql.contains("quantity_available ="));
+    }
+    
+    #[test]
+    fn test_validate_update_data_rejects_non_positive_price() {
+        let data = UpdateListingData {
+            unit_price: Some(Decimal::ZERO),
+            ..Default::default()
+        };
+        
+        assert!(matches!(validate_update_data(&data), Err(ListingError::Validation(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_update_listing_unit_price_only(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        let listing = create_listing(
+            &pool,
+            member_id,
+            CreateListingData {
+                name: "Organic Tomatoes".to_string(),
+                description: "Fresh organic tomatoes".to_string(),
+                category: "Vegetables".to_string(),
+                quantity_available: Decimal::new(100, 0),
+                unit_price: Decimal::new(299, 2),
+                unit_of_measure: "kg".to_string(),
+                expires_at: None,
+            },
+        )
+        .await
+        .unwrap();
+        
+        let update = UpdateListingData {
+            unit_price: Some(Decimal::new(1234, 2)),
+            ..Default::default()
+        };
+        update_listing(&pool, listing.id, member_id, update).await.unwrap();
+        
+        let reloaded = get_listing(&pool, listing.id, true).await.unwrap();
+        assert_eq!(reloaded.unit_price, Decimal::new(1234, 2));
+        assert_eq!(reloaded.quantity_available, listing.quantity_available);
+        assert_eq!(reloaded.name, listing.name);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_update_listing_version_conflict(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        let listing = create_listing(&pool, member_id, bulk_row("Carrots", Decimal::new(150, 2)))
+            .await
+            .unwrap();
+        assert_eq!(listing.version, 1);
+        
+        let update = |unit_price: i64, version: i32| UpdateListingData {
+            unit_price: Some(Decimal::new(unit_price, 2)),
+            expected_version: Some(version),
+            ..Default::default()
+        };
+        
+        let updated = update_listing(&pool, listing.id, member_id, update(175, 1)).await.unwrap();
+        assert_eq!(updated.version, 2);
+        assert_eq!(updated.unit_price, Decimal::new(175, 2));
+        
+        // A second editor still holding version 1 must not clobber the change
+        let result = update_listing(&pool, listing.id, member_id, update(120, 1)).await;
+        assert!(matches!(result, Err(ListingError::Conflict)));
+        assert_eq!(get_listing(&pool, listing.id, true).await.unwrap().unit_price, Decimal::new(175, 2));
+        
+        // The guard in the UPDATE catches an edit that lands after the version check
+        let mut query_builder = build_update_query(listing.id, update(120, 1)).unwrap();
+        let stale = query_builder.build_query_as::<ProductListing>().fetch_optional(&pool).await.unwrap();
+        assert!(stale.is_none());
+        
+        let updated = update_listing(&pool, listing.id, member_id, update(120, 2)).await.unwrap();
+        assert_eq!(updated.version, 3);
+        assert_eq!(updated.unit_price, Decimal::new(120, 2));
+    }
+    
+    #[test]
+    fn test_validate_category() {
+        for category in Category::ALL {
+            assert!(validate_category(&category.to_string()).is_ok());
+        }
+        assert!(matches!(validate_category("Vegetable"), Err(ListingError::InvalidData(_))));
+        assert!(matches!(validate_category("vegetables"), Err(ListingError::InvalidData(_))));
+        assert!(matches!(validate_category(""), Err(ListingError::InvalidData(_))));
+    }
+    
+    fn inventory_listing(quantity: Decimal, unit_price: Decimal, availability: AvailabilityStatus) -> ProductListing {
+        ProductListing {
+            id: Uuid::new_v4(),
+            member_id: Uuid::new_v4(),
+            name: "Carrots".to_string(),
+            description: "Fresh carrots".to_string(),
+            category: Category::Vegetables.to_string(),
+            quantity_available: quantity,
+            unit_price,
+            unit_of_measure: "kg".to_string(),
+            availability: availability.to_string(),
+            version: 1,
+            expires_at: None,
+            view_count: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+    
+    #[test]
+    fn test_build_inventory_flags_low_stock_and_totals_active() {
+        let seller_id = Uuid::new_v4();
+        let listings = vec![
+            inventory_listing(Decimal::from(40), Decimal::new(250, 2), AvailabilityStatus::Available),
+            inventory_listing(Decimal::from(3), Decimal::new(1999, 2), AvailabilityStatus::Available),
+            inventory_listing(Decimal::ZERO, Decimal::new(500, 2), AvailabilityStatus::OutOfStock),
+            inventory_listing(Decimal::from(2), Decimal::new(100, 2), AvailabilityStatus::Archived),
+        ];
+        
+        let inventory = build_inventory(seller_id, listings.clone(), Decimal::from(5), false);
+        assert_eq!(inventory.items.len(), 3);
+        assert_eq!(inventory.items.iter().map(|item| item.low_stock).collect::<Vec<_>>(), vec![false, true, true]);
+        assert_eq!(inventory.items[1].stock_value, Decimal::new(5997, 2));
+        // 100.00 + 59.97 + 0
+        assert_eq!(inventory.total_value, Decimal::new(15997, 2));
+        assert_eq!(inventory.active_listings, 3);
+        assert_eq!(inventory.low_stock_listings, 2);
+        
+        // Archived listings are shown, flagged, but left out of the totals
+        let inventory = build_inventory(seller_id, listings, Decimal::from(5), true);
+        assert_eq!(inventory.items.len(), 4);
+        assert!(inventory.items[3].archived);
+        assert!(!inventory.items[3].low_stock);
+        assert_eq!(inventory.total_value, Decimal::new(15997, 2));
+        assert_eq!(inventory.active_listings, 3);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_seller_inventory(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        let mut data = bulk_row("Onions", Decimal::new(150, 2));
+        data.quantity_available = Decimal::from(4);
+        create_listing(&pool, member_id, data).await.unwrap();
+        create_listing(&pool, member_id, bulk_row("Leeks", Decimal::new(200, 2))).await.unwrap();
+        let other_member = insert_test_member(&pool).await;
+        create_listing(&pool, other_member, bulk_row("Kale", Decimal::new(300, 2))).await.unwrap();
+        
+        let inventory = seller_inventory(&pool, member_id, Decimal::from(5), false).await.unwrap();
+        assert_eq!(inventory.items.len(), 2);
+        assert_eq!(inventory.low_stock_listings, 1);
+        let onions = inventory.items.iter().find(|item| item.name == "Onions").unwrap();
+        assert!(onions.low_stock);
+        assert_eq!(onions.stock_value, Decimal::new(600, 2));
+        
+        let result = seller_inventory(&pool, member_id, Decimal::NEGATIVE_ONE, false).await;
+        assert!(matches!(result, Err(ListingError::InvalidData(_))));
+    }
+    
+    #[test]
+    fn test_validate_unit_of_measure() {
+        assert_eq!(validate_unit_of_measure("Kilograms").unwrap(), UnitOfMeasure::Kg);
+        assert_eq!(validate_unit_of_measure("doz").unwrap(), UnitOfMeasure::Dozen);
+        assert!(matches!(validate_unit_of_measure("bushel"), Err(ListingError::InvalidData(_))));
+        assert!(matches!(validate_unit_of_measure(" "), Err(ListingError::InvalidData(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_listing_unit_stored_canonically(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        
+        let mut data = bulk_row("Milk", Decimal::new(120, 2));
+        data.unit_of_measure = "Liters".to_string();
+        let listing = create_listing(&pool, member_id, data).await.unwrap();
+        assert_eq!(listing.unit_of_measure, "litre");
+        
+        let update = UpdateListingData {
+            unit_of_measure: Some("ML".to_string()),
+            ..Default::default()
+        };
+        let result = update_listing(&pool, listing.id, member_id, update).await;
+        assert!(matches!(result, Err(ListingError::Validation(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_listing_category_validated(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        
+        let mut data = bulk_row("Apples", Decimal::new(300, 2));
+        data.category = "Fruit".to_string();
+        let result = create_listing(&pool, member_id, data.clone()).await;
+        assert!(matches!(result, Err(ListingError::Validation(_))));
+        
+        data.category = Category::Fruits.to_string();
+        let listing = create_listing(&pool, member_id, data).await.unwrap();
+        assert_eq!(listing.category, "Fruits");
+        
+        let update = |category: &str| UpdateListingData {
+            category: Some(category.to_string()),
+            ..Default::default()
+        };
+        let result = update_listing(&pool, listing.id, member_id, update("Produce")).await;
+        assert!(matches!(result, Err(ListingError::Validation(_))));
+        let updated = update_listing(&pool, listing.id, member_id, update("Other")).await.unwrap();
+        assert_eq!(updated.category, "Other");
+    }
+    
+    fn bulk_row(name: &str, unit_price: Decimal) -> CreateListingData {
+        CreateListingData {
+            name: name.to_string(),
+            description: format!("Fresh {}", name),
+            category: "Vegetables".to_string(),
+            quantity_available: Decimal::new(25, 0),
+            unit_price,
+            unit_of_measure: "kg".to_string(),
+            expires_at: None,
+        }
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_listings_bulk(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        let batch = vec![
+            bulk_row("Carrots", Decimal::new(150, 2)),
+            bulk_row("Beets", Decimal::new(200, 2)),
+            bulk_row("Kale", Decimal::new(350, 2)),
+        ];
+        
+        let created = create_listings_bulk(&pool, member_id, batch).await.unwrap();
+        
+        let names: Vec<&str> = created.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, vec!["Carrots", "Beets", "Kale"]);
+        assert!(created.iter().all(|l| l.member_id == member_id && l.availability == "Available"));
+        assert_eq!(get_listings_by_member(&pool, member_id).await.unwrap().len(), 3);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_listings_bulk_rolls_back_on_invalid_row(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        let batch = vec![
+            bulk_row("Carrots", Decimal::new(150, 2)),
+            bulk_row("Beets", Decimal::ZERO),
+            bulk_row("Kale", Decimal::new(350, 2)),
+            bulk_row("  ", Decimal::new(100, 2)),
+        ];
+        
+        let result = create_listings_bulk(&pool, member_id, batch).await;
+        
+        match result {
+            Err(ListingError::InvalidBatch(errors)) => {
+                let indexes: Vec<usize> = errors.iter().map(|e| e.index).collect();
+                assert_eq!(indexes, vec![1, 3]);
+                assert_eq!(errors[0].message, "Unit price must be positive");
+            }
+            other => panic!("expected an invalid batch, got {:?}", other),
+        }
+        assert!(get_listings_by_member(&pool, member_id).await.unwrap().is_empty());
+        
+        let result = create_listings_bulk(&pool, member_id, Vec::new()).await;
+        assert!(matches!(result, Err(ListingError::InvalidData(_))));
+        let oversized = vec![bulk_row("Carrots", Decimal::ONE); MAX_BULK_LISTINGS + 1];
+        let result = create_listings_bulk(&pool, member_id, oversized).await;
+        assert!(matches!(result, Err(ListingError::InvalidData(_))));
+    }
+    
+    #[test]
+    fn test_is_valid_availability_transition() {
+        use AvailabilityStatus::*;
+        
+        assert!(is_valid_availability_transition(&Available, &OutOfStock));
+        assert!(is_valid_availability_transition(&OutOfStock, &Available));
+        assert!(is_valid_availability_transition(&Available, &Archived));
+        assert!(is_valid_availability_transition(&OutOfStock, &Archived));
+        assert!(is_valid_availability_transition(&Archived, &Archived));
+        
+        assert!(!is_valid_availability_transition(&Archived, &Available));
+        assert!(!is_valid_availability_transition(&Archived, &OutOfStock));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_archived_listing_requires_restore(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, member).await;
+        
+        // Restoring only applies to archived listings
+        let result = restore_listing(&pool, listing_id, member).await;
+        assert!(matches!(result, Err(ListingError::InvalidAvailabilityTransition(_))));
+        
+        delete_listing(&pool, listing_id, member).await.unwrap();
+        
+        let result = mark_available(&pool, listing_id, member).await;
+        assert!(matches!(result, Err(ListingError::InvalidAvailabilityTransition(_))));
+        let result = mark_out_of_stock(&pool, listing_id, member).await;
+        assert!(matches!(result, Err(ListingError::InvalidAvailabilityTransition(_))));
+        assert_eq!(get_listing(&pool, listing_id, true).await.unwrap().availability, "Archived");
+        
+        let other = insert_test_member(&pool).await;
+        let result = restore_listing(&pool, listing_id, other).await;
+        assert!(matches!(result, Err(ListingError::Unauthorized)));
+        
+        let restored = restore_listing(&pool, listing_id, member).await.unwrap();
+        assert_eq!(restored.availability, "Available");
+        
+        // Regular edits work again once restored
+        let listing = mark_out_of_stock(&pool, listing_id, member).await.unwrap();
+        assert_eq!(listing.availability, "OutOfStock");
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_get_listings_by_member_includes_archived(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        let archived = insert_test_listing(&pool, member).await;
+        let out_of_stock = insert_test_listing(&pool, member).await;
+        let available = insert_test_listing(&pool, member).await;
+        insert_test_listing(&pool, insert_test_member(&pool).await).await;
+        mark_out_of_stock(&pool, out_of_stock, member).await.unwrap();
+        delete_listing(&pool, archived, member).await.unwrap();
+        
+        let listings = get_listings_by_member(&pool, member).await.unwrap();
+        
+        // Most recently updated first, whatever the availability
+        let ids: Vec<Uuid> = listings.iter().map(|listing| listing.id).collect();
+        assert_eq!(ids, vec![archived, out_of_stock, available]);
+        
+        // Public search hides the archived listing
+        let public = search_listings(&pool, ListingFilters::default()).await.unwrap();
+        assert!(public.iter().all(|listing| listing.id != archived));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_expire_stale_listings(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        let expired = insert_test_listing(&pool, member).await;
+        let seasonal = insert_test_listing(&pool, member).await;
+        let evergreen = insert_test_listing(&pool, member).await;
+        sqlx::query("UPDATE product_listings SET expires_at = NOW() - INTERVAL '1 hour' WHERE id = $1")
+            .bind(expired)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE product_listings SET expires_at = NOW() + INTERVAL '30 days' WHERE id = $1")
+            .bind(seasonal)
+            .execute(&pool)
+            .await
+            .unwrap();
+        
+        // Search hides the expired listing even before the sweep runs
+        let public = search_listings(&pool, ListingFilters::default()).await.unwrap();
+        let ids: Vec<Uuid> = public.iter().map(|listing| listing.id).collect();
+        assert!(!ids.contains(&expired));
+        assert!(ids.contains(&seasonal) && ids.contains(&evergreen));
+        
+        assert_eq!(expire_stale_listings(&pool).await.unwrap(), 1);
+        assert_eq!(expire_stale_listings(&pool).await.unwrap(), 0);
+        
+        let archived = get_listing(&pool, expired, true).await.unwrap();
+        assert_eq!(archived.availability, AvailabilityStatus::Archived.to_string());
+        let kept = get_listing(&pool, seasonal, true).await.unwrap();
+        assert_eq!(kept.availability, AvailabilityStatus::Available.to_string());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_get_listings_by_ids_skips_missing(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        let first = insert_test_listing(&pool, member).await;
+        let second = insert_test_listing(&pool, member).await;
+        let archived = insert_test_listing(&pool, member).await;
+        delete_listing(&pool, archived, member).await.unwrap();
+        
+        let ids = [first, Uuid::new_v4(), second, archived, Uuid::new_v4()];
+        let found: Vec<Uuid> = get_listings_by_ids(&pool, &ids, false)
+            .await
+            .unwrap()
+            .iter()
+            .map(|listing| listing.id)
+            .collect();
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&first) && found.contains(&second));
+        
+        let found = get_listings_by_ids(&pool, &ids, true).await.unwrap();
+        assert_eq!(found.len(), 3);
+        
+        assert!(get_listings_by_ids(&pool, &[], false).await.unwrap().is_empty());
+        let too_many = vec![first; MAX_BATCH_IDS + 1];
+        let result = get_listings_by_ids(&pool, &too_many, false).await;
+        assert!(matches!(result, Err(ListingError::InvalidData(_))));
     }
     
     #[test]
@@ -414,6 +1752,284 @@ mod tests {
         assert!(filters.min_price.is_none());
         assert!(filters.max_price.is_none());
         assert!(filters.availability.is_none());
+        assert!(filters.min_quantity.is_none());
+        assert!(filters.max_quantity.is_none());
+    }
+    
+    #[test]
+    fn test_build_search_query_category_filter() {
+        let filters = ListingFilters {
+            category: Some("Vegetables".to_string()),
+            ..Default::default()
+        };
+        
+        let query = build_search_query(&filters, SearchMode::Pattern);
+        
+        assert!(query.contains("AND category = $2"));
+    }
+    
+    #[test]
+    fn test_build_search_query_param_order() {
+        // Placeholders must follow the bind order in search_listings
+        let filters = ListingFilters {
+            search_term: Some("tomato".to_string()),
+            category: Some("Vegetables".to_string()),
+            min_price: Some(Decimal::new(100, 2)),
+            max_price: Some(Decimal::new(500, 2)),
+            availability: Some(AvailabilityStatus::Available),
+            min_quantity: None,
+            max_quantity: None,
+            sort: ListingSort::Newest,
+            limit: Some(10),
+            offset: Some(30),
+        };
+        
+        let query = build_search_query(&filters, SearchMode::Pattern);
+        
+        assert!(query.contains("(name ILIKE $2 OR description ILIKE $2)"));
+        assert!(query.contains("category = $3"));
+        assert!(query.contains("unit_price >= $4"));
+        assert!(query.contains("unit_price <= $5"));
+        assert!(query.contains("availability = $6"));
+        assert!(query.ends_with("LIMIT $7 OFFSET $8"));
+    }
+    
+    #[test]
+    fn test_build_search_query_quantity_after_price() {
+        let filters = ListingFilters {
+            min_price: Some(Decimal::new(100, 2)),
+            max_price: Some(Decimal::new(500, 2)),
+            min_quantity: Some(Decimal::new(50, 0)),
+            max_quantity: Some(Decimal::new(500, 0)),
+            ..Default::default()
+        };
+        
+        let query = build_search_query(&filters, SearchMode::Pattern);
+        
+        assert!(query.contains("unit_price >= $2 AND unit_price <= $3"));
+        assert!(query.contains("quantity_available >= $4 AND quantity_available <= $5"));
+        assert!(query.ends_with("LIMIT $6 OFFSET $7"));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_search_by_quantity_range(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        let mut ids = Vec::new();
+        for (quantity, price) in [(10, 100), (60, 100), (60, 900), (400, 200)] {
+            let mut data = bulk_row("Potatoes", Decimal::new(price, 2));
+            data.quantity_available = Decimal::new(quantity, 0);
+            ids.push(create_listing(&pool, member_id, data).await.unwrap().id);
+        }
+        let found = |listings: Vec<ProductListing>| {
+            let mut found: Vec<Uuid> = listings.iter().map(|l| l.id).collect();
+            found.sort();
+            found
+        };
+        
+        // Listings below the threshold are excluded
+        let filters = ListingFilters {
+            min_quantity: Some(Decimal::new(50, 0)),
+            ..Default::default()
+        };
+        let mut expected = vec![ids[1], ids[2], ids[3]];
+        expected.sort();
+        assert_eq!(found(search_listings(&pool, filters).await.unwrap()), expected);
+        
+        // Quantity and price bounds apply together
+        let filters = ListingFilters {
+            min_quantity: Some(Decimal::new(50, 0)),
+            max_quantity: Some(Decimal::new(100, 0)),
+            max_price: Some(Decimal::new(500, 2)),
+            ..Default::default()
+        };
+        assert_eq!(found(search_listings(&pool, filters).await.unwrap()), vec![ids[1]]);
+    }
+    
+    #[test]
+    fn test_build_count_query_matches_search_filters() {
+        let filters = ListingFilters {
+            search_term: Some("tomato".to_string()),
+            max_quantity: Some(Decimal::new(100, 0)),
+            sort: ListingSort::ReputationDesc,
+            limit: Some(10),
+            ..Default::default()
+        };
+        
+        let query = build_count_query(&filters, SearchMode::Pattern);
+        
+        assert_eq!(
+            query,
+            "SELECT COUNT(*) FROM product_listings WHERE availability != $1 \
+             AND (expires_at IS NULL OR expires_at > NOW()) \
+             AND (name ILIKE $2 OR description ILIKE $2) AND quantity_available <= $3"
+        );
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_search_listings_page_metadata(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        for name in ["Red onions", "White onions", "Spring onions", "Leeks"] {
+            create_listing(&pool, member_id, bulk_row(name, Decimal::new(150, 2))).await.unwrap();
+        }
+        let page = |search_term: Option<&str>, offset| ListingFilters {
+            search_term: search_term.map(str::to_string),
+            limit: Some(2),
+            offset: Some(offset),
+            ..Default::default()
+        };
+        
+        let first = search_listings_page(&pool, page(None, 0)).await.unwrap();
+        assert_eq!((first.items.len(), first.total, first.limit, first.offset), (2, 4, 2, 0));
+        assert!(first.has_more);
+        
+        let last = search_listings_page(&pool, page(None, 2)).await.unwrap();
+        assert_eq!((last.items.len(), last.total), (2, 4));
+        assert!(!last.has_more);
+        
+        // The total only counts listings matching the search
+        let onions = search_listings_page(&pool, page(Some("onions"), 0)).await.unwrap();
+        assert_eq!((onions.items.len(), onions.total), (2, 3));
+        assert!(onions.has_more);
+        let onions = search_listings_page(&pool, page(Some("onions"), 2)).await.unwrap();
+        assert_eq!((onions.items.len(), onions.total), (1, 3));
+        assert!(!onions.has_more);
+    }
+    
+    #[test]
+    fn test_build_search_query_no_filters() {
+        let query = build_search_query(&ListingFilters::default(), SearchMode::Pattern);
+        
+        assert!(!query.contains("category = "));
+        assert!(query.ends_with("ORDER BY created_at DESC LIMIT $2 OFFSET $3"));
+    }
+    
+    #[test]
+    fn test_build_search_query_full_text() {
+        let filters = ListingFilters {
+            search_term: Some("organic tomatoes".to_string()),
+            category: Some("Vegetables".to_string()),
+            ..Default::default()
+        };
+        
+        let query = build_search_query(&filters, SearchMode::FullText);
+        
+        assert!(query.contains("search_vector @@ plainto_tsquery('english', $2)"));
+        assert!(query.contains("ORDER BY ts_rank(search_vector, plainto_tsquery('english', $2)) DESC"));
+        assert!(query.contains("category = $3"));
+        assert!(!query.contains("ILIKE"));
+    }
+    
+    #[test]
+    fn test_build_search_query_reputation_sort() {
+        let filters = ListingFilters {
+            category: Some("Vegetables".to_string()),
+            sort: ListingSort::ReputationDesc,
+            ..Default::default()
+        };
+        
+        let query = build_search_query(&filters, SearchMode::Pattern);
+        
+        assert!(query.contains("LEFT JOIN member_reputation ON member_reputation.rated_id = member_id WHERE"));
+        assert!(query.contains("category = $2"));
+        assert!(query.ends_with("ORDER BY COALESCE(avg_score, 0) DESC, created_at DESC LIMIT $3 OFFSET $4"));
+        
+        // The default sort does not join ratings
+        let query = build_search_query(&ListingFilters::default(), SearchMode::Pattern);
+        assert!(!query.contains("member_reputation"));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_search_sorted_by_seller_reputation(pool: PgPool) {
+        let unrated_seller = insert_test_member(&pool).await;
+        let low_seller = insert_test_member(&pool).await;
+        let high_seller = insert_test_member(&pool).await;
+        
+        let high_listing = insert_test_listing(&pool, high_seller).await;
+        let low_listing = insert_test_listing(&pool, low_seller).await;
+        // Created last, so it would come first by recency
+        let unrated_listing = insert_test_listing(&pool, unrated_seller).await;
+        
+        for (seller, score) in [(low_seller, 2), (high_seller, 5), (high_seller, 4)] {
+            let buyer = insert_test_member(&pool).await;
+            let transaction_id = insert_test_transaction(&pool, buyer, seller, TransactionStatus::Completed).await;
+            reputation::create_rating(&pool, transaction_id, buyer, seller, score).await.unwrap();
+        }
+        
+        let filters = ListingFilters {
+            sort: ListingSort::ReputationDesc,
+            ..Default::default()
+        };
+        let results = search_listings(&pool, filters).await.unwrap();
+        
+        // insert_test_transaction creates one extra listing per rating
+        let ids: Vec<Uuid> = results
+            .iter()
+            .map(|listing| listing.id)
+            .filter(|id| [high_listing, low_listing, unrated_listing].contains(id))
+            .collect();
+        assert_eq!(ids, vec![high_listing, low_listing, unrated_listing]);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_full_text_search_matches_words_in_any_order(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        for (name, description) in [
+            ("Heirloom Tomatoes", "Organic tomatoes picked this week"),
+            ("Sweet Corn", "Fresh corn on the cob"),
+        ] {
+            create_listing(
+                &pool,
+                member_id,
+                CreateListingData {
+                    name: name.to_string(),
+                    description: description.to_string(),
+                    category: "Vegetables".to_string(),
+                    quantity_available: Decimal::new(10, 0),
+                    unit_price: Decimal::new(199, 2),
+                    unit_of_measure: "kg".to_string(),
+                    expires_at: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+        
+        let filters = ListingFilters {
+            search_term: Some("tomatoes organic".to_string()),
+            ..Default::default()
+        };
+        let results = search_listings_fts(&pool, &filters).await.unwrap();
+        
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "Heirloom Tomatoes");
+    }
+    
+    #[test]
+    fn test_page_window_defaults() {
+        let filters = ListingFilters::default();
+        
+        assert_eq!(filters.page_window(), (DEFAULT_SEARCH_LIMIT, 0));
+    }
+    
+    #[test]
+    fn test_page_window_clamps() {
+        let filters = ListingFilters {
+            limit: Some(10_000),
+            offset: Some(-5),
+            ..Default::default()
+        };
+        assert_eq!(filters.page_window(), (MAX_SEARCH_LIMIT, 0));
+        
+        let filters = ListingFilters {
+            limit: Some(0),
+            offset: Some(40),
+            ..Default::default()
+        };
+        assert_eq!(filters.page_window(), (1, 40));
     }
     
     #[test]
@@ -424,9 +2040,14 @@ mod tests {
             member_id: Uuid::new_v4(),
             name: "Test Product".to_string(),
             description: "Test Description".to_string(),
-            quantity: Decimal::new(10, 0),
+            category: "Vegetables".to_string(),
+            quantity_available: Decimal::new(10, 0),
             unit_price: Decimal::new(100, 0),
+            unit_of_measure: "kg".to_string(),
             availability: AvailabilityStatus::Available.to_string(),
+            version: 1,
+            expires_at: None,
+            view_count: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -443,11 +2064,28 @@ mod tests {
         
         // Test available but zero quantity
         let zero_quantity_listing = ProductListing {
-            quantity: Decimal::ZERO,
+            quantity_available: Decimal::ZERO,
             ..available_listing.clone()
         };
         
         assert!(!is_available_for_purchase(&zero_quantity_listing));
+        
+        // Test listing past its expiry that hasn't been swept yet
+        let expired_listing = ProductListing {
+            expires_at: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..available_listing.clone()
+        };
+        
+        assert!(!is_available_for_purchase(&expired_listing));
+    }
+    
+    #[test]
+    fn test_validate_expires_at() {
+        assert!(validate_expires_at(Utc::now() + chrono::Duration::days(1)).is_ok());
+        assert!(matches!(
+            validate_expires_at(Utc::now() - chrono::Duration::minutes(1)),
+            Err(ListingError::InvalidData(_))
+        ));
     }
     
     #[test]
@@ -472,7 +2110,7 @@ mod tests {
         );
         
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ListingError::InvalidData(_)));
+        assert!(matches!(result.unwrap_err(), ListingError::Validation(_)));
     }
     
     #[test]
@@ -485,7 +2123,7 @@ mod tests {
         );
         
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ListingError::InvalidData(_)));
+        assert!(matches!(result.unwrap_err(), ListingError::Validation(_)));
     }
     
     #[test]
@@ -498,7 +2136,7 @@ mod tests {
         );
         
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ListingError::InvalidData(_)));
+        assert!(matches!(result.unwrap_err(), ListingError::Validation(_)));
     }
     
     #[test]
@@ -511,10 +2149,189 @@ mod tests {
         );
         
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), ListingError::InvalidData(_)));
+        assert!(matches!(result.unwrap_err(), ListingError::Validation(_)));
+    }
+    
+    #[test]
+    fn test_validate_listing_data_reports_every_field() {
+        let result = validate_listing_data(
+            "",
+            "Fresh organic tomatoes",
+            Decimal::new(100, 0),
+            Decimal::new(-299, 2),
+        );
+        
+        match result {
+            Err(ListingError::Validation(errors)) => {
+                let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(fields, vec!["name", "unit_price"]);
+                assert_eq!(errors[0].message, "Product name cannot be empty");
+                assert_eq!(errors[1].message, "Unit price must be positive");
+            }
+            other => panic!("expected field errors, got {:?}", other),
+        }
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_listing_reports_every_invalid_field(pool: PgPool) {
+        let member_id = insert_test_member(&pool).await;
+        let mut data = bulk_row("", Decimal::new(-150, 2));
+        data.unit_of_measure = "bushel".to_string();
+        
+        let error = create_listing(&pool, member_id, data).await.unwrap_err();
+        
+        let fields: Vec<String> = match &error {
+            ListingError::Validation(errors) => errors.iter().map(|e| e.field.clone()).collect(),
+            other => panic!("expected field errors, got {:?}", other),
+        };
+        assert_eq!(fields, vec!["name", "unit_price", "unit_of_measure"]);
+        
+        let error = crate::error::DoftaError::from(error);
+        assert_eq!(error.status_code(), axum::http::StatusCode::BAD_REQUEST);
+        assert_eq!(error.validation_errors().map(<[_]>::len), Some(3));
+        assert!(get_listings_by_member(&pool, member_id).await.unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_validate_saved_search_name() {
+        assert!(validate_saved_search_name("Cheap vegetables").is_ok());
+        assert!(matches!(validate_saved_search_name("  "), Err(ListingError::InvalidData(_))));
+        assert!(matches!(
+            validate_saved_search_name(&"a".repeat(MAX_SAVED_SEARCH_NAME_LENGTH + 1)),
+            Err(ListingError::InvalidData(_))
+        ));
+    }
+    
+    #[test]
+    fn test_listing_filters_json_defaults_missing_fields() {
+        let filters: ListingFilters = serde_json::from_str(
+            r#"{"category": "Fruits", "max_price": "2.50", "sort": "reputation_desc"}"#
+        )
+        .unwrap();
+        
+        assert_eq!(filters.category.as_deref(), Some("Fruits"));
+        assert_eq!(filters.max_price, Some(Decimal::new(250, 2)));
+        assert_eq!(filters.sort, ListingSort::ReputationDesc);
+        assert!(filters.search_term.is_none() && filters.availability.is_none());
+        
+        let json = serde_json::to_value(&filters).unwrap();
+        assert_eq!(serde_json::from_value::<ListingFilters>(json).unwrap(), filters);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_saved_search_reruns_its_filters(pool: PgPool) {
+        let seller = insert_test_member(&pool).await;
+        let buyer = insert_test_member(&pool).await;
+        let mut listing_ids = HashMap::new();
+        for (name, category, cents) in [
+            ("Cheap Carrots", "Vegetables", 150),
+            ("Dear Asparagus", "Vegetables", 900),
+            ("Cheap Apples", "Fruits", 120),
+        ] {
+            let listing = create_listing(
+                &pool,
+                seller,
+                CreateListingData {
+                    name: name.to_string(),
+                    description: format!("Fresh {}", name.to_lowercase()),
+                    category: category.to_string(),
+                    quantity_available: Decimal::new(20, 0),
+                    unit_price: Decimal::new(cents, 2),
+                    unit_of_measure: "kg".to_string(),
+                    expires_at: None,
+                },
+            )
+            .await
+            .unwrap();
+            listing_ids.insert(name, listing.id);
+        }
+        
+        let filters = ListingFilters {
+            category: Some("Vegetables".to_string()),
+            max_price: Some(Decimal::new(500, 2)),
+            availability: Some(AvailabilityStatus::Available),
+            ..Default::default()
+        };
+        let saved = save_search(&pool, buyer, " Cheap veg ", filters.clone()).await.unwrap();
+        assert_eq!(saved.name, "Cheap veg");
+        assert_eq!(saved.filters.0, filters);
+        
+        let everything = save_search(&pool, buyer, "Everything", ListingFilters::default()).await.unwrap();
+        let names: Vec<String> = list_saved_searches(&pool, buyer)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|search| search.name)
+            .collect();
+        assert_eq!(names, vec!["Cheap veg", "Everything"]);
+        assert!(list_saved_searches(&pool, seller).await.unwrap().is_empty());
+        
+        // The stored filters are applied again, and see listings added since
+        let results = run_saved_search(&pool, buyer, saved.id).await.unwrap();
+        let ids: Vec<Uuid> = results.iter().map(|listing| listing.id).collect();
+        assert_eq!(ids, vec![listing_ids["Cheap Carrots"]]);
+        assert_eq!(run_saved_search(&pool, buyer, everything.id).await.unwrap().len(), 3);
+        
+        // Searches are private to the member who saved them
+        assert!(matches!(
+            run_saved_search(&pool, seller, saved.id).await,
+            Err(ListingError::SavedSearchNotFound)
+        ));
+        assert!(matches!(
+            delete_saved_search(&pool, seller, saved.id).await,
+            Err(ListingError::SavedSearchNotFound)
+        ));
+        
+        let widened = ListingFilters {
+            max_price: Some(Decimal::new(1000, 2)),
+            ..filters
+        };
+        let updated = update_saved_search(&pool, buyer, saved.id, "Any veg", widened).await.unwrap();
+        assert_eq!(updated.name, "Any veg");
+        assert_eq!(run_saved_search(&pool, buyer, saved.id).await.unwrap().len(), 2);
+        
+        delete_saved_search(&pool, buyer, saved.id).await.unwrap();
+        assert!(matches!(
+            run_saved_search(&pool, buyer, saved.id).await,
+            Err(ListingError::SavedSearchNotFound)
+        ));
+        assert_eq!(list_saved_searches(&pool, buyer).await.unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_validate_archive_reason() {
+        assert!(validate_archive_reason("Counterfeit organic label").is_ok());
+        assert!(matches!(validate_archive_reason(" "), Err(ListingError::InvalidData(_))));
+        assert!(matches!(
+            validate_archive_reason(&"a".repeat(MAX_ARCHIVE_REASON_LENGTH + 1)),
+            Err(ListingError::InvalidData(_))
+        ));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_admin_archive_listing(pool: PgPool) {
+        let owner = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, owner).await;
+        
+        let result = admin_archive_listing(&pool, listing_id, "  ").await;
+        assert!(matches!(result, Err(ListingError::InvalidData(_))));
+        let result = admin_archive_listing(&pool, Uuid::new_v4(), "Spam").await;
+        assert!(matches!(result, Err(ListingError::NotFound)));
+        
+        let listing = admin_archive_listing(&pool, listing_id, "Spam").await.unwrap();
+        assert_eq!(listing.member_id, owner);
+        assert_eq!(listing.availability, AvailabilityStatus::Archived.to_string());
+        assert_eq!(listing.version, 2);
+        
+        let result = admin_archive_listing(&pool, listing_id, "Spam").await;
+        assert!(matches!(result, Err(ListingError::InvalidAvailabilityTransition(_))));
+        
+        // The owner can still restore it, e.g. after fixing the listing
+        assert!(restore_listing(&pool, listing_id, owner).await.is_ok());
     }
-}
-
     
     // Property-Based Tests
     
@@ -538,15 +2355,18 @@ mod tests {
             let data = CreateListingData {
                 name: name.clone(),
                 description: description.clone(),
-                quantity,
+                category: "Vegetables".to_string(),
+                quantity_available: quantity,
                 unit_price,
+                unit_of_measure: "kg".to_string(),
+                expires_at: None,
             };
             
             // Validate the data
             let validation_result = validate_listing_data(
                 &data.name,
                 &data.description,
-                data.quantity,
+                data.quantity_available,
                 data.unit_price,
             );
             
@@ -559,7 +2379,7 @@ mod tests {
             prop_assert_eq!(&data.description, &description);
             
             // Property: Quantity should be positive
-            prop_assert!(data.quantity > Decimal::ZERO);
+            prop_assert!(data.quantity_available > Decimal::ZERO);
             
             // Property: Unit price should be positive
             prop_assert!(data.unit_price > Decimal::ZERO);
@@ -675,9 +2495,14 @@ mod tests {
                 member_id,
                 name: original_name.clone(),
                 description: original_description.clone(),
-                quantity: original_quantity,
+                category: "Vegetables".to_string(),
+                quantity_available: original_quantity,
                 unit_price: original_price,
+                unit_of_measure: "kg".to_string(),
                 availability: AvailabilityStatus::Available.to_string(),
+                version: 1,
+                expires_at: None,
+                view_count: None,
                 created_at,
                 updated_at: created_at,
             };
@@ -689,9 +2514,13 @@ mod tests {
             let update_data = UpdateListingData {
                 name: Some(new_name.clone()),
                 description: Some(new_description.clone()),
-                quantity: Some(new_quantity),
+                category: None,
+                quantity_available: Some(new_quantity),
                 unit_price: Some(new_price),
+                unit_of_measure: None,
                 availability: Some(AvailabilityStatus::OutOfStock),
+                expires_at: None,
+                expected_version: None,
             };
             
             // Property 1: Original listing ID should be preserved
@@ -706,7 +2535,7 @@ mod tests {
             // Property 4: Update data should contain new values
             prop_assert_eq!(update_data.name.as_ref().unwrap(), &new_name, "Update should have new name");
             prop_assert_eq!(update_data.description.as_ref().unwrap(), &new_description, "Update should have new description");
-            prop_assert_eq!(update_data.quantity.unwrap(), new_quantity, "Update should have new quantity");
+            prop_assert_eq!(update_data.quantity_available.unwrap(), new_quantity, "Update should have new quantity");
             prop_assert_eq!(update_data.unit_price.unwrap(), new_price, "Update should have new price");
             prop_assert_eq!(update_data.availability.as_ref().unwrap(), &AvailabilityStatus::OutOfStock, "Update should have new availability");
             
@@ -714,7 +2543,7 @@ mod tests {
             let validation_result = validate_listing_data(
                 update_data.name.as_ref().unwrap(),
                 update_data.description.as_ref().unwrap(),
-                update_data.quantity.unwrap(),
+                update_data.quantity_available.unwrap(),
                 update_data.unit_price.unwrap(),
             );
             prop_assert!(validation_result.is_ok(), "Update data should be valid");
@@ -737,9 +2566,14 @@ mod tests {
                 member_id: Uuid::new_v4(),
                 name: name.clone(),
                 description: "Test description".to_string(),
-                quantity: Decimal::new(100, 0),
+                category: "Vegetables".to_string(),
+                quantity_available: Decimal::new(100, 0),
                 unit_price: Decimal::new(299, 2),
+                unit_of_measure: "kg".to_string(),
                 availability: AvailabilityStatus::Available.to_string(),
+                version: 1,
+                expires_at: None,
+                view_count: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             };
@@ -1,4 +1,4 @@
-use dofta::{config::Config, db::Database, routes};
+use dofta::{auth, config::Config, db::Database, listings::{self, ViewCounter}, members, routes, security::FieldKeys, shutdown};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
@@ -16,22 +16,69 @@ async fn main() -> anyhow::Result<()> {
     
     // Load configuration
     let config = Config::from_env()?;
+    config.validate()?;
     tracing::info!("✅ Configuration loaded");
     
     // Initialize database connection pool
-    let db = Database::new(&config.database_url).await?;
+    let db = Database::new(&config).await?;
     tracing::info!("✅ Database connection pool established");
     
     // Run migrations
     db.migrate().await?;
     tracing::info!("✅ Database migrations completed");
     
+    // Encrypt profiles written before profile encryption; the key isn't available to migrations
+    let encrypted = members::encrypt_legacy_profiles(db.pool(), &FieldKeys::from_config(&config)?).await?;
+    if encrypted > 0 {
+        tracing::info!("✅ Encrypted {} legacy member profiles", encrypted);
+    }
+    
     // Health check
     db.health_check().await?;
     tracing::info!("✅ Database health check passed");
     
+    // Periodically drop revocation entries for tokens that have expired anyway
+    let cleanup_pool = db.pool().clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            match auth::purge_expired_revocations(&cleanup_pool).await {
+                Ok(purged) => tracing::debug!("Purged {} expired token revocations", purged),
+                Err(e) => tracing::warn!("Failed to purge expired token revocations: {}", e),
+            }
+        }
+    });
+    
+    // Archive listings once their expiry date has passed
+    let expiry_pool = db.pool().clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(listings::EXPIRY_SWEEP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match listings::expire_stale_listings(&expiry_pool).await {
+                Ok(expired) => tracing::debug!("Archived {} expired listings", expired),
+                Err(e) => tracing::warn!("Failed to archive expired listings: {}", e),
+            }
+        }
+    });
+    
+    // Write buffered listing views in batches rather than on every request
+    let views = ViewCounter::default();
+    let flush_views = views.clone();
+    let flush_pool = db.pool().clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(listings::VIEW_FLUSH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = flush_views.flush(&flush_pool).await {
+                tracing::warn!("Failed to flush listing views: {}", e);
+            }
+        }
+    });
+    
     // Create router
-    let app = routes::create_router(db.pool().clone());
+    let app = routes::create_router(db.pool().clone(), config.clone(), views.clone());
     
     // Start server
     let addr = format!("{}:{}", config.server_host, config.server_port);
@@ -41,7 +88,17 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("📡 Health check: http://{}/health", addr);
     tracing::info!("🔐 API endpoints: http://{}/api/*", addr);
     
-    axum::serve(listener, app).await?;
+    shutdown::serve(listener, app, shutdown::signal()).await?;
+    
+    // Keep views counted since the last periodic flush
+    if let Err(e) = views.flush(db.pool()).await {
+        tracing::warn!("Failed to flush listing views: {}", e);
+    }
+    
+    // Close idle connections and wait for checked-out ones to be returned
+    db.pool().close().await;
+    tracing::info!("✅ Database connection pool closed");
+    tracing::info!("👋 DOFTA API server stopped");
     
     Ok(())
 }
@@ -0,0 +1,358 @@
+use crate::error::SecurityError;
+use crate::models::{AvailabilityStatus, Member};
+use crate::security::{self, EncryptedField, FieldKeys};
+use chrono::Utc;
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Erase a member's personal data while keeping the records accounting needs
+///
+/// The member row is kept as an anonymous tombstone so orders, transactions,
+/// ratings and votes still reference a valid member, but nothing left on it
+/// identifies the person. Their listings are archived, and their notifications,
+/// preferences and refresh tokens are removed. Everything happens in one
+/// database transaction, so a failure leaves the account untouched.
+pub async fn delete_member_data(pool: &PgPool, member_id: Uuid) -> Result<(), SecurityError> {
+    let mut tx = pool.begin().await?;
+    
+    if !erase_member(&mut tx, member_id).await? {
+        return Err(SecurityError::MemberNotFound);
+    }
+    
+    tx.commit().await?;
+    
+    Ok(())
+}
+
+/// Erase a member's data on an open connection, as [`delete_member_data`] does
+///
+/// Returns `false` if there is no such member or it was already erased. The
+/// caller owns the transaction, so governance can remove a member atomically.
+pub(crate) async fn erase_member(conn: &mut PgConnection, member_id: Uuid) -> Result<bool, sqlx::Error> {
+    let now = Utc::now();
+    
+    // The password hash is blanked, which no password can ever verify against
+    let erased = sqlx::query(
+        "UPDATE members
+         SET email = $1, password_hash = '', name = '', farm_name = NULL, location = NULL,
+             farm_name_ciphertext = NULL, farm_name_nonce = NULL, location_ciphertext = NULL,
+             location_nonce = NULL, profile_key_id = NULL, deleted_at = $2
+         WHERE id = $3 AND deleted_at IS NULL"
+    )
+    .bind(tombstone_email(member_id))
+    .bind(now)
+    .bind(member_id)
+    .execute(&mut *conn)
+    .await?;
+    
+    if erased.rows_affected() == 0 {
+        return Ok(false);
+    }
+    
+    sqlx::query("UPDATE product_listings SET availability = $1, updated_at = $2, version = version + 1 WHERE member_id = $3")
+        .bind(AvailabilityStatus::Archived.to_string())
+        .bind(now)
+        .bind(member_id)
+        .execute(&mut *conn)
+        .await?;
+    
+    for statement in [
+        "DELETE FROM notifications WHERE recipient_id = $1",
+        "DELETE FROM notification_preferences WHERE member_id = $1",
+        "DELETE FROM refresh_tokens WHERE member_id = $1",
+        "DELETE FROM saved_searches WHERE member_id = $1",
+    ] {
+        sqlx::query(statement)
+            .bind(member_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    
+    Ok(true)
+}
+
+/// Encrypted profile columns of a member row
+#[derive(FromRow)]
+struct EncryptedProfile {
+    farm_name_ciphertext: Option<Vec<u8>>,
+    farm_name_nonce: Option<Vec<u8>>,
+    location_ciphertext: Option<Vec<u8>>,
+    location_nonce: Option<Vec<u8>>,
+    profile_key_id: Option<String>,
+}
+
+/// Fill in a member's farm name and location from their encrypted columns
+pub async fn decrypt_profile(pool: &PgPool, keys: &FieldKeys, member: &mut Member) -> Result<(), SecurityError> {
+    let profile = sqlx::query_as::<_, EncryptedProfile>(
+        "SELECT farm_name_ciphertext, farm_name_nonce, location_ciphertext, location_nonce, profile_key_id
+         FROM members
+         WHERE id = $1"
+    )
+    .bind(member.id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(SecurityError::MemberNotFound)?;
+    
+    let Some(key_id) = profile.profile_key_id else {
+        return Ok(());
+    };
+    let open = |ciphertext: Option<Vec<u8>>, nonce: Option<Vec<u8>>| match (ciphertext, nonce) {
+        (Some(ciphertext), Some(nonce)) => security::decrypt_field(
+            keys,
+            &EncryptedField {
+                key_id: key_id.clone(),
+                nonce,
+                ciphertext,
+            },
+        )
+        .map(Some),
+        _ => Ok(None),
+    };
+    
+    member.farm_name = open(profile.farm_name_ciphertext, profile.farm_name_nonce)?;
+    member.location = open(profile.location_ciphertext, profile.location_nonce)?;
+    
+    Ok(())
+}
+
+/// Encrypt profiles stored in plaintext before profile encryption, clearing the plaintext
+///
+/// Run at startup, after migrations, since the key isn't available to SQL. Each
+/// value is encrypted with the current key, and the count of members updated is
+/// returned; once every legacy row is done this is a no-op.
+pub async fn encrypt_legacy_profiles(pool: &PgPool, keys: &FieldKeys) -> Result<u64, SecurityError> {
+    let mut tx = pool.begin().await?;
+    
+    let legacy: Vec<(Uuid, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT id, farm_name, location
+         FROM members
+         WHERE (farm_name IS NOT NULL OR location IS NOT NULL) AND profile_key_id IS NULL
+         FOR UPDATE"
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    
+    for (member_id, farm_name, location) in &legacy {
+        let farm_name = farm_name.as_deref().map(|value| security::encrypt_field(keys, value)).transpose()?;
+        let location = location.as_deref().map(|value| security::encrypt_field(keys, value)).transpose()?;
+        
+        sqlx::query(
+            "UPDATE members
+             SET farm_name = NULL, location = NULL, farm_name_ciphertext = $1, farm_name_nonce = $2,
+                 location_ciphertext = $3, location_nonce = $4, profile_key_id = $5
+             WHERE id = $6"
+        )
+        .bind(farm_name.as_ref().map(|field| &field.ciphertext))
+        .bind(farm_name.as_ref().map(|field| &field.nonce))
+        .bind(location.as_ref().map(|field| &field.ciphertext))
+        .bind(location.as_ref().map(|field| &field.nonce))
+        .bind(keys.current_id())
+        .bind(member_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    
+    tx.commit().await?;
+    
+    Ok(legacy.len() as u64)
+}
+
+/// Placeholder email for an erased member, unique so the column constraint still holds
+fn tombstone_email(member_id: Uuid) -> String {
+    format!("deleted-{}@deleted.invalid", member_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth;
+    use crate::error::AuthError;
+    use crate::listings;
+    use crate::models::{NotificationType, OrderStatus};
+    use crate::notifications;
+    use crate::orders::{self, CreateOrderData};
+    use crate::reports;
+    use crate::test_utils::{insert_test_listing, insert_test_member, test_config, test_field_keys};
+    use chrono::Duration;
+    use rust_decimal::Decimal;
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_delete_member_data(pool: PgPool) {
+        let seller = auth::register_member(
+            &pool,
+            "grower@example.com",
+            "securepassword",
+            "Jane Grower",
+            Some("Hilltop Farm"),
+            Some("Vermont"),
+            &test_field_keys(),
+        )
+        .await
+        .unwrap();
+        let buyer = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller.id).await;
+        let order = orders::create_order(
+            &pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(3, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        sqlx::query("UPDATE orders SET status = $1 WHERE id = $2")
+            .bind(OrderStatus::Completed.to_string())
+            .bind(order.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        notifications::create_notification(&pool, seller.id, NotificationType::NewProposal, "Vote now")
+            .await
+            .unwrap();
+        
+        let config = test_config();
+        let other_device_token = auth::generate_token(&seller, &config).unwrap();
+        
+        delete_member_data(&pool, seller.id).await.unwrap();
+        
+        // Tokens issued to any device are rejected once the account is closed
+        let result = auth::validate_token(&pool, &other_device_token, &config.jwt_secret).await;
+        assert!(matches!(result, Err(AuthError::TokenRevoked)));
+        
+        // Personal data is gone and the account can no longer sign in
+        let (email, name, farm_name, location): (String, String, Option<String>, Option<String>) =
+            sqlx::query_as("SELECT email, name, farm_name, location FROM members WHERE id = $1")
+                .bind(seller.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(email, tombstone_email(seller.id));
+        assert_eq!(name, "");
+        assert_eq!((farm_name, location), (None, None));
+        let mut erased = Member { farm_name: None, location: None, ..seller.clone() };
+        decrypt_profile(&pool, &test_field_keys(), &mut erased).await.unwrap();
+        assert_eq!((erased.farm_name, erased.location), (None, None));
+        let (matches,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM members WHERE email = $1")
+            .bind("grower@example.com")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(matches, 0);
+        let result = auth::authenticate_member(&pool, "grower@example.com", "securepassword").await;
+        assert!(matches!(result, Err(AuthError::InvalidCredentials)));
+        
+        assert_eq!(listings::get_listing(&pool, listing_id, true).await.unwrap().availability, "Archived");
+        assert!(notifications::get_notifications(&pool, seller.id).await.unwrap().is_empty());
+        
+        // Completed sales still count towards the books
+        let now = Utc::now();
+        let report = reports::sales_report(&pool, seller.id, now - Duration::days(1), now).await.unwrap();
+        assert_eq!(report.order_count, 1);
+        assert_eq!(report.total_revenue, order.total_amount);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_profile_stored_encrypted(pool: PgPool) {
+        let keys = test_field_keys();
+        let member = auth::register_member(
+            &pool,
+            "grower@example.com",
+            "securepassword",
+            "Jane Grower",
+            Some("Hilltop Farm"),
+            None,
+            &keys,
+        )
+        .await
+        .unwrap();
+        assert_eq!(member.farm_name.as_deref(), Some("Hilltop Farm"));
+        
+        let (farm_name, ciphertext, key_id): (Option<String>, Vec<u8>, String) = sqlx::query_as(
+            "SELECT farm_name, farm_name_ciphertext, profile_key_id FROM members WHERE id = $1"
+        )
+        .bind(member.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(farm_name, None);
+        assert!(!String::from_utf8_lossy(&ciphertext).contains("Hilltop"));
+        assert_eq!(key_id, keys.current_id());
+        
+        // Login reads the row without the plaintext, so it has to be decrypted
+        let mut loaded = auth::authenticate_member(&pool, "grower@example.com", "securepassword").await.unwrap();
+        assert_eq!(loaded.farm_name, None);
+        decrypt_profile(&pool, &keys, &mut loaded).await.unwrap();
+        assert_eq!(loaded.farm_name.as_deref(), Some("Hilltop Farm"));
+        assert_eq!(loaded.location, None);
+        
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_encrypt_legacy_profiles(pool: PgPool) {
+        let keys = test_field_keys();
+        let legacy = insert_test_member(&pool).await;
+        sqlx::query("UPDATE members SET location = 'Vermont' WHERE id = $1")
+            .bind(legacy)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let encrypted = auth::register_member(
+            &pool,
+            "grower@example.com",
+            "securepassword",
+            "Jane Grower",
+            Some("Hilltop Farm"),
+            None,
+            &keys,
+        )
+        .await
+        .unwrap();
+        
+        // Only the plaintext row is rewritten, and only once
+        assert_eq!(encrypt_legacy_profiles(&pool, &keys).await.unwrap(), 1);
+        assert_eq!(encrypt_legacy_profiles(&pool, &keys).await.unwrap(), 0);
+        
+        let (location, ciphertext, key_id): (Option<String>, Vec<u8>, String) = sqlx::query_as(
+            "SELECT location, location_ciphertext, profile_key_id FROM members WHERE id = $1"
+        )
+        .bind(legacy)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(location, None);
+        assert!(!String::from_utf8_lossy(&ciphertext).contains("Vermont"));
+        assert_eq!(key_id, keys.current_id());
+        
+        let mut member = Member {
+            id: legacy,
+            farm_name: None,
+            location: None,
+            ..encrypted.clone()
+        };
+        decrypt_profile(&pool, &keys, &mut member).await.unwrap();
+        assert_eq!((member.farm_name, member.location.as_deref()), (None, Some("Vermont")));
+        
+        let mut member = Member { farm_name: None, ..encrypted };
+        decrypt_profile(&pool, &keys, &mut member).await.unwrap();
+        assert_eq!(member.farm_name.as_deref(), Some("Hilltop Farm"));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_delete_member_data_only_once(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        
+        delete_member_data(&pool, member).await.unwrap();
+        
+        let result = delete_member_data(&pool, member).await;
+        assert!(matches!(result, Err(SecurityError::MemberNotFound)));
+        let result = delete_member_data(&pool, Uuid::new_v4()).await;
+        assert!(matches!(result, Err(SecurityError::MemberNotFound)));
+    }
+}
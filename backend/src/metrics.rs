@@ -0,0 +1,171 @@
+//! Prometheus metrics for HTTP traffic and the database pool
+
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Upper bounds in seconds of the request latency histogram buckets
+const LATENCY_BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Path label for requests that matched no route, so unknown URLs can't grow the label set
+pub const UNMATCHED_PATH: &str = "unmatched";
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Non-cumulative count per bucket; the cumulative form is produced when rendering
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|&bound| seconds <= bound) {
+            self.buckets[bucket] += 1;
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct HttpMetrics {
+    /// Request counts keyed by method, route and status code
+    requests: BTreeMap<(String, String, u16), u64>,
+    /// Request latencies keyed by method and route
+    latencies: BTreeMap<(String, String), Histogram>,
+}
+
+fn http_metrics() -> &'static Mutex<HttpMetrics> {
+    static METRICS: OnceLock<Mutex<HttpMetrics>> = OnceLock::new();
+    METRICS.get_or_init(Default::default)
+}
+
+/// Record a completed HTTP request
+pub fn record_request(method: &str, path: &str, status: u16, elapsed: Duration) {
+    let mut metrics = http_metrics().lock().unwrap_or_else(|e| e.into_inner());
+    
+    *metrics
+        .requests
+        .entry((method.to_string(), path.to_string(), status))
+        .or_default() += 1;
+    metrics
+        .latencies
+        .entry((method.to_string(), path.to_string()))
+        .or_default()
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Render all metrics in the Prometheus text exposition format
+pub fn render(pool: &PgPool) -> String {
+    let metrics = http_metrics().lock().unwrap_or_else(|e| e.into_inner());
+    let mut out = String::new();
+    
+    // Writing to a String cannot fail
+    let _ = writeln!(out, "# HELP http_requests_total Total HTTP requests by method, route and status");
+    let _ = writeln!(out, "# TYPE http_requests_total counter");
+    for ((method, path, status), count) in &metrics.requests {
+        let _ = writeln!(
+            out,
+            "http_requests_total{{method=\"{}\",path=\"{}\",status=\"{}\"}} {}",
+            escape_label(method),
+            escape_label(path),
+            status,
+            count
+        );
+    }
+    
+    let _ = writeln!(out, "# HELP http_request_duration_seconds HTTP request latency by method and route");
+    let _ = writeln!(out, "# TYPE http_request_duration_seconds histogram");
+    for ((method, path), histogram) in &metrics.latencies {
+        let labels = format!("method=\"{}\",path=\"{}\"", escape_label(method), escape_label(path));
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+            cumulative += count;
+            let _ = writeln!(out, "http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}", labels, bound, cumulative);
+        }
+        let _ = writeln!(out, "http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}", labels, histogram.count);
+        let _ = writeln!(out, "http_request_duration_seconds_sum{{{}}} {}", labels, histogram.sum);
+        let _ = writeln!(out, "http_request_duration_seconds_count{{{}}} {}", labels, histogram.count);
+    }
+    
+    let _ = writeln!(out, "# HELP db_pool_connections Open connections in the database pool");
+    let _ = writeln!(out, "# TYPE db_pool_connections gauge");
+    let _ = writeln!(out, "db_pool_connections {}", pool.size());
+    let _ = writeln!(out, "# HELP db_pool_idle_connections Idle connections in the database pool");
+    let _ = writeln!(out, "# TYPE db_pool_idle_connections gauge");
+    let _ = writeln!(out, "db_pool_idle_connections {}", pool.num_idle());
+    
+    out
+}
+
+/// Escape a label value per the exposition format
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::create_router;
+    use crate::test_utils::test_config;
+    use axum::{body::Body, http::Request, Router};
+    use tower::Service;
+    
+    async fn get(app: &mut Router, uri: &str) -> String {
+        std::future::poll_fn(|cx| Service::<Request<Body>>::poll_ready(app, cx)).await.unwrap();
+        let response = app
+            .call(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+    
+    /// Value of the sample whose name and labels start the line, or 0 if absent
+    fn sample(metrics: &str, series: &str) -> u64 {
+        metrics
+            .lines()
+            .find_map(|line| line.strip_prefix(series))
+            .map(|value| value.trim().parse().unwrap())
+            .unwrap_or(0)
+    }
+    
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = Histogram::default();
+        histogram.observe(0.003);
+        histogram.observe(0.2);
+        histogram.observe(60.0);
+        
+        assert_eq!(histogram.buckets[0], 1);
+        assert_eq!(histogram.buckets[5], 1);
+        // Observations above every bound only count towards +Inf
+        assert_eq!(histogram.buckets.iter().sum::<u64>(), 2);
+        assert_eq!(histogram.count, 3);
+    }
+    
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("/api/listings/:id"), "/api/listings/:id");
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+    
+    #[tokio::test]
+    async fn test_requests_counted_at_metrics_endpoint() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let mut app = create_router(pool, test_config(), Default::default());
+        let series = "http_requests_total{method=\"GET\",path=\"/health/live\",status=\"200\"}";
+        
+        let before = sample(&get(&mut app, "/metrics").await, series);
+        assert_eq!(get(&mut app, "/health/live").await, "OK");
+        let metrics = get(&mut app, "/metrics").await;
+        
+        assert_eq!(sample(&metrics, series), before + 1);
+        assert!(metrics.contains("http_request_duration_seconds_count{method=\"GET\",path=\"/health/live\"}"));
+        assert!(metrics.contains("db_pool_connections 0"));
+        assert!(metrics.contains("db_pool_idle_connections 0"));
+    }
+}
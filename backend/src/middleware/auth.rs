@@ -1,6 +1,6 @@
 use axum::{
     async_trait,
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts},
     http::{request::Parts, StatusCode},
     RequestPartsExt,
 };
@@ -9,16 +9,20 @@ use axum_extra::{
     TypedHeader,
 };
 
-use crate::{auth::Claims, error::DoftaError};
+use sqlx::PgPool;
+
+use crate::{auth::Claims, config::Config, error::AuthError, models::Role};
 
 #[async_trait]
 impl<S> FromRequestParts<S> for Claims
 where
     S: Send + Sync,
+    Config: FromRef<S>,
+    PgPool: FromRef<S>,
 {
     type Rejection = (StatusCode, String);
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         // Extract the token from the authorization header
         let TypedHeader(Authorization(bearer)) = parts
             .extract::<TypedHeader<Authorization<Bearer>>>()
@@ -31,11 +35,163 @@ where
             })?;
 
         // Validate the token
-        crate::auth::validate_token(bearer.token()).map_err(|e| {
-            (
-                StatusCode::UNAUTHORIZED,
-                format!("Invalid token: {}", e),
-            )
-        })
+        let config = Config::from_ref(state);
+        let pool = PgPool::from_ref(state);
+        crate::auth::validate_token(&pool, bearer.token(), &config.jwt_secret)
+            .await
+            .map_err(|e| match e {
+                AuthError::TokenExpired => (StatusCode::UNAUTHORIZED, "Token expired".to_string()),
+                AuthError::TokenRevoked => (StatusCode::UNAUTHORIZED, "Token revoked".to_string()),
+                AuthError::Database(_) => {
+                    tracing::error!("Token validation failed: {}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
+                }
+                _ => (StatusCode::UNAUTHORIZED, "Invalid token".to_string()),
+            })
+    }
+}
+
+/// Claims of an authenticated admin; rejects other members with 403
+#[derive(Debug, Clone)]
+pub struct AdminClaims(pub Claims);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminClaims
+where
+    S: Send + Sync,
+    Config: FromRef<S>,
+    PgPool: FromRef<S>,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = Claims::from_request_parts(parts, state).await?;
+
+        if claims.role != Role::Admin {
+            return Err((StatusCode::FORBIDDEN, "Admin access required".to_string()));
+        }
+
+        Ok(AdminClaims(claims))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::AppState;
+    use crate::test_utils::{insert_test_member, test_config, test_member};
+    use axum::http::Request;
+    use chrono::Utc;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use uuid::Uuid;
+
+    fn app_state(pool: PgPool) -> AppState {
+        AppState {
+            pool,
+            config: test_config(),
+            views: Default::default(),
+        }
+    }
+
+    /// State whose pool never connects, for rejections that happen before any query
+    fn offline_state() -> AppState {
+        app_state(PgPool::connect_lazy("postgres://localhost/unused").unwrap())
+    }
+
+    fn request_parts(token: Option<&str>) -> Parts {
+        let mut request = Request::builder().uri("/protected");
+        if let Some(token) = token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        request.body(()).unwrap().into_parts().0
+    }
+
+    async fn extract(state: &AppState, token: Option<&str>) -> Result<Claims, (StatusCode, String)> {
+        Claims::from_request_parts(&mut request_parts(token), state).await
+    }
+
+    async fn extract_admin(state: &AppState, token: Option<&str>) -> Result<AdminClaims, (StatusCode, String)> {
+        AdminClaims::from_request_parts(&mut request_parts(token), state).await
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_valid_token_accepted(pool: PgPool) {
+        let state = app_state(pool);
+        let member = test_member(Role::Member);
+        let token = crate::auth::generate_token(&member, &state.config).unwrap();
+
+        let claims = extract(&state, Some(&token)).await.unwrap();
+
+        assert_eq!(claims.sub, member.id);
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_logged_out_token_rejected(pool: PgPool) {
+        let state = app_state(pool);
+        let mut member = test_member(Role::Member);
+        member.id = insert_test_member(&state.pool).await;
+        let token = crate::auth::generate_token(&member, &state.config).unwrap();
+        let claims = extract(&state, Some(&token)).await.unwrap();
+
+        crate::auth::logout(&state.pool, &claims).await.unwrap();
+
+        assert_eq!(
+            extract(&state, Some(&token)).await.unwrap_err(),
+            (StatusCode::UNAUTHORIZED, "Token revoked".to_string())
+        );
+    }
+
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_admin_guard(pool: PgPool) {
+        let state = app_state(pool);
+        let admin = test_member(Role::Admin);
+        let token = crate::auth::generate_token(&admin, &state.config).unwrap();
+        let AdminClaims(claims) = extract_admin(&state, Some(&token)).await.unwrap();
+        assert_eq!(claims.sub, admin.id);
+
+        let member = test_member(Role::Member);
+        let token = crate::auth::generate_token(&member, &state.config).unwrap();
+        assert_eq!(
+            extract_admin(&state, Some(&token)).await.unwrap_err(),
+            (StatusCode::FORBIDDEN, "Admin access required".to_string())
+        );
+
+        // Authentication failures are still reported as 401
+        assert_eq!(extract_admin(&state, None).await.unwrap_err().0, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_malformed_tokens_rejected() {
+        let state = offline_state();
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: Uuid::new_v4(),
+            role: Role::Member,
+            jti: Uuid::new_v4(),
+            iat: now - 7200,
+            exp: now - 3600,
+        };
+        let expired = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(state.config.jwt_secret.as_bytes()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            extract(&state, Some(&expired)).await.unwrap_err(),
+            (StatusCode::UNAUTHORIZED, "Token expired".to_string())
+        );
+        assert_eq!(
+            extract(&state, Some("not-a-jwt")).await.unwrap_err(),
+            (StatusCode::UNAUTHORIZED, "Invalid token".to_string())
+        );
+        assert_eq!(
+            extract(&state, None).await.unwrap_err(),
+            (StatusCode::UNAUTHORIZED, "Missing authorization header".to_string())
+        );
     }
 }
@@ -0,0 +1,26 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+
+use crate::metrics;
+
+/// Count each request and time it, labelled by its route template
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    // Route templates such as /api/listings/:id keep label cardinality bounded
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| metrics::UNMATCHED_PATH.to_string());
+    let method = request.method().clone();
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    metrics::record_request(method.as_str(), &path, response.status().as_u16(), started.elapsed());
+
+    response
+}
@@ -1 +1,4 @@
 pub mod auth;
+pub mod request_id;
+pub mod metrics;
+pub mod rate_limit;
@@ -0,0 +1,211 @@
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{config::Config, error::DoftaError};
+
+/// Largest login body buffered to read the email from
+const MAX_LOGIN_BODY_BYTES: usize = 16 * 1024;
+
+/// Clients tracked before buckets that have fully refilled are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// In-memory token buckets keyed by client, refilled continuously over a window
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// Allow `attempts` per `window` for each key, with bursts of up to `attempts`
+    pub fn new(attempts: u32, window: Duration) -> Self {
+        let capacity = f64::from(attempts.max(1));
+        Self {
+            capacity,
+            refill_per_sec: capacity / window.as_secs_f64().max(f64::EPSILON),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Limiter for login attempts using the configured rate
+    pub fn for_login(config: &Config) -> Self {
+        Self::new(
+            config.login_rate_limit_attempts,
+            Duration::from_secs(config.login_rate_limit_window_secs),
+        )
+    }
+
+    /// Take a token for `key`, or return how long until one is available
+    pub fn check(&self, key: &str) -> Result<(), Duration> {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(key) {
+            buckets.retain(|_, bucket| self.refilled(*bucket, now) < self.capacity);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        bucket.tokens = self.refilled(*bucket, now);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.refill_per_sec))
+        }
+    }
+
+    fn refilled(&self, bucket: Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity)
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginEmail {
+    email: String,
+}
+
+/// Throttle login attempts per client IP and per email address
+///
+/// The body is buffered to read the email and then passed on unchanged.
+/// Rejected attempts get a 429 with a `Retry-After` header.
+pub async fn login_rate_limit(
+    State(limiter): State<RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Result<Response, DoftaError> {
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let (parts, body) = request.into_parts();
+    let bytes = axum::body::to_bytes(body, MAX_LOGIN_BODY_BYTES)
+        .await
+        .map_err(|_| DoftaError::InvalidInput("Request body is too large".to_string()))?;
+    let email = serde_json::from_slice::<LoginEmail>(&bytes)
+        .ok()
+        .map(|login| login.email.trim().to_lowercase());
+
+    let mut keys = vec![format!("ip:{}", ip)];
+    keys.extend(email.map(|email| format!("email:{}", email)));
+    for key in &keys {
+        if let Err(retry_after) = limiter.check(key) {
+            tracing::warn!("Login rate limit exceeded for {}", key);
+            // Round up so clients never retry before a token is available
+            let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            return Err(DoftaError::TooManyRequests(retry_after_secs));
+        }
+    }
+
+    Ok(next.run(Request::from_parts(parts, Body::from(bytes))).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        http::{header, StatusCode},
+        routing::post,
+        Router,
+    };
+    use tower::Service;
+
+    async fn login(app: &mut Router, ip: [u8; 4], email: &str) -> Response {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/login")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!(r#"{{"email":"{}","password":"wrong"}}"#, email)))
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((ip, 40000))));
+
+        std::future::poll_fn(|cx| Service::<Request<Body>>::poll_ready(app, cx)).await.unwrap();
+        app.call(request).await.unwrap()
+    }
+
+    fn app(limiter: RateLimiter) -> Router {
+        // Echo the body to show it reaches the handler intact
+        Router::new().route(
+            "/login",
+            post(|body: String| async move { body })
+                .layer(axum::middleware::from_fn_with_state(limiter, login_rate_limit)),
+        )
+    }
+
+    #[test]
+    fn test_bucket_refills_over_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert!(limiter.check_at("client", start).is_ok());
+        assert!(limiter.check_at("client", start).is_ok());
+        let retry_after = limiter.check_at("client", start).unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(30));
+
+        // One token comes back every 30 seconds
+        assert!(limiter.check_at("client", start + Duration::from_secs(29)).is_err());
+        assert!(limiter.check_at("client", start + Duration::from_secs(30)).is_ok());
+        // Other clients have their own bucket
+        assert!(limiter.check_at("other", start).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rapid_login_attempts_rejected() {
+        let mut app = app(RateLimiter::new(3, Duration::from_secs(60)));
+
+        for _ in 0..3 {
+            let response = login(&mut app, [10, 0, 0, 1], "farmer@example.com").await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = login(&mut app, [10, 0, 0, 1], "farmer@example.com").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "20");
+    }
+
+    #[tokio::test]
+    async fn test_login_limited_per_email_across_ips() {
+        let mut app = app(RateLimiter::new(2, Duration::from_secs(60)));
+
+        let response = login(&mut app, [10, 0, 0, 1], "farmer@example.com").await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains("farmer@example.com"));
+        assert_eq!(login(&mut app, [10, 0, 0, 2], "Farmer@Example.com").await.status(), StatusCode::OK);
+
+        // A third address still can't guess the same account's password
+        let response = login(&mut app, [10, 0, 0, 3], "farmer@example.com").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        // But may log in to a different account
+        assert_eq!(login(&mut app, [10, 0, 0, 3], "grower@example.com").await.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,106 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Header carrying the request's correlation ID
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest incoming request ID that is trusted rather than replaced
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Tag each request with an ID and log its method, path, status and latency
+///
+/// An incoming `x-request-id` is kept so IDs can be correlated across services;
+/// otherwise a new one is generated. The ID is echoed in the response headers.
+pub async fn request_id(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= MAX_REQUEST_ID_LENGTH)
+        .and_then(|value| HeaderValue::from_str(value).ok())
+        .unwrap_or_else(generate_request_id);
+
+    // Make the ID visible to handlers as well
+    request.headers_mut().insert(REQUEST_ID_HEADER.clone(), request_id.clone());
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id.to_str().unwrap_or_default(),
+        %method,
+        %path,
+    );
+
+    let started = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+
+    span.in_scope(|| {
+        tracing::info!(
+            status = response.status().as_u16(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "{} {} {}",
+            method,
+            path,
+            response.status()
+        );
+    });
+
+    response.headers_mut().insert(REQUEST_ID_HEADER.clone(), request_id);
+    response
+}
+
+fn generate_request_id() -> HeaderValue {
+    HeaderValue::from_str(&Uuid::new_v4().to_string()).expect("UUIDs are valid header values")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::Service;
+
+    /// Request ID echoed back for a request carrying `incoming`, if any
+    async fn response_request_id(incoming: Option<&str>) -> Option<String> {
+        let mut app = Router::new()
+            .route("/health", get(|| async { "OK" }))
+            .layer(axum::middleware::from_fn(request_id));
+        let mut request = Request::builder().uri("/health");
+        if let Some(incoming) = incoming {
+            request = request.header(&REQUEST_ID_HEADER, incoming);
+        }
+
+        std::future::poll_fn(|cx| Service::<Request<Body>>::poll_ready(&mut app, cx)).await.unwrap();
+        let response = app.call(request.body(Body::empty()).unwrap()).await.unwrap();
+
+        response
+            .headers()
+            .get(&REQUEST_ID_HEADER)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_supplied_request_id_preserved() {
+        assert_eq!(
+            response_request_id(Some("abc-123")).await.as_deref(),
+            Some("abc-123")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_id_generated_when_absent() {
+        let generated = response_request_id(None).await.expect("request ID missing");
+        assert!(Uuid::parse_str(&generated).is_ok());
+
+        // Oversized IDs are replaced rather than trusted
+        let replaced = response_request_id(Some(&"x".repeat(MAX_REQUEST_ID_LENGTH + 1))).await.unwrap();
+        assert!(Uuid::parse_str(&replaced).is_ok());
+    }
+}
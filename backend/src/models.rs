@@ -1,3 +1,4 @@
+use crate::listings::ListingFilters;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -5,14 +6,38 @@ use sqlx::FromRow;
 use uuid::Uuid;
 
 /// Member represents a registered farmer in the cooperative
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Clone, Serialize, Deserialize, FromRow)]
 pub struct Member {
     pub id: Uuid,
     pub email: String,
+    #[serde(skip_serializing)]
     pub password_hash: String,
+    pub name: String,
+    /// Stored encrypted; filled in by `members::decrypt_profile`, not read from the row
+    #[sqlx(default)]
+    pub farm_name: Option<String>,
+    #[sqlx(default)]
+    pub location: Option<String>,
+    pub role: String,
     pub created_at: DateTime<Utc>,
 }
 
+// Written by hand so the password hash never ends up in logs
+impl std::fmt::Debug for Member {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Member")
+            .field("id", &self.id)
+            .field("email", &self.email)
+            .field("password_hash", &"<redacted>")
+            .field("name", &self.name)
+            .field("farm_name", &self.farm_name)
+            .field("location", &self.location)
+            .field("role", &self.role)
+            .field("created_at", &self.created_at)
+            .finish()
+    }
+}
+
 impl Member {
     /// Validate member data
     pub fn validate(&self) -> Result<(), String> {
@@ -28,12 +53,45 @@ impl Member {
             return Err("Password hash cannot be empty".to_string());
         }
         
+        // Validate role
+        self.role.parse::<Role>()
+            .map_err(|e| format!("Invalid role: {}", e))?;
+        
         Ok(())
     }
 }
 
+/// Member role enumeration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text")]
+pub enum Role {
+    Member,
+    Admin,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Member => write!(f, "Member"),
+            Role::Admin => write!(f, "Admin"),
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Member" => Ok(Role::Member),
+            "Admin" => Ok(Role::Admin),
+            _ => Err(format!("Invalid role: {}", s)),
+        }
+    }
+}
+
 /// Availability status for product listings
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "text")]
 pub enum AvailabilityStatus {
     Available,
@@ -64,6 +122,59 @@ impl std::str::FromStr for AvailabilityStatus {
     }
 }
 
+/// Product category for listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text")]
+pub enum Category {
+    Vegetables,
+    Fruits,
+    Grains,
+    Dairy,
+    Livestock,
+    Other,
+}
+
+impl Category {
+    /// Every category, in display order
+    pub const ALL: [Category; 6] = [
+        Category::Vegetables,
+        Category::Fruits,
+        Category::Grains,
+        Category::Dairy,
+        Category::Livestock,
+        Category::Other,
+    ];
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Category::Vegetables => write!(f, "Vegetables"),
+            Category::Fruits => write!(f, "Fruits"),
+            Category::Grains => write!(f, "Grains"),
+            Category::Dairy => write!(f, "Dairy"),
+            Category::Livestock => write!(f, "Livestock"),
+            Category::Other => write!(f, "Other"),
+        }
+    }
+}
+
+impl std::str::FromStr for Category {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Vegetables" => Ok(Category::Vegetables),
+            "Fruits" => Ok(Category::Fruits),
+            "Grains" => Ok(Category::Grains),
+            "Dairy" => Ok(Category::Dairy),
+            "Livestock" => Ok(Category::Livestock),
+            "Other" => Ok(Category::Other),
+            _ => Err(format!("Invalid category: {}", s)),
+        }
+    }
+}
+
 /// Product listing represents an item offered for sale
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ProductListing {
@@ -71,9 +182,19 @@ pub struct ProductListing {
     pub member_id: Uuid,
     pub name: String,
     pub description: String,
-    pub quantity: Decimal,
+    pub category: String,
+    pub quantity_available: Decimal,
     pub unit_price: Decimal,
+    pub unit_of_measure: String,
     pub availability: String,
+    /// Incremented on every change, for optimistic concurrency
+    pub version: i32,
+    /// After this the listing is hidden from search and archived by the expiry sweep
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Views by other members, only loaded for the owner
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view_count: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -89,7 +210,11 @@ impl ProductListing {
             return Err("Product description cannot be empty".to_string());
         }
         
-        if self.quantity <= Decimal::ZERO {
+        if self.category.trim().is_empty() {
+            return Err("Product category cannot be empty".to_string());
+        }
+        
+        if self.quantity_available <= Decimal::ZERO {
             return Err("Quantity must be positive".to_string());
         }
         
@@ -97,6 +222,10 @@ impl ProductListing {
             return Err("Unit price must be positive".to_string());
         }
         
+        if self.unit_of_measure.trim().is_empty() {
+            return Err("Unit of measure cannot be empty".to_string());
+        }
+        
         // Validate availability status
         self.availability.parse::<AvailabilityStatus>()
             .map_err(|e| format!("Invalid availability status: {}", e))?;
@@ -105,15 +234,32 @@ impl ProductListing {
     }
 }
 
+/// A listing search a member saved under a name to run again later
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SavedSearch {
+    pub id: Uuid,
+    pub member_id: Uuid,
+    pub name: String,
+    pub filters: sqlx::types::Json<ListingFilters>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 /// Order status enumeration
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "text")]
 pub enum OrderStatus {
     Pending,
     Accepted,
     Rejected,
+    Shipped,
+    Delivered,
     Completed,
     Cancelled,
+    /// A party has raised a dispute and an admin must resolve it
+    Disputed,
+    /// An admin has resolved the dispute
+    Resolved,
 }
 
 impl std::fmt::Display for OrderStatus {
@@ -122,8 +268,12 @@ impl std::fmt::Display for OrderStatus {
             OrderStatus::Pending => write!(f, "Pending"),
             OrderStatus::Accepted => write!(f, "Accepted"),
             OrderStatus::Rejected => write!(f, "Rejected"),
+            OrderStatus::Shipped => write!(f, "Shipped"),
+            OrderStatus::Delivered => write!(f, "Delivered"),
             OrderStatus::Completed => write!(f, "Completed"),
             OrderStatus::Cancelled => write!(f, "Cancelled"),
+            OrderStatus::Disputed => write!(f, "Disputed"),
+            OrderStatus::Resolved => write!(f, "Resolved"),
         }
     }
 }
@@ -136,13 +286,66 @@ impl std::str::FromStr for OrderStatus {
             "Pending" => Ok(OrderStatus::Pending),
             "Accepted" => Ok(OrderStatus::Accepted),
             "Rejected" => Ok(OrderStatus::Rejected),
+            "Shipped" => Ok(OrderStatus::Shipped),
+            "Delivered" => Ok(OrderStatus::Delivered),
             "Completed" => Ok(OrderStatus::Completed),
             "Cancelled" => Ok(OrderStatus::Cancelled),
+            "Disputed" => Ok(OrderStatus::Disputed),
+            "Resolved" => Ok(OrderStatus::Resolved),
             _ => Err(format!("Invalid order status: {}", s)),
         }
     }
 }
 
+/// Status of an order's escrow on the NEAR contract, mirroring the contract's `OrderStatus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EscrowStatus {
+    /// Funds are locked awaiting delivery
+    Pending,
+    /// Funds were released to the seller
+    Completed,
+    /// Funds were returned to the buyer
+    Refunded,
+    /// Funds are frozen pending arbitration
+    Disputed,
+    /// Arbitration paid out the buyer, the seller or both
+    Resolved,
+}
+
+impl EscrowStatus {
+    /// Whether the escrow has paid out and holds no more funds
+    pub fn is_settled(&self) -> bool {
+        matches!(self, EscrowStatus::Completed | EscrowStatus::Refunded | EscrowStatus::Resolved)
+    }
+}
+
+impl std::fmt::Display for EscrowStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EscrowStatus::Pending => write!(f, "Pending"),
+            EscrowStatus::Completed => write!(f, "Completed"),
+            EscrowStatus::Refunded => write!(f, "Refunded"),
+            EscrowStatus::Disputed => write!(f, "Disputed"),
+            EscrowStatus::Resolved => write!(f, "Resolved"),
+        }
+    }
+}
+
+impl std::str::FromStr for EscrowStatus {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Pending" => Ok(EscrowStatus::Pending),
+            "Completed" => Ok(EscrowStatus::Completed),
+            "Refunded" => Ok(EscrowStatus::Refunded),
+            "Disputed" => Ok(EscrowStatus::Disputed),
+            "Resolved" => Ok(EscrowStatus::Resolved),
+            _ => Err(format!("Invalid escrow status: {}", s)),
+        }
+    }
+}
+
 /// Order represents a purchase request
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Order {
@@ -153,9 +356,25 @@ pub struct Order {
     pub quantity: Decimal,
     pub total_amount: Decimal,
     pub status: String,
+    /// Order ID on the NEAR escrow contract, once the buyer has funded escrow
+    pub escrow_order_id: Option<String>,
+    /// Seller's note to the buyer when accepting or rejecting the order
+    pub seller_note: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
+/// Order joined to a summary of its listing, so order views don't need a
+/// separate listing fetch per row
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OrderWithListing {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub order: Order,
+    /// `None` only if the listing row no longer exists
+    pub product_name: Option<String>,
+    pub unit_of_measure: Option<String>,
+}
+
 impl Order {
     /// Validate order data
     pub fn validate(&self) -> Result<(), String> {
@@ -175,8 +394,73 @@ impl Order {
     }
 }
 
+/// Order status change records who moved an order between statuses
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OrderStatusChange {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub from_status: String,
+    pub to_status: String,
+    pub actor_id: Uuid,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A message in the thread between an order's buyer and seller
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OrderMessage {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub sender_id: Uuid,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How an admin settled a dispute, matching the escrow contract's resolutions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisputeResolution {
+    RefundBuyer,
+    PaySeller,
+    /// `buyer_bps` basis points (0-10000) go back to the buyer, the rest to the seller
+    Split { buyer_bps: u16 },
+}
+
+impl DisputeResolution {
+    /// Buyer's share in basis points, for split resolutions
+    pub fn buyer_bps(&self) -> Option<u16> {
+        match self {
+            DisputeResolution::Split { buyer_bps } => Some(*buyer_bps),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for DisputeResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisputeResolution::RefundBuyer => write!(f, "RefundBuyer"),
+            DisputeResolution::PaySeller => write!(f, "PaySeller"),
+            DisputeResolution::Split { .. } => write!(f, "Split"),
+        }
+    }
+}
+
+/// Dispute raised by a buyer or seller over an order
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Dispute {
+    pub id: Uuid,
+    pub order_id: Uuid,
+    pub raised_by: Uuid,
+    pub reason: String,
+    /// Set once resolved, see [`DisputeResolution`]
+    pub resolution: Option<String>,
+    pub buyer_bps: Option<i32>,
+    pub resolved_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
 /// Transaction status enumeration
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "text")]
 pub enum TransactionStatus {
     Pending,
@@ -210,6 +494,16 @@ impl std::str::FromStr for TransactionStatus {
     }
 }
 
+/// Cooperative fee override for one listing category
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CategoryFee {
+    pub category: String,
+    /// Fraction of the order total withheld, between 0 and 1
+    pub fee_percentage: Decimal,
+    pub updated_by: Uuid,
+    pub updated_at: DateTime<Utc>,
+}
+
 /// Transaction represents a financial exchange
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Transaction {
@@ -220,6 +514,8 @@ pub struct Transaction {
     pub status: String,
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub reversal_reason: Option<String>,
+    pub reversed_at: Option<DateTime<Utc>>,
 }
 
 impl Transaction {
@@ -242,7 +538,7 @@ impl Transaction {
 }
 
 /// Proposal status enumeration
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "text")]
 pub enum ProposalStatus {
     Active,
@@ -276,6 +572,18 @@ impl std::str::FromStr for ProposalStatus {
     }
 }
 
+/// What a proposal does once it passes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProposalKind {
+    /// A decision recorded for the members, with no automatic effect
+    #[default]
+    General,
+    /// Set the cooperative-wide fee, a fraction between 0 and 1
+    FeeChange { new_fee: Decimal },
+    /// Erase a member's personal data, as a self-deletion would
+    MemberRemoval { member_id: Uuid },
+}
+
 /// Proposal represents a governance decision
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Proposal {
@@ -286,6 +594,12 @@ pub struct Proposal {
     pub status: String,
     pub votes_for: i32,
     pub votes_against: i32,
+    /// Whether the outcome is decided by the reputation-weighted tallies
+    pub weighted_voting: bool,
+    pub weighted_for: Decimal,
+    pub weighted_against: Decimal,
+    pub quorum: Decimal,
+    pub kind: sqlx::types::Json<ProposalKind>,
     pub created_at: DateTime<Utc>,
     pub voting_ends_at: DateTime<Utc>,
 }
@@ -309,6 +623,14 @@ impl Proposal {
             return Err("Votes against cannot be negative".to_string());
         }
         
+        if self.weighted_for < Decimal::ZERO || self.weighted_against < Decimal::ZERO {
+            return Err("Weighted votes cannot be negative".to_string());
+        }
+        
+        if self.quorum < Decimal::ZERO || self.quorum > Decimal::ONE {
+            return Err("Quorum must be between 0 and 1".to_string());
+        }
+        
         // Validate status
         self.status.parse::<ProposalStatus>()
             .map_err(|e| format!("Invalid proposal status: {}", e))?;
@@ -318,7 +640,7 @@ impl Proposal {
 }
 
 /// Vote type enumeration
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "text")]
 pub enum VoteType {
     For,
@@ -366,6 +688,16 @@ impl Vote {
     }
 }
 
+/// Proposal comment is a member's contribution to a proposal's discussion
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProposalComment {
+    pub id: Uuid,
+    pub proposal_id: Uuid,
+    pub member_id: Uuid,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Rating represents a member's rating of another member
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Rating {
@@ -389,13 +721,14 @@ impl Rating {
 }
 
 /// Notification type enumeration
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "text")]
 pub enum NotificationType {
     OrderPlaced,
     OrderStatusChanged,
     NewProposal,
     VotingEnded,
+    ListingArchived,
 }
 
 impl std::fmt::Display for NotificationType {
@@ -405,6 +738,7 @@ impl std::fmt::Display for NotificationType {
             NotificationType::OrderStatusChanged => write!(f, "OrderStatusChanged"),
             NotificationType::NewProposal => write!(f, "NewProposal"),
             NotificationType::VotingEnded => write!(f, "VotingEnded"),
+            NotificationType::ListingArchived => write!(f, "ListingArchived"),
         }
     }
 }
@@ -418,11 +752,58 @@ impl std::str::FromStr for NotificationType {
             "OrderStatusChanged" => Ok(NotificationType::OrderStatusChanged),
             "NewProposal" => Ok(NotificationType::NewProposal),
             "VotingEnded" => Ok(NotificationType::VotingEnded),
+            "ListingArchived" => Ok(NotificationType::ListingArchived),
             _ => Err(format!("Invalid notification type: {}", s)),
         }
     }
 }
 
+/// Kind of action recorded in the admin audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminAction {
+    FeeChanged,
+    DisputeResolved,
+    ProposalFinalized,
+    ListingArchived,
+}
+
+impl std::fmt::Display for AdminAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminAction::FeeChanged => write!(f, "FeeChanged"),
+            AdminAction::DisputeResolved => write!(f, "DisputeResolved"),
+            AdminAction::ProposalFinalized => write!(f, "ProposalFinalized"),
+            AdminAction::ListingArchived => write!(f, "ListingArchived"),
+        }
+    }
+}
+
+impl std::str::FromStr for AdminAction {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "FeeChanged" => Ok(AdminAction::FeeChanged),
+            "DisputeResolved" => Ok(AdminAction::DisputeResolved),
+            "ProposalFinalized" => Ok(AdminAction::ProposalFinalized),
+            "ListingArchived" => Ok(AdminAction::ListingArchived),
+            _ => Err(format!("Invalid admin action: {}", s)),
+        }
+    }
+}
+
+/// An entry in the admin audit log
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AdminAuditEntry {
+    pub id: Uuid,
+    pub actor_id: Uuid,
+    pub action: String,
+    /// What the action applied to, such as a dispute ID or a category
+    pub target: String,
+    pub metadata: sqlx::types::Json<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Notification represents a message to a member
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Notification {
@@ -431,6 +812,8 @@ pub struct Notification {
     pub notification_type: String,
     pub message: String,
     pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
 }
 
 impl Notification {
@@ -449,12 +832,76 @@ impl Notification {
 }
 
 
+/// A page of results with the metadata clients need to fetch the rest
+#[derive(Debug, Clone, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    /// Number of results across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub has_more: bool,
+}
+
+impl<T> Paginated<T> {
+    /// Wrap one page of `items` taken at `offset` from `total` results
+    pub fn new(items: Vec<T>, total: i64, limit: i64, offset: i64) -> Self {
+        let has_more = offset.saturating_add(items.len() as i64) < total;
+        Self {
+            items,
+            total,
+            limit,
+            offset,
+            has_more,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use proptest::prelude::*;
     use rust_decimal::Decimal;
     
+    #[test]
+    fn test_paginated_has_more() {
+        assert!(Paginated::new(vec![1, 2], 5, 2, 0).has_more);
+        assert!(Paginated::new(vec![3, 4], 5, 2, 2).has_more);
+        assert!(!Paginated::new(vec![5], 5, 2, 4).has_more);
+        assert!(!Paginated::new(vec![1, 2], 2, 2, 0).has_more);
+        // Paging past the end
+        assert!(!Paginated::new(Vec::<i32>::new(), 5, 2, 10).has_more);
+        assert!(!Paginated::new(Vec::<i32>::new(), 0, 20, 0).has_more);
+    }
+    
+    #[test]
+    fn test_listing_validate_rejects_blank_text() {
+        let listing = ProductListing {
+            id: Uuid::new_v4(),
+            member_id: Uuid::new_v4(),
+            name: "A".to_string(),
+            description: " ".to_string(),
+            category: "Vegetables".to_string(),
+            quantity_available: Decimal::ONE,
+            unit_price: Decimal::ONE,
+            unit_of_measure: "kg".to_string(),
+            availability: AvailabilityStatus::Available.to_string(),
+            version: 1,
+            expires_at: None,
+            view_count: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        assert_eq!(listing.validate(), Err("Product description cannot be empty".to_string()));
+        
+        let listing = ProductListing {
+            name: "  ".to_string(),
+            description: "Fresh".to_string(),
+            ..listing
+        };
+        assert_eq!(listing.validate(), Err("Product name cannot be empty".to_string()));
+    }
+    
     // Feature: dofta-farmers-coop, Property 9: Listing Completeness Invariant
     // For any created listing, it must contain non-empty name, description, 
     // positive quantity, positive unit price, and a valid availability status.
@@ -482,9 +929,14 @@ mod tests {
                 member_id: Uuid::new_v4(),
                 name: name.clone(),
                 description: description.clone(),
-                quantity,
+                category: "Vegetables".to_string(),
+                quantity_available: quantity,
                 unit_price,
+                unit_of_measure: "kg".to_string(),
                 availability: availability.clone(),
+                version: 1,
+                expires_at: None,
+                view_count: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             };
@@ -492,6 +944,16 @@ mod tests {
             // Validate the listing
             let validation_result = listing.validate();
             
+            // Property: Whitespace-only names and descriptions count as empty and are rejected
+            if name.trim().is_empty() {
+                prop_assert_eq!(validation_result, Err("Product name cannot be empty".to_string()));
+                return Ok(());
+            }
+            if description.trim().is_empty() {
+                prop_assert_eq!(validation_result, Err("Product description cannot be empty".to_string()));
+                return Ok(());
+            }
+            
             // Property: All fields must be valid
             prop_assert!(validation_result.is_ok(), "Listing validation failed: {:?}", validation_result);
             
@@ -502,7 +964,7 @@ mod tests {
             prop_assert!(!listing.description.trim().is_empty(), "Description is empty");
             
             // Property: Quantity must be positive
-            prop_assert!(listing.quantity > Decimal::ZERO, "Quantity is not positive");
+            prop_assert!(listing.quantity_available > Decimal::ZERO, "Quantity is not positive");
             
             // Property: Unit price must be positive
             prop_assert!(listing.unit_price > Decimal::ZERO, "Unit price is not positive");
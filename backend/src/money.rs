@@ -0,0 +1,60 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Round a monetary amount to the cent using banker's rounding
+///
+/// Ties go to the even cent, so rounding many amounts doesn't drift upwards.
+pub fn round_money(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointNearestEven)
+}
+
+/// Split a total into the cooperative fee and the seller's net amount
+///
+/// The total is rounded first and the fee is rounded on its own; the seller
+/// takes whatever remains, so the two parts always add back up to the total.
+pub fn split_fee(total: Decimal, fee_percentage: Decimal) -> (Decimal, Decimal) {
+    let total = round_money(total);
+    let fee = round_money(total * fee_percentage).clamp(Decimal::ZERO, total.max(Decimal::ZERO));
+    
+    (fee, total - fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    
+    #[test]
+    fn test_round_money_ties_to_even() {
+        assert_eq!(round_money(Decimal::new(4485, 3)), Decimal::new(448, 2));
+        assert_eq!(round_money(Decimal::new(4475, 3)), Decimal::new(448, 2));
+        assert_eq!(round_money(Decimal::new(44851, 4)), Decimal::new(449, 2));
+        assert_eq!(round_money(Decimal::new(5, 3)), Decimal::ZERO);
+        assert_eq!(round_money(Decimal::new(1196, 2)), Decimal::new(1196, 2));
+    }
+    
+    #[test]
+    fn test_split_fee() {
+        // 20.93 * 0.05 = 1.0465
+        assert_eq!(split_fee(Decimal::new(2093, 2), Decimal::new(5, 2)), (Decimal::new(105, 2), Decimal::new(1988, 2)));
+        // An unrounded total is rounded before the fee is taken
+        assert_eq!(split_fee(Decimal::new(4485, 3), Decimal::new(5, 2)), (Decimal::new(22, 2), Decimal::new(426, 2)));
+        assert_eq!(split_fee(Decimal::new(1196, 2), Decimal::ZERO), (Decimal::ZERO, Decimal::new(1196, 2)));
+        assert_eq!(split_fee(Decimal::new(1196, 2), Decimal::ONE), (Decimal::new(1196, 2), Decimal::ZERO));
+    }
+    
+    proptest! {
+        #[test]
+        fn test_split_parts_sum_to_rounded_total(
+            price_cents in 1i64..1_000_000,
+            quantity_thousandths in 1i64..10_000_000,
+            basis_points in 0i64..=10_000,
+        ) {
+            let total = Decimal::new(price_cents, 2) * Decimal::new(quantity_thousandths, 3);
+            let (fee, net) = split_fee(total, Decimal::new(basis_points, 4));
+            
+            prop_assert_eq!(fee + net, round_money(total));
+            prop_assert!(fee >= Decimal::ZERO && net >= Decimal::ZERO);
+            prop_assert!(fee.scale() <= 2 && net.scale() <= 2);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use crate::error::NotificationError;
+use crate::models::{Notification, NotificationType};
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use uuid::Uuid;
+
+/// Maximum length of a notification message in characters
+pub const MAX_MESSAGE_LENGTH: usize = 1000;
+
+/// Number of notifications buffered for slow subscribers before they start missing events
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Channel carrying every newly created notification to live subscribers
+fn events() -> &'static broadcast::Sender<Notification> {
+    static EVENTS: OnceLock<broadcast::Sender<Notification>> = OnceLock::new();
+    EVENTS.get_or_init(|| broadcast::channel(EVENT_CHANNEL_CAPACITY).0)
+}
+
+/// Create an in-app notification for a member
+pub async fn create_notification(
+    pool: &PgPool,
+    recipient_id: Uuid,
+    notification_type: NotificationType,
+    message: &str,
+) -> Result<Notification, NotificationError> {
+    let mut conn = pool.acquire().await?;
+    let notification = insert_notification(&mut conn, recipient_id, notification_type, message).await?;
+    publish(&notification);
+    
+    Ok(notification)
+}
+
+/// Store a notification on an open connection without delivering it to live subscribers
+///
+/// The caller should [`publish`] it once its transaction has committed, so
+/// subscribers never see a notification that was rolled back.
+pub(crate) async fn insert_notification(
+    conn: &mut PgConnection,
+    recipient_id: Uuid,
+    notification_type: NotificationType,
+    message: &str,
+) -> Result<Notification, NotificationError> {
+    validate_message(message)?;
+    
+    let now = Utc::now();
+    let notification = sqlx::query_as::<_, Notification>(
+        "INSERT INTO notifications (id, recipient_id, notification_type, message, sent_at, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, recipient_id, notification_type, message, sent_at, created_at, read_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(recipient_id)
+    .bind(notification_type.to_string())
+    .bind(message.trim())
+    .bind(now)
+    .bind(now)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db) if db.is_foreign_key_violation() => NotificationError::RecipientNotFound,
+        e => NotificationError::Database(e),
+    })?;
+    
+    Ok(notification)
+}
+
+/// Deliver a stored notification to live subscribers
+pub(crate) fn publish(notification: &Notification) {
+    // Sending only fails when nobody is subscribed
+    let _ = events().send(notification.clone());
+}
+
+/// Get a member's notifications, most recent first
+pub async fn get_notifications(
+    pool: &PgPool,
+    recipient_id: Uuid,
+) -> Result<Vec<Notification>, NotificationError> {
+    let notifications = sqlx::query_as::<_, Notification>(
+        "SELECT id, recipient_id, notification_type, message, sent_at, created_at, read_at
+         FROM notifications
+         WHERE recipient_id = $1
+         ORDER BY created_at DESC, id"
+    )
+    .bind(recipient_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(notifications)
+}
+
+/// Get a member's notifications created after `last_id`, oldest first
+///
+/// Returns nothing if `last_id` is not one of the member's notifications.
+pub async fn get_notifications_since(
+    pool: &PgPool,
+    recipient_id: Uuid,
+    last_id: Uuid,
+) -> Result<Vec<Notification>, NotificationError> {
+    let notifications = sqlx::query_as::<_, Notification>(
+        "SELECT id, recipient_id, notification_type, message, sent_at, created_at, read_at
+         FROM notifications
+         WHERE recipient_id = $1
+           AND (created_at, id) > (
+               SELECT created_at, id FROM notifications WHERE id = $2 AND recipient_id = $1
+           )
+         ORDER BY created_at ASC, id"
+    )
+    .bind(recipient_id)
+    .bind(last_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(notifications)
+}
+
+/// Stream a member's notifications as they are created
+///
+/// When `last_event_id` is given, notifications created after it are replayed
+/// first so a reconnecting client does not miss any.
+pub async fn subscribe(
+    pool: &PgPool,
+    recipient_id: Uuid,
+    last_event_id: Option<Uuid>,
+) -> Result<impl Stream<Item = Notification>, NotificationError> {
+    // Subscribe before reading the backlog so nothing created in between is lost
+    let live = BroadcastStream::new(events().subscribe());
+    
+    let missed = match last_event_id {
+        Some(last_id) => get_notifications_since(pool, recipient_id, last_id).await?,
+        None => Vec::new(),
+    };
+    let replayed: HashSet<Uuid> = missed.iter().map(|n| n.id).collect();
+    
+    // Lagged receivers skip the dropped events; clients can reconnect to replay them
+    let live = live.filter_map(move |event| match event {
+        Ok(notification) if notification.recipient_id == recipient_id && !replayed.contains(&notification.id) => {
+            Some(notification)
+        }
+        _ => None,
+    });
+    
+    Ok(tokio_stream::iter(missed).chain(live))
+}
+
+/// Mark a notification as read; only its recipient may do so
+pub async fn mark_read(
+    pool: &PgPool,
+    notification_id: Uuid,
+    recipient_id: Uuid,
+) -> Result<Notification, NotificationError> {
+    // Keep the first read time if the notification was already read
+    let notification = sqlx::query_as::<_, Notification>(
+        "UPDATE notifications SET read_at = COALESCE(read_at, $1)
+         WHERE id = $2 AND recipient_id = $3
+         RETURNING id, recipient_id, notification_type, message, sent_at, created_at, read_at"
+    )
+    .bind(Utc::now())
+    .bind(notification_id)
+    .bind(recipient_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(NotificationError::NotFound)?;
+    
+    Ok(notification)
+}
+
+/// Validate a notification message
+pub fn validate_message(message: &str) -> Result<(), NotificationError> {
+    if message.trim().is_empty() {
+        return Err(NotificationError::InvalidData("Notification message cannot be empty".to_string()));
+    }
+    
+    if message.chars().count() > MAX_MESSAGE_LENGTH {
+        return Err(NotificationError::InvalidData(format!(
+            "Notification message cannot exceed {} characters",
+            MAX_MESSAGE_LENGTH
+        )));
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::insert_test_member;
+    
+    #[test]
+    fn test_validate_message() {
+        assert!(validate_message("Your order has shipped").is_ok());
+        assert!(matches!(validate_message(""), Err(NotificationError::InvalidData(_))));
+        assert!(matches!(validate_message("   "), Err(NotificationError::InvalidData(_))));
+        assert!(matches!(
+            validate_message(&"x".repeat(MAX_MESSAGE_LENGTH + 1)),
+            Err(NotificationError::InvalidData(_))
+        ));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_and_list_notifications(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        
+        let first = create_notification(&pool, member, NotificationType::OrderPlaced, "New order received")
+            .await
+            .unwrap();
+        let second = create_notification(&pool, member, NotificationType::NewProposal, "A new proposal is open")
+            .await
+            .unwrap();
+        
+        assert_eq!(first.notification_type, "OrderPlaced");
+        assert!(first.sent_at.is_some());
+        assert!(first.read_at.is_none());
+        
+        let notifications = get_notifications(&pool, member).await.unwrap();
+        let ids: Vec<Uuid> = notifications.iter().map(|n| n.id).collect();
+        assert_eq!(ids, vec![second.id, first.id]);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_notification_validation(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        
+        let result = create_notification(&pool, member, NotificationType::OrderPlaced, "  ").await;
+        assert!(matches!(result, Err(NotificationError::InvalidData(_))));
+        
+        let result = create_notification(&pool, Uuid::new_v4(), NotificationType::OrderPlaced, "Hello").await;
+        assert!(matches!(result, Err(NotificationError::RecipientNotFound)));
+        
+        assert!(get_notifications(&pool, member).await.unwrap().is_empty());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_mark_read_only_by_owner(pool: PgPool) {
+        let owner = insert_test_member(&pool).await;
+        let other = insert_test_member(&pool).await;
+        let notification = create_notification(&pool, owner, NotificationType::VotingEnded, "Voting has ended")
+            .await
+            .unwrap();
+        
+        let result = mark_read(&pool, notification.id, other).await;
+        assert!(matches!(result, Err(NotificationError::NotFound)));
+        assert!(get_notifications(&pool, owner).await.unwrap()[0].read_at.is_none());
+        
+        let read = mark_read(&pool, notification.id, owner).await.unwrap();
+        assert!(read.read_at.is_some());
+        
+        // Marking again keeps the original read time
+        let again = mark_read(&pool, notification.id, owner).await.unwrap();
+        assert_eq!(again.read_at, read.read_at);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_subscribe_receives_order_notification(pool: PgPool) {
+        use crate::orders::{self, CreateOrderData};
+        use crate::test_utils::{insert_test_listing, test_config};
+        use rust_decimal::Decimal;
+        use std::time::Duration;
+        
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let stream = subscribe(&pool, seller, None).await.unwrap();
+        tokio::pin!(stream);
+        
+        orders::create_order(
+            &pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(2, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        
+        let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("no notification received")
+            .unwrap();
+        
+        assert_eq!(event.recipient_id, seller);
+        assert_eq!(event.notification_type, "OrderPlaced");
+        assert!(event.message.contains("Organic Tomatoes"));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_subscribe_replays_after_last_event_id(pool: PgPool) {
+        use std::time::Duration;
+        
+        let member = insert_test_member(&pool).await;
+        let first = create_notification(&pool, member, NotificationType::NewProposal, "First").await.unwrap();
+        let second = create_notification(&pool, member, NotificationType::NewProposal, "Second").await.unwrap();
+        
+        let stream = subscribe(&pool, member, Some(first.id)).await.unwrap();
+        tokio::pin!(stream);
+        let third = create_notification(&pool, member, NotificationType::VotingEnded, "Third").await.unwrap();
+        
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .expect("no notification received")
+                .unwrap();
+            received.push(event.id);
+        }
+        
+        assert_eq!(received, vec![second.id, third.id]);
+        
+        // An unknown event ID replays nothing
+        assert!(get_notifications_since(&pool, member, Uuid::new_v4()).await.unwrap().is_empty());
+    }
+}
@@ -1,31 +1,111 @@
-use crate::error::OrderError;
-use crate::models::{Order, OrderStatus};
+use crate::error::{validation_result, OrderError, TransactionError, ValidationError};
+use crate::models::{
+    EscrowStatus, Notification, NotificationType, Order, OrderMessage, OrderStatus, OrderStatusChange, OrderWithListing,
+    Transaction,
+};
+use crate::fees;
 use crate::listings;
-use chrono::Utc;
+use crate::money;
+use crate::notifications;
+use crate::transactions;
+use crate::units::UnitOfMeasure;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use sqlx::PgPool;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
+/// Largest order total the `orders.total_amount` column (DECIMAL(10,2)) can hold
+pub const MAX_ORDER_TOTAL: Decimal = Decimal::from_parts(1_410_065_407, 2, 0, false, 2); // 99,999,999.99
+
+/// Longest escrow order ID the `orders.escrow_order_id` column can hold
+pub const MAX_ESCROW_ORDER_ID_LENGTH: usize = 128;
+
+/// Longest note a seller can leave when accepting or rejecting an order
+pub const MAX_SELLER_NOTE_LENGTH: usize = 500;
+
+/// Maximum length of an order message, in characters
+pub const MAX_ORDER_MESSAGE_LENGTH: usize = 2000;
+
+/// Orders per page when no limit is given
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+
+/// Most orders returned in a single page
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Position in an order list, just after the order it was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl OrderCursor {
+    /// Cursor continuing after `order`
+    pub fn after(order: &Order) -> Self {
+        Self {
+            created_at: order.created_at,
+            id: order.id,
+        }
+    }
+    
+    /// Encode as an opaque, URL-safe token
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", self.created_at.timestamp_micros(), self.id))
+    }
+    
+    /// Decode a token produced by [`OrderCursor::encode`]
+    pub fn decode(token: &str) -> Result<Self, OrderError> {
+        let invalid = || OrderError::InvalidData("Invalid pagination cursor".to_string());
+        
+        let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (micros, id) = text.split_once(':').ok_or_else(invalid)?;
+        
+        Ok(Self {
+            created_at: micros
+                .parse()
+                .ok()
+                .and_then(DateTime::from_timestamp_micros)
+                .ok_or_else(invalid)?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// A page of orders and the cursor for the next one
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderPage {
+    pub orders: Vec<Order>,
+    /// `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
 /// Data for creating a new order
 #[derive(Debug, Clone)]
 pub struct CreateOrderData {
-    pub product_listing_id: Uuid,
+    pub listing_id: Uuid,
     pub quantity: Decimal,
 }
 
 /// Create a new order
+///
+/// `max_quantity` caps how many units a single order may request. An order
+/// identical to one of the buyer's pending orders from the last
+/// `duplicate_window_secs` seconds is taken for an accidental resubmit and
+/// rejected; 0 turns that check off.
 pub async fn create_order(
     pool: &PgPool,
     buyer_id: Uuid,
     data: CreateOrderData,
+    max_quantity: Decimal,
+    duplicate_window_secs: u64,
 ) -> Result<Order, OrderError> {
-    // Validate quantity
-    if data.quantity <= Decimal::ZERO {
-        return Err(OrderError::InvalidData("Order quantity must be positive".to_string()));
-    }
+    validate_order_data(&data, max_quantity)?;
     
     // Get the product listing to validate availability and calculate total
-    let listing = listings::get_listing(pool, data.product_listing_id)
+    let listing = listings::get_listing(pool, data.listing_id, false)
         .await
         .map_err(|_| OrderError::ProductUnavailable)?;
     
@@ -34,13 +114,35 @@ pub async fn create_order(
         return Err(OrderError::ProductUnavailable);
     }
     
+    // Sellers can't buy from themselves to inflate their own sales or reputation
+    if listing.member_id == buyer_id {
+        return Err(OrderError::SelfOrder);
+    }
+    
+    let discrete = listing
+        .unit_of_measure
+        .parse::<UnitOfMeasure>()
+        .is_ok_and(UnitOfMeasure::is_discrete);
+    if discrete && !data.quantity.fract().is_zero() {
+        return Err(OrderError::InvalidData(format!(
+            "Quantity must be a whole number when sold by {}",
+            listing.unit_of_measure
+        )));
+    }
+    
     // Check if there's sufficient quantity
-    if listing.quantity < data.quantity {
+    if listing.quantity_available < data.quantity {
         return Err(OrderError::InsufficientQuantity);
     }
     
+    if duplicate_window_secs > 0 && has_recent_duplicate(pool, buyer_id, &data, duplicate_window_secs).await? {
+        return Err(OrderError::DuplicateOrder);
+    }
+    
     // Calculate total amount
-    let total_amount = listing.unit_price * data.quantity;
+    let total_amount = calculate_total_amount(listing.unit_price, data.quantity)
+        .filter(|total| *total <= MAX_ORDER_TOTAL)
+        .ok_or_else(|| OrderError::InvalidData("Order total is too large".to_string()))?;
     
     // Create the order
     let order_id = Uuid::new_v4();
@@ -51,30 +153,87 @@ pub async fn create_order(
     let order = sqlx::query_as::<_, Order>(
         "INSERT INTO orders (id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, created_at)
          VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-         RETURNING id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, created_at"
+         RETURNING id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at"
     )
     .bind(order_id)
     .bind(buyer_id)
     .bind(seller_id)
-    .bind(data.product_listing_id)
+    .bind(data.listing_id)
     .bind(data.quantity)
     .bind(total_amount)
     .bind(&status)
     .bind(now)
     .fetch_one(pool)
-    .await
-    .map_err(|e| OrderError::InvalidData(format!("Failed to create order: {}", e)))?;
+    .await?;
+    
+    // The order stands even if the seller can't be notified
+    let message = format!("New order for {} {} of {}", data.quantity, listing.unit_of_measure, listing.name);
+    if let Err(e) = notifications::create_notification(pool, seller_id, NotificationType::OrderPlaced, &message).await {
+        tracing::warn!("Failed to notify seller of order {}: {}", order_id, e);
+    }
     
     Ok(order)
 }
 
+/// Whether the buyer placed an identical order that is still pending within the window
+///
+/// A soft guard against double submits: two requests racing each other can
+/// both pass it.
+async fn has_recent_duplicate(
+    pool: &PgPool,
+    buyer_id: Uuid,
+    data: &CreateOrderData,
+    window_secs: u64,
+) -> Result<bool, OrderError> {
+    let since = Utc::now() - chrono::Duration::seconds(window_secs.min(i64::MAX as u64) as i64);
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (
+             SELECT 1 FROM orders
+             WHERE buyer_id = $1 AND product_listing_id = $2 AND quantity = $3
+               AND status = $4 AND created_at > $5
+         )"
+    )
+    .bind(buyer_id)
+    .bind(data.listing_id)
+    .bind(data.quantity)
+    .bind(OrderStatus::Pending.to_string())
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(exists)
+}
+
+/// Validate the fields of a new order, reporting every invalid one
+pub fn validate_order_data(data: &CreateOrderData, max_quantity: Decimal) -> Result<(), OrderError> {
+    let mut errors = Vec::new();
+    
+    if data.quantity <= Decimal::ZERO {
+        errors.push(ValidationError::new("quantity", "Order quantity must be positive"));
+    } else if data.quantity > max_quantity {
+        errors.push(ValidationError::new(
+            "quantity",
+            format!("Order quantity cannot exceed {}", max_quantity),
+        ));
+    }
+    
+    validation_result(errors).map_err(OrderError::Validation)
+}
+
+/// Calculate the total price for an order quantity, rounded to the cent
+///
+/// Returns `None` if the product overflows `Decimal`.
+pub fn calculate_total_amount(unit_price: Decimal, quantity: Decimal) -> Option<Decimal> {
+    unit_price.checked_mul(quantity).map(money::round_money)
+}
+
 /// Get an order by ID
 pub async fn get_order(
     pool: &PgPool,
     order_id: Uuid,
 ) -> Result<Order, OrderError> {
     let order = sqlx::query_as::<_, Order>(
-        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, created_at
+        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at
          FROM orders
          WHERE id = $1"
     )
@@ -87,69 +246,332 @@ pub async fn get_order(
     Ok(order)
 }
 
-/// Get all orders for a buyer
+/// Get an order by ID along with its listing's name and unit
+pub async fn get_order_with_listing(
+    pool: &PgPool,
+    order_id: Uuid,
+) -> Result<OrderWithListing, OrderError> {
+    let order = sqlx::query_as::<_, OrderWithListing>(
+        "SELECT o.id, o.buyer_id, o.seller_id, o.product_listing_id, o.quantity, o.total_amount, o.status, o.escrow_order_id, o.seller_note, o.created_at,
+                l.name AS product_name, l.unit_of_measure
+         FROM orders o
+         LEFT JOIN product_listings l ON l.id = o.product_listing_id
+         WHERE o.id = $1"
+    )
+    .bind(order_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(OrderError::NotFound)?;
+    
+    Ok(order)
+}
+
+/// Get a page of a buyer's orders, newest first, starting after `cursor`
 pub async fn get_orders_by_buyer(
     pool: &PgPool,
     buyer_id: Uuid,
+    cursor: Option<OrderCursor>,
+    limit: Option<i64>,
+) -> Result<OrderPage, OrderError> {
+    get_order_page(pool, "buyer_id", buyer_id, cursor, limit).await
+}
+
+/// Get a page of a seller's orders, newest first, starting after `cursor`
+pub async fn get_orders_by_seller(
+    pool: &PgPool,
+    seller_id: Uuid,
+    cursor: Option<OrderCursor>,
+    limit: Option<i64>,
+) -> Result<OrderPage, OrderError> {
+    get_order_page(pool, "seller_id", seller_id, cursor, limit).await
+}
+
+/// Keyset pagination over `(created_at, id)`, so rows inserted while paging
+/// can't shift later pages the way an offset would
+async fn get_order_page(
+    pool: &PgPool,
+    member_column: &str,
+    member_id: Uuid,
+    cursor: Option<OrderCursor>,
+    limit: Option<i64>,
+) -> Result<OrderPage, OrderError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let query = format!(
+        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at
+         FROM orders
+         WHERE {column} = $1
+           AND ($2::TIMESTAMPTZ IS NULL OR (created_at, id) < ($2, $3))
+         ORDER BY created_at DESC, id DESC
+         LIMIT $4",
+        column = member_column
+    );
+    
+    // Fetch one extra row to learn whether another page follows
+    let mut orders = sqlx::query_as::<_, Order>(&query)
+        .bind(member_id)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id))
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+    
+    let next_cursor = if orders.len() as i64 > limit {
+        orders.truncate(limit as usize);
+        orders.last().map(|last| OrderCursor::after(last).encode())
+    } else {
+        None
+    };
+    
+    Ok(OrderPage { orders, next_cursor })
+}
+
+/// Get a seller's orders in the given status, newest first
+pub async fn get_orders_by_seller_and_status(
+    pool: &PgPool,
+    seller_id: Uuid,
+    status: OrderStatus,
 ) -> Result<Vec<Order>, OrderError> {
     let orders = sqlx::query_as::<_, Order>(
-        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, created_at
+        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at
          FROM orders
-         WHERE buyer_id = $1
-         ORDER BY created_at DESC"
+         WHERE seller_id = $1 AND status = $2
+         ORDER BY created_at DESC, id"
     )
-    .bind(buyer_id)
+    .bind(seller_id)
+    .bind(status.to_string())
     .fetch_all(pool)
-    .await
-    .map_err(|e| OrderError::InvalidData(format!("Failed to fetch orders: {}", e)))?;
+    .await?;
     
     Ok(orders)
 }
 
-/// Get all orders for a seller
-pub async fn get_orders_by_seller(
+/// Get a buyer's orders in the given status, newest first
+pub async fn get_orders_by_buyer_and_status(
     pool: &PgPool,
-    seller_id: Uuid,
+    buyer_id: Uuid,
+    status: OrderStatus,
 ) -> Result<Vec<Order>, OrderError> {
     let orders = sqlx::query_as::<_, Order>(
-        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, created_at
+        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at
          FROM orders
-         WHERE seller_id = $1
-         ORDER BY created_at DESC"
+         WHERE buyer_id = $1 AND status = $2
+         ORDER BY created_at DESC, id"
     )
-    .bind(seller_id)
+    .bind(buyer_id)
+    .bind(status.to_string())
     .fetch_all(pool)
-    .await
-    .map_err(|e| OrderError::InvalidData(format!("Failed to fetch orders: {}", e)))?;
+    .await?;
+    
+    Ok(orders)
+}
+
+/// Get orders in the given status where the member is the buyer or the seller, newest first
+pub async fn get_orders_for_member_and_status(
+    pool: &PgPool,
+    member_id: Uuid,
+    status: OrderStatus,
+) -> Result<Vec<OrderWithListing>, OrderError> {
+    let orders = sqlx::query_as::<_, OrderWithListing>(
+        "SELECT o.id, o.buyer_id, o.seller_id, o.product_listing_id, o.quantity, o.total_amount, o.status, o.escrow_order_id, o.seller_note, o.created_at,
+                l.name AS product_name, l.unit_of_measure
+         FROM orders o
+         LEFT JOIN product_listings l ON l.id = o.product_listing_id
+         WHERE (o.buyer_id = $1 OR o.seller_id = $1) AND o.status = $2
+         ORDER BY o.created_at DESC, o.id"
+    )
+    .bind(member_id)
+    .bind(status.to_string())
+    .fetch_all(pool)
+    .await?;
     
     Ok(orders)
 }
 
-/// Update order status
+/// Get all orders where the member is the buyer or the seller, newest first
+pub async fn get_orders_for_member(
+    pool: &PgPool,
+    member_id: Uuid,
+) -> Result<Vec<OrderWithListing>, OrderError> {
+    let orders = sqlx::query_as::<_, OrderWithListing>(
+        "SELECT o.id, o.buyer_id, o.seller_id, o.product_listing_id, o.quantity, o.total_amount, o.status, o.escrow_order_id, o.seller_note, o.created_at,
+                l.name AS product_name, l.unit_of_measure
+         FROM orders o
+         LEFT JOIN product_listings l ON l.id = o.product_listing_id
+         WHERE o.buyer_id = $1 OR o.seller_id = $1
+         ORDER BY o.created_at DESC, o.id"
+    )
+    .bind(member_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(orders)
+}
+
+/// Update order status and record the change in the status history
 async fn update_order_status(
     pool: &PgPool,
     order_id: Uuid,
+    from_status: OrderStatus,
+    new_status: OrderStatus,
+    actor_id: Uuid,
+) -> Result<Order, OrderError> {
+    let mut tx = pool.begin().await?;
+    let order = record_status_change(&mut tx, order_id, from_status, new_status, actor_id).await?;
+    tx.commit().await?;
+    
+    Ok(order)
+}
+
+/// Update an order's status and, if one is given, replace the seller's note
+async fn update_order_status_with_note(
+    pool: &PgPool,
+    order_id: Uuid,
+    from_status: OrderStatus,
+    new_status: OrderStatus,
+    actor_id: Uuid,
+    note: Option<&str>,
+) -> Result<Order, OrderError> {
+    let mut tx = pool.begin().await?;
+    let mut order = record_status_change(&mut tx, order_id, from_status, new_status, actor_id).await?;
+    
+    if let Some(note) = note {
+        order = sqlx::query_as::<_, Order>(
+            "UPDATE orders SET seller_note = $1 WHERE id = $2
+             RETURNING id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at"
+        )
+        .bind(note)
+        .bind(order_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    }
+    
+    tx.commit().await?;
+    
+    Ok(order)
+}
+
+/// Set an order's status and add the change to its history on an open connection
+///
+/// Callers are expected to run this inside a transaction so the two writes land together.
+/// The update only applies while the order is still in `from_status`, so a concurrent
+/// change made since the caller read the order fails instead of being overwritten.
+pub(crate) async fn record_status_change(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    from_status: OrderStatus,
     new_status: OrderStatus,
+    actor_id: Uuid,
 ) -> Result<Order, OrderError> {
     let order = sqlx::query_as::<_, Order>(
-        "UPDATE orders SET status = $1 WHERE id = $2
-         RETURNING id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, created_at"
+        "UPDATE orders SET status = $1 WHERE id = $2 AND status = $3
+         RETURNING id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at"
     )
     .bind(new_status.to_string())
     .bind(order_id)
-    .fetch_one(pool)
-    .await
-    .map_err(|_| OrderError::NotFound)?;
+    .bind(from_status.to_string())
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| OrderError::InvalidStatusTransition(
+        format!("Order is no longer {:?}", from_status)
+    ))?;
+    
+    sqlx::query(
+        "INSERT INTO order_status_history (id, order_id, from_status, to_status, actor_id, changed_at)
+         VALUES ($1, $2, $3, $4, $5, $6)"
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_id)
+    .bind(from_status.to_string())
+    .bind(new_status.to_string())
+    .bind(actor_id)
+    .bind(Utc::now())
+    .execute(&mut *conn)
+    .await?;
     
     Ok(order)
 }
 
-/// Accept an order (seller action)
+/// Get the status history of an order, oldest first
+pub async fn get_order_history(
+    pool: &PgPool,
+    order_id: Uuid,
+) -> Result<Vec<OrderStatusChange>, OrderError> {
+    let history = sqlx::query_as::<_, OrderStatusChange>(
+        "SELECT id, order_id, from_status, to_status, actor_id, changed_at
+         FROM order_status_history
+         WHERE order_id = $1
+         ORDER BY changed_at ASC"
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(history)
+}
+
+/// Post a message to an order's thread (buyer or seller only)
+pub async fn send_order_message(
+    pool: &PgPool,
+    order_id: Uuid,
+    sender_id: Uuid,
+    body: &str,
+) -> Result<OrderMessage, OrderError> {
+    validate_message_body(body)?;
+    
+    let order = get_order(pool, order_id).await?;
+    if order.buyer_id != sender_id && order.seller_id != sender_id {
+        return Err(OrderError::Unauthorized);
+    }
+    
+    let message = sqlx::query_as::<_, OrderMessage>(
+        "INSERT INTO order_messages (id, order_id, sender_id, body, created_at)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING id, order_id, sender_id, body, created_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_id)
+    .bind(sender_id)
+    .bind(body.trim())
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(message)
+}
+
+/// Get an order's messages, oldest first (buyer or seller only)
+pub async fn get_order_messages(
+    pool: &PgPool,
+    order_id: Uuid,
+    requester_id: Uuid,
+) -> Result<Vec<OrderMessage>, OrderError> {
+    let order = get_order(pool, order_id).await?;
+    if order.buyer_id != requester_id && order.seller_id != requester_id {
+        return Err(OrderError::Unauthorized);
+    }
+    
+    let messages = sqlx::query_as::<_, OrderMessage>(
+        "SELECT id, order_id, sender_id, body, created_at
+         FROM order_messages
+         WHERE order_id = $1
+         ORDER BY created_at ASC, id"
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(messages)
+}
+
+/// Accept an order (seller action), optionally leaving a note for the buyer
 pub async fn accept_order(
     pool: &PgPool,
     order_id: Uuid,
     seller_id: Uuid,
+    note: Option<&str>,
 ) -> Result<Order, OrderError> {
+    let note = validate_seller_note(note)?;
+    
     // Get the order and verify it belongs to the seller
     let order = get_order(pool, order_id).await?;
     
@@ -168,15 +590,18 @@ pub async fn accept_order(
     }
     
     // Update status to Accepted
-    update_order_status(pool, order_id, OrderStatus::Accepted).await
+    update_order_status_with_note(pool, order_id, current_status, OrderStatus::Accepted, seller_id, note).await
 }
 
-/// Reject an order (seller action)
+/// Reject an order (seller action), optionally leaving a note for the buyer
 pub async fn reject_order(
     pool: &PgPool,
     order_id: Uuid,
     seller_id: Uuid,
+    note: Option<&str>,
 ) -> Result<Order, OrderError> {
+    let note = validate_seller_note(note)?;
+    
     // Get the order and verify it belongs to the seller
     let order = get_order(pool, order_id).await?;
     
@@ -195,17 +620,99 @@ pub async fn reject_order(
     }
     
     // Update status to Rejected
-    update_order_status(pool, order_id, OrderStatus::Rejected).await
+    update_order_status_with_note(pool, order_id, current_status, OrderStatus::Rejected, seller_id, note).await
 }
 
-/// Complete an order (after successful transaction)
-pub async fn complete_order(
+/// Mark an order as shipped (seller action)
+pub async fn ship_order(
     pool: &PgPool,
     order_id: Uuid,
+    seller_id: Uuid,
 ) -> Result<Order, OrderError> {
-    // Get the order
+    // Get the order and verify it belongs to the seller
     let order = get_order(pool, order_id).await?;
     
+    if order.seller_id != seller_id {
+        return Err(OrderError::Unauthorized);
+    }
+    
+    // Validate status transition
+    let current_status = order.status.parse::<OrderStatus>()
+        .map_err(|e| OrderError::InvalidData(format!("Invalid order status: {}", e)))?;
+    
+    if !is_valid_status_transition(&current_status, &OrderStatus::Shipped) {
+        return Err(OrderError::InvalidStatusTransition(
+            format!("Cannot transition from {:?} to Shipped", current_status)
+        ));
+    }
+    
+    // Update status to Shipped
+    update_order_status(pool, order_id, current_status, OrderStatus::Shipped, seller_id).await
+}
+
+/// Mark an order as delivered (buyer action)
+pub async fn mark_delivered(
+    pool: &PgPool,
+    order_id: Uuid,
+    buyer_id: Uuid,
+) -> Result<Order, OrderError> {
+    // Get the order and verify it belongs to the buyer
+    let order = get_order(pool, order_id).await?;
+    
+    if order.buyer_id != buyer_id {
+        return Err(OrderError::Unauthorized);
+    }
+    
+    // Validate status transition
+    let current_status = order.status.parse::<OrderStatus>()
+        .map_err(|e| OrderError::InvalidData(format!("Invalid order status: {}", e)))?;
+    
+    if !is_valid_status_transition(&current_status, &OrderStatus::Delivered) {
+        return Err(OrderError::InvalidStatusTransition(
+            format!("Cannot transition from {:?} to Delivered", current_status)
+        ));
+    }
+    
+    // Update status to Delivered
+    update_order_status(pool, order_id, current_status, OrderStatus::Delivered, buyer_id).await
+}
+
+/// What completing an order wrote, returned so the caller can act once it commits
+#[derive(Debug, Clone)]
+pub struct CompletedOrder {
+    pub order: Order,
+    pub transaction: Transaction,
+    pub notifications: Vec<Notification>,
+}
+
+/// Complete an order, record a completed transaction for its total and notify both parties
+///
+/// Only the buyer can complete an order, as with `complete_order` on chain.
+/// Everything is written through the caller's database transaction, so if any
+/// step fails and the transaction is dropped, the order is left as it was.
+/// Publish the returned notifications with [`notifications::publish`] after committing.
+pub async fn complete_order(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    buyer_id: Uuid,
+    default_fee_percentage: Decimal,
+) -> Result<CompletedOrder, OrderError> {
+    // Lock the order so a concurrent completion waits for this one
+    let order = sqlx::query_as::<_, Order>(
+        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at
+         FROM orders
+         WHERE id = $1
+         FOR UPDATE"
+    )
+    .bind(order_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(OrderError::NotFound)?;
+    
+    if order.buyer_id != buyer_id {
+        return Err(OrderError::Unauthorized);
+    }
+    
     // Validate status transition
     let current_status = order.status.parse::<OrderStatus>()
         .map_err(|e| OrderError::InvalidData(format!("Invalid order status: {}", e)))?;
@@ -217,15 +724,63 @@ pub async fn complete_order(
     }
     
     // Update status to Completed
-    update_order_status(pool, order_id, OrderStatus::Completed).await
+    let order = record_status_change(conn, order_id, current_status, OrderStatus::Completed, buyer_id).await?;
+    
+    let transaction = transactions::create_transaction(conn, order_id, default_fee_percentage).await?;
+    let transaction = transactions::mark_completed(conn, transaction.id).await?;
+    
+    let mut sent = Vec::with_capacity(2);
+    for (recipient_id, message) in [
+        (order.buyer_id, format!("Order {} is complete", order_id)),
+        (
+            order.seller_id,
+            format!(
+                "Order {} is complete: {} to be paid out after a cooperative fee of {}",
+                order_id,
+                transaction.amount - transaction.cooperative_fee,
+                transaction.cooperative_fee
+            ),
+        ),
+    ] {
+        let notification = notifications::insert_notification(
+            conn,
+            recipient_id,
+            NotificationType::OrderStatusChanged,
+            &message,
+        )
+        .await?;
+        sent.push(notification);
+    }
+    
+    Ok(CompletedOrder {
+        order,
+        transaction,
+        notifications: sent,
+    })
+}
+
+/// What is left to do after an order is cancelled
+#[derive(Debug, Clone)]
+pub enum CancelOutcome {
+    /// Nothing was locked on chain, so cancelling the order is the end of it
+    Cancelled(Order),
+    /// The order is cancelled but its funds stay in escrow until `refund_order`
+    /// is called on chain for `escrow_order_id`
+    RefundRequired {
+        order: Order,
+        escrow_order_id: String,
+    },
 }
 
 /// Cancel an order (buyer action)
+///
+/// Orders linked to a NEAR escrow come back as [`CancelOutcome::RefundRequired`]
+/// so the caller can have the escrow refunded.
 pub async fn cancel_order(
     pool: &PgPool,
     order_id: Uuid,
     buyer_id: Uuid,
-) -> Result<Order, OrderError> {
+) -> Result<CancelOutcome, OrderError> {
     // Get the order and verify it belongs to the buyer
     let order = get_order(pool, order_id).await?;
     
@@ -244,7 +799,225 @@ pub async fn cancel_order(
     }
     
     // Update status to Cancelled
-    update_order_status(pool, order_id, OrderStatus::Cancelled).await
+    let order = update_order_status(pool, order_id, current_status, OrderStatus::Cancelled, buyer_id).await?;
+    
+    Ok(match order.escrow_order_id.clone() {
+        Some(escrow_order_id) => CancelOutcome::RefundRequired { order, escrow_order_id },
+        None => CancelOutcome::Cancelled(order),
+    })
+}
+
+/// Amount a buyer must lock in escrow for an order, with the fee breakdown
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrderQuote {
+    pub order_id: Uuid,
+    /// What the buyer attaches to the escrow, equal to the order total
+    pub amount_due: Decimal,
+    /// Cooperative fee withheld from the seller when the order completes
+    pub cooperative_fee: Decimal,
+    /// What the seller receives once the fee is withheld
+    pub seller_amount: Decimal,
+    pub fee_percentage: Decimal,
+}
+
+/// Quote the escrow amount for an order (buyer action)
+///
+/// The fee uses the same category override and rounding as the transaction
+/// recorded on completion, so the quote matches what is eventually settled.
+pub async fn quote_order(
+    pool: &PgPool,
+    order_id: Uuid,
+    buyer_id: Uuid,
+    default_fee_percentage: Decimal,
+) -> Result<OrderQuote, OrderError> {
+    let order = get_order(pool, order_id).await?;
+    
+    if order.buyer_id != buyer_id {
+        return Err(OrderError::Unauthorized);
+    }
+    
+    let mut conn = pool.acquire().await?;
+    let fee_percentage = fees::resolve_listing_fee(&mut conn, order.product_listing_id, default_fee_percentage)
+        .await
+        .map_err(TransactionError::from)?;
+    let (cooperative_fee, seller_amount) = money::split_fee(order.total_amount, fee_percentage);
+    
+    Ok(OrderQuote {
+        order_id,
+        amount_due: order.total_amount,
+        cooperative_fee,
+        seller_amount,
+        fee_percentage,
+    })
+}
+
+/// Record the NEAR escrow order that funds an order (buyer action)
+///
+/// Setting the same ID again is a no-op; once linked, an order can't be moved
+/// to a different escrow order.
+pub async fn set_escrow_order_id(
+    pool: &PgPool,
+    order_id: Uuid,
+    buyer_id: Uuid,
+    escrow_order_id: &str,
+) -> Result<Order, OrderError> {
+    let escrow_order_id = escrow_order_id.trim();
+    validate_escrow_order_id(escrow_order_id)?;
+    
+    let order = get_order(pool, order_id).await?;
+    
+    if order.buyer_id != buyer_id {
+        return Err(OrderError::Unauthorized);
+    }
+    
+    // The condition re-checks the link so a concurrent update can't be overwritten
+    sqlx::query_as::<_, Order>(
+        "UPDATE orders SET escrow_order_id = $1
+         WHERE id = $2 AND (escrow_order_id IS NULL OR escrow_order_id = $1)
+         RETURNING id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at"
+    )
+    .bind(escrow_order_id)
+    .bind(order_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => OrderError::EscrowAlreadyLinked,
+        e => OrderError::Database(e),
+    })?
+    .ok_or(OrderError::EscrowAlreadyLinked)
+}
+
+/// How an order's status disagrees with its escrow on chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum StatusMismatch {
+    /// The escrow has paid out but the order is still open
+    EscrowSettled,
+    /// The order is finished but the escrow still holds the funds
+    OrderSettled,
+    /// Both are settled, but the escrow paid out the other way
+    Conflicting,
+}
+
+/// Comparison of an order's status with its on-chain escrow status
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReconciliationReport {
+    pub order_id: Uuid,
+    pub escrow_order_id: Option<String>,
+    pub order_status: OrderStatus,
+    pub escrow_status: EscrowStatus,
+    /// `None` when the two agree
+    pub mismatch: Option<StatusMismatch>,
+}
+
+/// Compare an order's status with the status its escrow reports on chain
+///
+/// Nothing is changed; a sync job decides what to do about a mismatch.
+pub async fn reconcile_order(
+    pool: &PgPool,
+    order_id: Uuid,
+    chain_status: EscrowStatus,
+) -> Result<ReconciliationReport, OrderError> {
+    let order = get_order(pool, order_id).await?;
+    let order_status = order.status.parse::<OrderStatus>()
+        .map_err(|e| OrderError::InvalidData(format!("Invalid order status: {}", e)))?;
+    
+    Ok(ReconciliationReport {
+        order_id,
+        escrow_order_id: order.escrow_order_id,
+        mismatch: status_mismatch(&order_status, chain_status),
+        order_status,
+        escrow_status: chain_status,
+    })
+}
+
+/// Whether an order status is consistent with an escrow status
+///
+/// Open orders keep their funds locked, possibly under dispute. Completed
+/// orders pay the seller and rejected or cancelled ones refund the buyer,
+/// while an arbitrated escrow may have done either.
+pub fn is_consistent_with_escrow(order_status: &OrderStatus, escrow_status: EscrowStatus) -> bool {
+    match escrow_status {
+        EscrowStatus::Pending | EscrowStatus::Disputed => !is_final_status(order_status),
+        EscrowStatus::Completed => *order_status == OrderStatus::Completed,
+        EscrowStatus::Refunded => matches!(order_status, OrderStatus::Rejected | OrderStatus::Cancelled),
+        EscrowStatus::Resolved => is_final_status(order_status),
+    }
+}
+
+/// Classify how an order status disagrees with an escrow status, if it does
+pub fn status_mismatch(order_status: &OrderStatus, escrow_status: EscrowStatus) -> Option<StatusMismatch> {
+    if is_consistent_with_escrow(order_status, escrow_status) {
+        return None;
+    }
+    
+    match (is_final_status(order_status), escrow_status.is_settled()) {
+        (false, true) => Some(StatusMismatch::EscrowSettled),
+        (true, false) => Some(StatusMismatch::OrderSettled),
+        _ => Some(StatusMismatch::Conflicting),
+    }
+}
+
+/// Whether an order has reached a status it can't leave
+fn is_final_status(status: &OrderStatus) -> bool {
+    matches!(
+        status,
+        OrderStatus::Completed | OrderStatus::Rejected | OrderStatus::Cancelled | OrderStatus::Resolved
+    )
+}
+
+/// Validate a seller's note, returning it trimmed, or `None` if it is blank
+pub fn validate_seller_note(note: Option<&str>) -> Result<Option<&str>, OrderError> {
+    let note = match note.map(str::trim) {
+        Some(note) if !note.is_empty() => note,
+        _ => return Ok(None),
+    };
+    
+    if note.chars().count() > MAX_SELLER_NOTE_LENGTH {
+        return Err(OrderError::InvalidData(format!(
+            "Seller note cannot be longer than {} characters",
+            MAX_SELLER_NOTE_LENGTH
+        )));
+    }
+    
+    Ok(Some(note))
+}
+
+/// Validate an order message before it is stored
+pub fn validate_message_body(body: &str) -> Result<(), OrderError> {
+    let body = body.trim();
+    
+    if body.is_empty() {
+        return Err(OrderError::InvalidData("Message cannot be empty".to_string()));
+    }
+    
+    if body.chars().count() > MAX_ORDER_MESSAGE_LENGTH {
+        return Err(OrderError::InvalidData(format!(
+            "Message cannot be longer than {} characters",
+            MAX_ORDER_MESSAGE_LENGTH
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Validate an on-chain escrow order ID before storing it
+pub fn validate_escrow_order_id(escrow_order_id: &str) -> Result<(), OrderError> {
+    if escrow_order_id.is_empty() {
+        return Err(OrderError::InvalidData("Escrow order ID cannot be empty".to_string()));
+    }
+    
+    if escrow_order_id.len() > MAX_ESCROW_ORDER_ID_LENGTH {
+        return Err(OrderError::InvalidData(format!(
+            "Escrow order ID cannot exceed {} characters",
+            MAX_ESCROW_ORDER_ID_LENGTH
+        )));
+    }
+    
+    if escrow_order_id.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(OrderError::InvalidData("Escrow order ID cannot contain whitespace".to_string()));
+    }
+    
+    Ok(())
 }
 
 /// Validate if a status transition is allowed
@@ -256,13 +1029,26 @@ pub fn is_valid_status_transition(from: &OrderStatus, to: &OrderStatus) -> bool
         (OrderStatus::Pending, OrderStatus::Cancelled) => true,
         
         // From Accepted
+        (OrderStatus::Accepted, OrderStatus::Shipped) => true,
         (OrderStatus::Accepted, OrderStatus::Completed) => true,
         (OrderStatus::Accepted, OrderStatus::Cancelled) => true,
         
+        // From Shipped
+        (OrderStatus::Shipped, OrderStatus::Delivered) => true,
+        
+        // From Delivered
+        (OrderStatus::Delivered, OrderStatus::Completed) => true,
+        
+        // Disputes can be raised while goods are changing hands
+        (OrderStatus::Accepted, OrderStatus::Disputed) => true,
+        (OrderStatus::Shipped, OrderStatus::Disputed) => true,
+        (OrderStatus::Disputed, OrderStatus::Resolved) => true,
+        
         // No transitions from terminal states
         (OrderStatus::Rejected, _) => false,
         (OrderStatus::Completed, _) => false,
         (OrderStatus::Cancelled, _) => false,
+        (OrderStatus::Resolved, _) => false,
         
         // All other transitions are invalid
         _ => false,
@@ -290,7 +1076,7 @@ pub fn can_reject_order(order: &Order) -> bool {
 /// Check if an order can be completed
 pub fn can_complete_order(order: &Order) -> bool {
     if let Ok(status) = order.status.parse::<OrderStatus>() {
-        matches!(status, OrderStatus::Accepted)
+        matches!(status, OrderStatus::Accepted | OrderStatus::Delivered)
     } else {
         false
     }
@@ -311,13 +1097,692 @@ mod tests {
     use super::*;
     use proptest::prelude::*;
     use crate::models::{ProductListing, AvailabilityStatus};
+    use crate::test_utils::{insert_test_listing, insert_test_member, test_config};
     
     // Unit tests
     
+    async fn insert_test_order(
+        pool: &PgPool,
+        buyer_id: Uuid,
+        seller_id: Uuid,
+        listing_id: Uuid,
+        created_at: chrono::DateTime<Utc>,
+    ) -> Uuid {
+        let order_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO orders (id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, created_at)
+             VALUES ($1, $2, $3, $4, 1, 1, 'Pending', $5)"
+        )
+        .bind(order_id)
+        .bind(buyer_id)
+        .bind(seller_id)
+        .bind(listing_id)
+        .bind(created_at)
+        .execute(pool)
+        .await
+        .unwrap();
+        order_id
+    }
+    
+    #[test]
+    fn test_max_order_total_matches_column() {
+        assert_eq!(MAX_ORDER_TOTAL, Decimal::new(9_999_999_999, 2));
+    }
+    
+    #[test]
+    fn test_calculate_total_amount_overflow() {
+        assert_eq!(calculate_total_amount(Decimal::new(299, 2), Decimal::new(4, 0)), Some(Decimal::new(1196, 2)));
+        assert_eq!(calculate_total_amount(Decimal::MAX, Decimal::new(2, 0)), None);
+        // 1.5 kg at 2.99 is 4.485, which rounds to the even cent
+        assert_eq!(calculate_total_amount(Decimal::new(299, 2), Decimal::new(15, 1)), Some(Decimal::new(448, 2)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_order_rejects_oversized_quantities(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let order = |quantity| CreateOrderData { listing_id, quantity };
+        
+        // Above the configured maximum, even with stock to spare
+        let result = create_order(&pool, buyer, order(Decimal::new(51, 0)), Decimal::new(50, 0), 0).await;
+        assert!(matches!(result, Err(OrderError::Validation(_))));
+        assert!(create_order(&pool, buyer, order(Decimal::new(50, 0)), Decimal::new(50, 0), 0).await.is_ok());
+        
+        // A total that overflows the column is rejected rather than failing in the database
+        sqlx::query("UPDATE product_listings SET unit_price = 99999999.99, quantity_available = 99999999.99 WHERE id = $1")
+            .bind(listing_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let result = create_order(&pool, buyer, order(Decimal::new(2, 0)), Decimal::MAX, 0).await;
+        assert!(matches!(result, Err(OrderError::InvalidData(_))));
+        let result = create_order(&pool, buyer, order(Decimal::new(99_999_999, 0)), Decimal::MAX, 0).await;
+        assert!(matches!(result, Err(OrderError::InvalidData(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_quote_order(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let config = test_config();
+        let quantity = Decimal::new(7, 0);
+        let order = create_order(&pool, buyer, CreateOrderData { listing_id, quantity }, config.max_order_quantity, config.duplicate_order_window_secs)
+            .await
+            .unwrap();
+        let unit_price = listings::get_listing(&pool, listing_id, false).await.unwrap().unit_price;
+        
+        let quote = quote_order(&pool, order.id, buyer, config.cooperative_fee_percentage).await.unwrap();
+        
+        // 7 x 2.99 = 20.93, and 5% of that is 1.0465, rounded to 1.05
+        assert_eq!(quote.amount_due, unit_price * quantity);
+        assert_eq!(quote.cooperative_fee, Decimal::new(105, 2));
+        assert_eq!(quote.seller_amount + quote.cooperative_fee, quote.amount_due);
+        assert_eq!(quote.fee_percentage, config.cooperative_fee_percentage);
+        
+        let result = quote_order(&pool, order.id, seller, config.cooperative_fee_percentage).await;
+        assert!(matches!(result, Err(OrderError::Unauthorized)));
+    }
+    
+    #[test]
+    fn test_validate_escrow_order_id() {
+        assert!(validate_escrow_order_id("order-42").is_ok());
+        assert!(matches!(validate_escrow_order_id(""), Err(OrderError::InvalidData(_))));
+        assert!(matches!(validate_escrow_order_id("order 42"), Err(OrderError::InvalidData(_))));
+        assert!(matches!(
+            validate_escrow_order_id(&"x".repeat(MAX_ESCROW_ORDER_ID_LENGTH + 1)),
+            Err(OrderError::InvalidData(_))
+        ));
+    }
+    
+    #[test]
+    fn test_status_mismatch_matching_combinations() {
+        for status in [OrderStatus::Pending, OrderStatus::Accepted, OrderStatus::Shipped, OrderStatus::Delivered] {
+            assert_eq!(status_mismatch(&status, EscrowStatus::Pending), None);
+            assert_eq!(status_mismatch(&status, EscrowStatus::Disputed), None);
+        }
+        assert_eq!(status_mismatch(&OrderStatus::Completed, EscrowStatus::Completed), None);
+        assert_eq!(status_mismatch(&OrderStatus::Cancelled, EscrowStatus::Refunded), None);
+        assert_eq!(status_mismatch(&OrderStatus::Rejected, EscrowStatus::Refunded), None);
+        // Arbitration may settle either way
+        assert_eq!(status_mismatch(&OrderStatus::Completed, EscrowStatus::Resolved), None);
+        assert_eq!(status_mismatch(&OrderStatus::Cancelled, EscrowStatus::Resolved), None);
+    }
+    
+    #[test]
+    fn test_status_mismatch_mismatching_combinations() {
+        assert_eq!(
+            status_mismatch(&OrderStatus::Accepted, EscrowStatus::Completed),
+            Some(StatusMismatch::EscrowSettled)
+        );
+        assert_eq!(
+            status_mismatch(&OrderStatus::Delivered, EscrowStatus::Refunded),
+            Some(StatusMismatch::EscrowSettled)
+        );
+        assert_eq!(
+            status_mismatch(&OrderStatus::Shipped, EscrowStatus::Resolved),
+            Some(StatusMismatch::EscrowSettled)
+        );
+        assert_eq!(
+            status_mismatch(&OrderStatus::Completed, EscrowStatus::Pending),
+            Some(StatusMismatch::OrderSettled)
+        );
+        assert_eq!(
+            status_mismatch(&OrderStatus::Cancelled, EscrowStatus::Disputed),
+            Some(StatusMismatch::OrderSettled)
+        );
+        assert_eq!(
+            status_mismatch(&OrderStatus::Completed, EscrowStatus::Refunded),
+            Some(StatusMismatch::Conflicting)
+        );
+        assert_eq!(
+            status_mismatch(&OrderStatus::Rejected, EscrowStatus::Completed),
+            Some(StatusMismatch::Conflicting)
+        );
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_reconcile_order_does_not_mutate(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let order = create_order(
+            &pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(1, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        accept_order(&pool, order.id, seller, None).await.unwrap();
+        set_escrow_order_id(&pool, order.id, buyer, "escrow-7").await.unwrap();
+        
+        let report = reconcile_order(&pool, order.id, EscrowStatus::Pending).await.unwrap();
+        assert_eq!(report.mismatch, None);
+        assert_eq!(report.escrow_order_id.as_deref(), Some("escrow-7"));
+        
+        let report = reconcile_order(&pool, order.id, EscrowStatus::Completed).await.unwrap();
+        assert_eq!(report.order_status, OrderStatus::Accepted);
+        assert_eq!(report.escrow_status, EscrowStatus::Completed);
+        assert_eq!(report.mismatch, Some(StatusMismatch::EscrowSettled));
+        assert_eq!(get_order(&pool, order.id).await.unwrap().status, OrderStatus::Accepted.to_string());
+        
+        let result = reconcile_order(&pool, Uuid::new_v4(), EscrowStatus::Pending).await;
+        assert!(matches!(result, Err(OrderError::NotFound)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_set_escrow_order_id(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let max_quantity = test_config().max_order_quantity;
+        // Identical orders are placed on purpose, so the resubmit guard is off
+        let duplicate_window_secs = 0;
+        let order = || CreateOrderData {
+            listing_id,
+            quantity: Decimal::new(1, 0),
+        };
+        let first = create_order(&pool, buyer, order(), max_quantity, duplicate_window_secs).await.unwrap();
+        let second = create_order(&pool, buyer, order(), max_quantity, duplicate_window_secs).await.unwrap();
+        assert_eq!(first.escrow_order_id, None);
+        
+        // Only the buyer may link the escrow order
+        let result = set_escrow_order_id(&pool, first.id, seller, "escrow-1").await;
+        assert!(matches!(result, Err(OrderError::Unauthorized)));
+        
+        let linked = set_escrow_order_id(&pool, first.id, buyer, " escrow-1 ").await.unwrap();
+        assert_eq!(linked.escrow_order_id.as_deref(), Some("escrow-1"));
+        assert_eq!(get_order(&pool, first.id).await.unwrap().escrow_order_id.as_deref(), Some("escrow-1"));
+        
+        // Repeating the same link is fine, but it can't be changed or reused
+        assert!(set_escrow_order_id(&pool, first.id, buyer, "escrow-1").await.is_ok());
+        let result = set_escrow_order_id(&pool, first.id, buyer, "escrow-2").await;
+        assert!(matches!(result, Err(OrderError::EscrowAlreadyLinked)));
+        let result = set_escrow_order_id(&pool, second.id, buyer, "escrow-1").await;
+        assert!(matches!(result, Err(OrderError::EscrowAlreadyLinked)));
+        
+        let result = set_escrow_order_id(&pool, Uuid::new_v4(), buyer, "escrow-3").await;
+        assert!(matches!(result, Err(OrderError::NotFound)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_cancel_order_flags_escrow_refund(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let place = |quantity| {
+            create_order(&pool, buyer, CreateOrderData { listing_id, quantity }, test_config().max_order_quantity, 0)
+        };
+        let plain = place(Decimal::new(1, 0)).await.unwrap();
+        let funded = place(Decimal::new(2, 0)).await.unwrap();
+        set_escrow_order_id(&pool, funded.id, buyer, "escrow-7").await.unwrap();
+        
+        // Without an escrow the order is just cancelled
+        match cancel_order(&pool, plain.id, buyer).await.unwrap() {
+            CancelOutcome::Cancelled(order) => assert_eq!(order.status, "Cancelled"),
+            other => panic!("expected a plain cancellation, got {:?}", other),
+        }
+        
+        // With one, the caller is told which escrow to refund
+        match cancel_order(&pool, funded.id, buyer).await.unwrap() {
+            CancelOutcome::RefundRequired { order, escrow_order_id } => {
+                assert_eq!(order.status, "Cancelled");
+                assert_eq!(escrow_order_id, "escrow-7");
+            }
+            other => panic!("expected a refund to be required, got {:?}", other),
+        }
+        assert_eq!(get_order(&pool, funded.id).await.unwrap().status, "Cancelled");
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_order_rejects_rapid_duplicate(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let config = test_config();
+        let order = |quantity| CreateOrderData { listing_id, quantity };
+        let place = |quantity| {
+            create_order(&pool, buyer, order(quantity), config.max_order_quantity, config.duplicate_order_window_secs)
+        };
+        
+        let first = place(Decimal::new(3, 0)).await.unwrap();
+        assert!(matches!(place(Decimal::new(3, 0)).await, Err(OrderError::DuplicateOrder)));
+        
+        // A different quantity is a different order
+        assert!(place(Decimal::new(4, 0)).await.is_ok());
+        
+        // Once the window has passed the same order goes through
+        sqlx::query("UPDATE orders SET created_at = $1 WHERE id = $2")
+            .bind(Utc::now() - chrono::Duration::seconds(config.duplicate_order_window_secs as i64 + 1))
+            .bind(first.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(place(Decimal::new(3, 0)).await.is_ok());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_order_rejects_self_order(pool: PgPool) {
+        let seller = insert_test_member(&pool).await;
+        let buyer = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let max_quantity = test_config().max_order_quantity;
+        let duplicate_window_secs = test_config().duplicate_order_window_secs;
+        let order = || CreateOrderData {
+            listing_id,
+            quantity: Decimal::new(1, 0),
+        };
+        
+        let result = create_order(&pool, seller, order(), max_quantity, duplicate_window_secs).await;
+        assert!(matches!(result, Err(OrderError::SelfOrder)));
+        assert!(get_orders_by_seller(&pool, seller, None, None).await.unwrap().orders.is_empty());
+        
+        let order = create_order(&pool, buyer, order(), max_quantity, duplicate_window_secs).await.unwrap();
+        assert_eq!((order.buyer_id, order.seller_id), (buyer, seller));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_order_requires_whole_discrete_units(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let max_quantity = test_config().max_order_quantity;
+        let duplicate_window_secs = test_config().duplicate_order_window_secs;
+        let order = |quantity| CreateOrderData { listing_id, quantity };
+        
+        // Produce sold by weight can be ordered in fractions
+        assert!(create_order(&pool, buyer, order(Decimal::new(15, 1)), max_quantity, duplicate_window_secs).await.is_ok());
+        
+        sqlx::query("UPDATE product_listings SET unit_of_measure = 'each' WHERE id = $1")
+            .bind(listing_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let result = create_order(&pool, buyer, order(Decimal::new(15, 1)), max_quantity, duplicate_window_secs).await;
+        assert!(matches!(result, Err(OrderError::InvalidData(_))));
+        assert!(create_order(&pool, buyer, order(Decimal::new(2, 0)), max_quantity, duplicate_window_secs).await.is_ok());
+    }
+    
+    #[test]
+    fn test_order_cursor_round_trip() {
+        let cursor = OrderCursor {
+            created_at: DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+        
+        let token = cursor.encode();
+        
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(OrderCursor::decode(&token).unwrap(), cursor);
+        for token in ["", "not base64!", "bm9jb2xvbg", &URL_SAFE_NO_PAD.encode("12:not-a-uuid")] {
+            assert!(matches!(OrderCursor::decode(token), Err(OrderError::InvalidData(_))));
+        }
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_order_pages_stable_under_inserts(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let max_quantity = test_config().max_order_quantity;
+        // Identical orders are placed on purpose, so the resubmit guard is off
+        let duplicate_window_secs = 0;
+        let order = || CreateOrderData {
+            listing_id,
+            quantity: Decimal::new(1, 0),
+        };
+        let mut expected = Vec::new();
+        for _ in 0..5 {
+            expected.push(create_order(&pool, buyer, order(), max_quantity, duplicate_window_secs).await.unwrap().id);
+        }
+        expected.reverse();
+        
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = get_orders_by_buyer(&pool, buyer, cursor, Some(2)).await.unwrap();
+            assert!(page.orders.len() <= 2);
+            seen.extend(page.orders.iter().map(|o| o.id));
+            
+            // Newer orders arriving mid-iteration must not shift later pages
+            create_order(&pool, buyer, order(), max_quantity, duplicate_window_secs).await.unwrap();
+            
+            match page.next_cursor {
+                Some(next) => cursor = Some(OrderCursor::decode(&next).unwrap()),
+                None => break,
+            }
+        }
+        
+        assert_eq!(seen, expected);
+        
+        // The seller sees the same orders, and the newer ones, from the start
+        let page = get_orders_by_seller(&pool, seller, None, Some(MAX_PAGE_SIZE + 1)).await.unwrap();
+        assert_eq!(page.orders.len(), 8);
+        assert_eq!(page.next_cursor, None);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_get_orders_by_status(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let max_quantity = test_config().max_order_quantity;
+        // Identical orders are placed on purpose, so the resubmit guard is off
+        let duplicate_window_secs = 0;
+        let order = || CreateOrderData {
+            listing_id,
+            quantity: Decimal::new(1, 0),
+        };
+        let pending = create_order(&pool, buyer, order(), max_quantity, duplicate_window_secs).await.unwrap();
+        let accepted = create_order(&pool, buyer, order(), max_quantity, duplicate_window_secs).await.unwrap();
+        accept_order(&pool, accepted.id, seller, None).await.unwrap();
+        // Another buyer's pending order from the same seller
+        let other_buyer = insert_test_member(&pool).await;
+        let other = create_order(&pool, other_buyer, order(), max_quantity, duplicate_window_secs).await.unwrap();
+        
+        let ids = |orders: Vec<Order>| orders.into_iter().map(|o| o.id).collect::<Vec<_>>();
+        
+        let orders = get_orders_by_seller_and_status(&pool, seller, OrderStatus::Pending).await.unwrap();
+        assert_eq!(ids(orders), vec![other.id, pending.id]);
+        let orders = get_orders_by_buyer_and_status(&pool, buyer, OrderStatus::Pending).await.unwrap();
+        assert_eq!(ids(orders), vec![pending.id]);
+        let orders = get_orders_for_member_and_status(&pool, buyer, OrderStatus::Accepted).await.unwrap();
+        assert_eq!(ids(orders.into_iter().map(|o| o.order).collect()), vec![accepted.id]);
+        let orders = get_orders_by_seller_and_status(&pool, seller, OrderStatus::Completed).await.unwrap();
+        assert!(orders.is_empty());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_status_changes_recorded_in_history(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        
+        let order = create_order(
+            &pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(2, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        
+        accept_order(&pool, order.id, seller, None).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        complete_order(&mut conn, order.id, buyer, Decimal::new(5, 2)).await.unwrap();
+        
+        let history = get_order_history(&pool, order.id).await.unwrap();
+        
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].from_status, "Pending");
+        assert_eq!(history[0].to_status, "Accepted");
+        assert_eq!(history[0].actor_id, seller);
+        assert_eq!(history[1].from_status, "Accepted");
+        assert_eq!(history[1].to_status, "Completed");
+        assert_eq!(history[1].actor_id, buyer);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_stale_status_change_rejected(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        
+        let order = create_order(
+            &pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(2, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        accept_order(&pool, order.id, seller, None).await.unwrap();
+        
+        // A cancel that read the order while it was still Pending must not overwrite the accept
+        let mut tx = pool.begin().await.unwrap();
+        let result = record_status_change(&mut tx, order.id, OrderStatus::Pending, OrderStatus::Cancelled, buyer).await;
+        assert!(matches!(result, Err(OrderError::InvalidStatusTransition(_))));
+        tx.rollback().await.unwrap();
+        
+        let order = get_order(&pool, order.id).await.unwrap();
+        assert_eq!(order.status, "Accepted");
+        assert_eq!(get_order_history(&pool, order.id).await.unwrap().len(), 1);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_only_buyer_can_complete_order(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let stranger = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        
+        let order = create_order(
+            &pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(2, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        accept_order(&pool, order.id, seller, None).await.unwrap();
+        
+        // Neither a stranger nor the seller can complete the buyer's order
+        let mut conn = pool.acquire().await.unwrap();
+        for actor in [stranger, seller] {
+            let result = complete_order(&mut conn, order.id, actor, Decimal::new(5, 2)).await;
+            assert!(matches!(result, Err(OrderError::Unauthorized)));
+        }
+        
+        let reloaded = get_order(&pool, order.id).await.unwrap();
+        assert_eq!(reloaded.status, "Accepted");
+        assert_eq!(get_order_history(&pool, order.id).await.unwrap().len(), 1);
+        assert!(transactions::get_transactions_by_order(&pool, order.id).await.unwrap().is_empty());
+    }
+    
+    #[test]
+    fn test_validate_message_body() {
+        assert!(validate_message_body(" Pickup at the north gate ").is_ok());
+        assert!(matches!(validate_message_body("  "), Err(OrderError::InvalidData(_))));
+        assert!(validate_message_body(&"a".repeat(MAX_ORDER_MESSAGE_LENGTH)).is_ok());
+        assert!(matches!(
+            validate_message_body(&"a".repeat(MAX_ORDER_MESSAGE_LENGTH + 1)),
+            Err(OrderError::InvalidData(_))
+        ));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_order_messages_thread(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let stranger = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let order = insert_test_order(&pool, buyer, seller, listing_id, Utc::now()).await;
+        
+        send_order_message(&pool, order, buyer, "Can I collect on Saturday?").await.unwrap();
+        send_order_message(&pool, order, seller, " Saturday morning works ").await.unwrap();
+        
+        // A third party can neither read nor post
+        let result = send_order_message(&pool, order, stranger, "Hello").await;
+        assert!(matches!(result, Err(OrderError::Unauthorized)));
+        let result = get_order_messages(&pool, order, stranger).await;
+        assert!(matches!(result, Err(OrderError::Unauthorized)));
+        
+        // The thread outlives the order
+        sqlx::query("UPDATE orders SET status = 'Completed' WHERE id = $1")
+            .bind(order)
+            .execute(&pool)
+            .await
+            .unwrap();
+        
+        let messages = get_order_messages(&pool, order, seller).await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].sender_id, buyer);
+        assert_eq!(messages[0].body, "Can I collect on Saturday?");
+        assert_eq!(messages[1].sender_id, seller);
+        assert_eq!(messages[1].body, "Saturday morning works");
+    }
+    
+    #[test]
+    fn test_validate_seller_note() {
+        assert_eq!(validate_seller_note(None).unwrap(), None);
+        assert_eq!(validate_seller_note(Some("  ")).unwrap(), None);
+        assert_eq!(validate_seller_note(Some(" Back next week ")).unwrap(), Some("Back next week"));
+        assert!(validate_seller_note(Some(&"a".repeat(MAX_SELLER_NOTE_LENGTH))).is_ok());
+        assert!(matches!(
+            validate_seller_note(Some(&"a".repeat(MAX_SELLER_NOTE_LENGTH + 1))),
+            Err(OrderError::InvalidData(_))
+        ));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_rejection_note_visible_to_buyer(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let data = CreateOrderData {
+            listing_id,
+            quantity: Decimal::new(2, 0),
+        };
+        let max_quantity = test_config().max_order_quantity;
+        // Identical orders are placed on purpose, so the resubmit guard is off
+        let duplicate_window_secs = 0;
+        let rejected = create_order(&pool, buyer, data.clone(), max_quantity, duplicate_window_secs).await.unwrap();
+        let accepted = create_order(&pool, buyer, data, max_quantity, duplicate_window_secs).await.unwrap();
+        
+        let result = reject_order(&pool, rejected.id, seller, Some(&"a".repeat(MAX_SELLER_NOTE_LENGTH + 1))).await;
+        assert!(matches!(result, Err(OrderError::InvalidData(_))));
+        assert_eq!(get_order(&pool, rejected.id).await.unwrap().status, "Pending");
+        
+        let order = reject_order(&pool, rejected.id, seller, Some("Out of stock until next week")).await.unwrap();
+        assert_eq!(order.seller_note.as_deref(), Some("Out of stock until next week"));
+        let order = accept_order(&pool, accepted.id, seller, None).await.unwrap();
+        assert_eq!(order.seller_note, None);
+        
+        let page = get_orders_by_buyer(&pool, buyer, None, None).await.unwrap();
+        let rejected = page.orders.iter().find(|o| o.id == rejected.id).unwrap();
+        assert_eq!(rejected.status, "Rejected");
+        assert_eq!(rejected.seller_note.as_deref(), Some("Out of stock until next week"));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_ship_and_deliver_order(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        
+        let order = create_order(
+            &pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(1, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        accept_order(&pool, order.id, seller, None).await.unwrap();
+        
+        // Only the seller ships and only the buyer confirms delivery
+        assert!(matches!(ship_order(&pool, order.id, buyer).await, Err(OrderError::Unauthorized)));
+        let shipped = ship_order(&pool, order.id, seller).await.unwrap();
+        assert_eq!(shipped.status, "Shipped");
+        
+        assert!(matches!(mark_delivered(&pool, order.id, seller).await, Err(OrderError::Unauthorized)));
+        let delivered = mark_delivered(&pool, order.id, buyer).await.unwrap();
+        assert_eq!(delivered.status, "Delivered");
+        
+        let mut conn = pool.acquire().await.unwrap();
+        let completed = complete_order(&mut conn, order.id, buyer, Decimal::new(5, 2)).await.unwrap();
+        assert_eq!(completed.order.status, "Completed");
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_get_orders_for_member_deduplicated_and_sorted(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        let other = insert_test_member(&pool).await;
+        let own_listing = insert_test_listing(&pool, member).await;
+        let other_listing = insert_test_listing(&pool, other).await;
+        let now = Utc::now();
+        
+        // Member as buyer, as seller, and as both
+        insert_test_order(&pool, member, other, other_listing, now - chrono::Duration::hours(3)).await;
+        insert_test_order(&pool, other, member, own_listing, now - chrono::Duration::hours(1)).await;
+        insert_test_order(&pool, member, member, own_listing, now - chrono::Duration::hours(2)).await;
+        
+        let orders = get_orders_for_member(&pool, member).await.unwrap();
+        
+        let mut ids: Vec<Uuid> = orders.iter().map(|o| o.order.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(orders.len(), 3);
+        assert!(orders.windows(2).all(|w| w[0].order.created_at >= w[1].order.created_at));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_orders_carry_listing_summary(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let order = insert_test_order(&pool, buyer, seller, listing_id, Utc::now()).await;
+        
+        let enriched = get_order_with_listing(&pool, order).await.unwrap();
+        assert_eq!(enriched.order.id, order);
+        assert_eq!(enriched.product_name.as_deref(), Some("Organic Tomatoes"));
+        assert_eq!(enriched.unit_of_measure.as_deref(), Some("kg"));
+        
+        // Archiving the listing later doesn't drop it from the order
+        crate::listings::delete_listing(&pool, listing_id, seller).await.unwrap();
+        let orders = get_orders_for_member(&pool, buyer).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].product_name.as_deref(), Some("Organic Tomatoes"));
+        
+        let result = get_order_with_listing(&pool, Uuid::new_v4()).await;
+        assert!(matches!(result, Err(OrderError::NotFound)));
+    }
+    
     #[test]
     fn test_create_order_data_validation() {
         let data = CreateOrderData {
-            product_listing_id: Uuid::new_v4(),
+            listing_id: Uuid::new_v4(),
             quantity: Decimal::new(10, 0),
         };
         
@@ -349,12 +1814,40 @@ mod tests {
         assert!(is_valid_status_transition(&OrderStatus::Accepted, &OrderStatus::Cancelled));
     }
     
+    #[test]
+    fn test_is_valid_status_transition_accepted_to_shipped() {
+        assert!(is_valid_status_transition(&OrderStatus::Accepted, &OrderStatus::Shipped));
+    }
+    
+    #[test]
+    fn test_is_valid_status_transition_shipped_to_delivered() {
+        assert!(is_valid_status_transition(&OrderStatus::Shipped, &OrderStatus::Delivered));
+    }
+    
+    #[test]
+    fn test_is_valid_status_transition_delivered_to_completed() {
+        assert!(is_valid_status_transition(&OrderStatus::Delivered, &OrderStatus::Completed));
+    }
+    
+    #[test]
+    fn test_is_valid_status_transition_shipped_invalid() {
+        // Shipped orders can only move to Delivered
+        assert!(!is_valid_status_transition(&OrderStatus::Shipped, &OrderStatus::Completed));
+        assert!(!is_valid_status_transition(&OrderStatus::Shipped, &OrderStatus::Cancelled));
+        assert!(!is_valid_status_transition(&OrderStatus::Shipped, &OrderStatus::Accepted));
+        assert!(!is_valid_status_transition(&OrderStatus::Delivered, &OrderStatus::Shipped));
+        assert!(!is_valid_status_transition(&OrderStatus::Delivered, &OrderStatus::Cancelled));
+        assert!(!is_valid_status_transition(&OrderStatus::Pending, &OrderStatus::Shipped));
+        assert!(!is_valid_status_transition(&OrderStatus::Pending, &OrderStatus::Delivered));
+    }
+    
     #[test]
     fn test_is_valid_status_transition_rejected_to_any() {
         // Rejected is a terminal state
         assert!(!is_valid_status_transition(&OrderStatus::Rejected, &OrderStatus::Pending));
         assert!(!is_valid_status_transition(&OrderStatus::Rejected, &OrderStatus::Accepted));
         assert!(!is_valid_status_transition(&OrderStatus::Rejected, &OrderStatus::Completed));
+        assert!(!is_valid_status_transition(&OrderStatus::Rejected, &OrderStatus::Shipped));
     }
     
     #[test]
@@ -363,6 +1856,8 @@ mod tests {
         assert!(!is_valid_status_transition(&OrderStatus::Completed, &OrderStatus::Pending));
         assert!(!is_valid_status_transition(&OrderStatus::Completed, &OrderStatus::Accepted));
         assert!(!is_valid_status_transition(&OrderStatus::Completed, &OrderStatus::Cancelled));
+        assert!(!is_valid_status_transition(&OrderStatus::Completed, &OrderStatus::Shipped));
+        assert!(!is_valid_status_transition(&OrderStatus::Completed, &OrderStatus::Delivered));
     }
     
     #[test]
@@ -371,6 +1866,22 @@ mod tests {
         assert!(!is_valid_status_transition(&OrderStatus::Cancelled, &OrderStatus::Pending));
         assert!(!is_valid_status_transition(&OrderStatus::Cancelled, &OrderStatus::Accepted));
         assert!(!is_valid_status_transition(&OrderStatus::Cancelled, &OrderStatus::Completed));
+        assert!(!is_valid_status_transition(&OrderStatus::Cancelled, &OrderStatus::Shipped));
+    }
+    
+    #[test]
+    fn test_is_valid_status_transition_disputes() {
+        assert!(is_valid_status_transition(&OrderStatus::Accepted, &OrderStatus::Disputed));
+        assert!(is_valid_status_transition(&OrderStatus::Shipped, &OrderStatus::Disputed));
+        assert!(is_valid_status_transition(&OrderStatus::Disputed, &OrderStatus::Resolved));
+        
+        assert!(!is_valid_status_transition(&OrderStatus::Pending, &OrderStatus::Disputed));
+        assert!(!is_valid_status_transition(&OrderStatus::Completed, &OrderStatus::Disputed));
+        assert!(!is_valid_status_transition(&OrderStatus::Disputed, &OrderStatus::Completed));
+        assert!(!is_valid_status_transition(&OrderStatus::Disputed, &OrderStatus::Cancelled));
+        // Resolved is a terminal state
+        assert!(!is_valid_status_transition(&OrderStatus::Resolved, &OrderStatus::Disputed));
+        assert!(!is_valid_status_transition(&OrderStatus::Resolved, &OrderStatus::Completed));
     }
     
     #[test]
@@ -391,6 +1902,8 @@ mod tests {
             quantity: Decimal::new(10, 0),
             total_amount: Decimal::new(100, 0),
             status: OrderStatus::Pending.to_string(),
+            escrow_order_id: None,
+            seller_note: None,
             created_at: Utc::now(),
         };
         
@@ -414,6 +1927,8 @@ mod tests {
             quantity: Decimal::new(10, 0),
             total_amount: Decimal::new(100, 0),
             status: OrderStatus::Pending.to_string(),
+            escrow_order_id: None,
+            seller_note: None,
             created_at: Utc::now(),
         };
         
@@ -437,6 +1952,8 @@ mod tests {
             quantity: Decimal::new(10, 0),
             total_amount: Decimal::new(100, 0),
             status: OrderStatus::Accepted.to_string(),
+            escrow_order_id: None,
+            seller_note: None,
             created_at: Utc::now(),
         };
         
@@ -448,6 +1965,20 @@ mod tests {
         };
         
         assert!(!can_complete_order(&pending_order));
+        
+        let delivered_order = Order {
+            status: OrderStatus::Delivered.to_string(),
+            ..pending_order.clone()
+        };
+        
+        assert!(can_complete_order(&delivered_order));
+        
+        let shipped_order = Order {
+            status: OrderStatus::Shipped.to_string(),
+            ..pending_order
+        };
+        
+        assert!(!can_complete_order(&shipped_order));
     }
     
     #[test]
@@ -460,6 +1991,8 @@ mod tests {
             quantity: Decimal::new(10, 0),
             total_amount: Decimal::new(100, 0),
             status: OrderStatus::Pending.to_string(),
+            escrow_order_id: None,
+            seller_note: None,
             created_at: Utc::now(),
         };
         
@@ -503,9 +2036,14 @@ mod tests {
                 member_id: Uuid::new_v4(),
                 name: "Test Product".to_string(),
                 description: "Test Description".to_string(),
-                quantity: listing_quantity,
+                category: "Vegetables".to_string(),
+                quantity_available: listing_quantity,
                 unit_price,
+                unit_of_measure: "kg".to_string(),
                 availability: AvailabilityStatus::Available.to_string(),
+                version: 1,
+                expires_at: None,
+                view_count: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             };
@@ -518,15 +2056,15 @@ mod tests {
             
             // Property 2: Listing must have sufficient quantity
             prop_assert!(
-                listing.quantity >= order_quantity,
+                listing.quantity_available >= order_quantity,
                 "Listing quantity ({}) must be >= order quantity ({})",
-                listing.quantity,
+                listing.quantity_available,
                 order_quantity
             );
             
             // Create order data
             let order_data = CreateOrderData {
-                product_listing_id: listing.id,
+                listing_id: listing.id,
                 quantity: order_quantity,
             };
             
@@ -548,7 +2086,7 @@ mod tests {
             // Here we verify the preconditions that would make order creation succeed
             prop_assert!(
                 order_data.quantity > Decimal::ZERO && 
-                order_data.quantity <= listing.quantity &&
+                order_data.quantity <= listing.quantity_available &&
                 listings::is_available_for_purchase(&listing),
                 "Order should meet all preconditions for successful creation"
             );
@@ -0,0 +1,500 @@
+use crate::error::ReportError;
+use crate::models::{Order, OrderStatus, TransactionStatus};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A seller's completed sales over a date range
+#[derive(Debug, Clone, Serialize)]
+pub struct SalesReport {
+    pub seller_id: Uuid,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_revenue: Decimal,
+    pub order_count: i64,
+    /// Average order total rounded to two decimals, or `None` when there were no orders
+    pub average_order_value: Option<Decimal>,
+}
+
+/// Summarize a seller's completed orders placed between `from` and `to`, inclusive
+pub async fn sales_report(
+    pool: &PgPool,
+    seller_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<SalesReport, ReportError> {
+    validate_date_range(from, to)?;
+    
+    let (total_revenue, order_count, average_order_value): (Option<Decimal>, i64, Option<Decimal>) = sqlx::query_as(
+        "SELECT SUM(total_amount), COUNT(*), ROUND(AVG(total_amount), 2)
+         FROM orders
+         WHERE seller_id = $1 AND status = $2 AND created_at BETWEEN $3 AND $4"
+    )
+    .bind(seller_id)
+    .bind(OrderStatus::Completed.to_string())
+    .bind(from)
+    .bind(to)
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(SalesReport {
+        seller_id,
+        from,
+        to,
+        total_revenue: total_revenue.unwrap_or(Decimal::ZERO),
+        order_count,
+        average_order_value,
+    })
+}
+
+/// What a seller earned from completed orders over a date range
+#[derive(Debug, Clone, Serialize)]
+pub struct EarningsSummary {
+    pub member_id: Uuid,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Sum of completed order totals
+    pub gross: Decimal,
+    /// Cooperative fees withheld from those orders
+    pub fees: Decimal,
+    pub net: Decimal,
+}
+
+/// Summarize a seller's earnings from completed orders placed between `from` and `to`, inclusive
+///
+/// Fees are taken from the transactions recorded when each order completed,
+/// ignoring reversed ones, so a later change to the fee rate doesn't rewrite history.
+pub async fn member_earnings(
+    pool: &PgPool,
+    member_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<EarningsSummary, ReportError> {
+    validate_date_range(from, to)?;
+    
+    let (gross, fees): (Option<Decimal>, Option<Decimal>) = sqlx::query_as(
+        "WITH completed AS (
+             SELECT id, total_amount
+             FROM orders
+             WHERE seller_id = $1 AND status = $2 AND created_at BETWEEN $3 AND $4
+         )
+         SELECT
+             (SELECT SUM(total_amount) FROM completed),
+             (SELECT SUM(cooperative_fee) FROM transactions
+              WHERE order_id IN (SELECT id FROM completed) AND status != $5)"
+    )
+    .bind(member_id)
+    .bind(OrderStatus::Completed.to_string())
+    .bind(from)
+    .bind(to)
+    .bind(TransactionStatus::Reversed.to_string())
+    .fetch_one(pool)
+    .await?;
+    
+    let gross = gross.unwrap_or(Decimal::ZERO);
+    let fees = fees.unwrap_or(Decimal::ZERO);
+    
+    Ok(EarningsSummary {
+        member_id,
+        from,
+        to,
+        gross,
+        fees,
+        net: gross - fees,
+    })
+}
+
+/// Cooperative fees collected in one calendar month (UTC)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MonthlyRevenue {
+    /// First day of the month
+    pub month: NaiveDate,
+    pub fees: Decimal,
+    pub transaction_count: i64,
+}
+
+/// Cooperative fee income across the platform over a date range
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformRevenue {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_fees: Decimal,
+    /// One entry per month touched by the range, including months without income
+    pub months: Vec<MonthlyRevenue>,
+}
+
+/// Sum cooperative fees of transactions completed between `from` and `to`, inclusive, by month
+pub async fn platform_revenue(
+    pool: &PgPool,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<PlatformRevenue, ReportError> {
+    validate_date_range(from, to)?;
+    
+    let rows: Vec<(NaiveDate, Decimal, i64)> = sqlx::query_as(
+        "SELECT date_trunc('month', completed_at AT TIME ZONE 'UTC')::date, SUM(cooperative_fee), COUNT(*)
+         FROM transactions
+         WHERE status = $1 AND completed_at BETWEEN $2 AND $3
+         GROUP BY 1"
+    )
+    .bind(TransactionStatus::Completed.to_string())
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    
+    let mut by_month: HashMap<NaiveDate, (Decimal, i64)> = rows
+        .into_iter()
+        .map(|(month, fees, count)| (month, (fees, count)))
+        .collect();
+    let months: Vec<MonthlyRevenue> = month_starts(from, to)
+        .into_iter()
+        .map(|month| {
+            let (fees, transaction_count) = by_month.remove(&month).unwrap_or((Decimal::ZERO, 0));
+            MonthlyRevenue {
+                month,
+                fees,
+                transaction_count,
+            }
+        })
+        .collect();
+    
+    Ok(PlatformRevenue {
+        from,
+        to,
+        total_fees: months.iter().map(|month| month.fees).sum(),
+        months,
+    })
+}
+
+/// First day of every month from the one containing `from` to the one containing `to`
+pub fn month_starts(from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<NaiveDate> {
+    let first_of_month = |date: NaiveDate| date.with_day(1).expect("every month has a first day");
+    let last = first_of_month(to.date_naive());
+    
+    let mut months = Vec::new();
+    let mut month = first_of_month(from.date_naive());
+    while month <= last {
+        months.push(month);
+        month = match month.checked_add_months(Months::new(1)) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    
+    months
+}
+
+/// Column headers of the sales CSV export
+pub const SALES_CSV_HEADER: &str = "order_id,buyer_id,quantity,total_amount,status,created_at";
+
+/// Export a seller's orders placed between `from` and `to`, inclusive, as CSV
+///
+/// Unlike [`sales_report`], orders in every status are included so the
+/// export can be reconciled against the status column.
+pub async fn export_sales_csv(
+    pool: &PgPool,
+    seller_id: Uuid,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<String, ReportError> {
+    validate_date_range(from, to)?;
+    
+    let orders = sqlx::query_as::<_, Order>(
+        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at
+         FROM orders
+         WHERE seller_id = $1 AND created_at BETWEEN $2 AND $3
+         ORDER BY created_at ASC, id"
+    )
+    .bind(seller_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    
+    let mut csv = String::from(SALES_CSV_HEADER);
+    csv.push_str("\r\n");
+    for order in &orders {
+        csv.push_str(&csv_row(&[
+            order.id.to_string(),
+            order.buyer_id.to_string(),
+            order.quantity.to_string(),
+            order.total_amount.to_string(),
+            order.status.clone(),
+            order.created_at.to_rfc3339(),
+        ]));
+    }
+    
+    Ok(csv)
+}
+
+/// Join fields into a CRLF-terminated CSV record
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields.iter().map(|field| escape_csv_field(field)).collect::<Vec<_>>().join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break, doubling any quotes
+pub fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Validate that a report's date range does not end before it starts
+pub fn validate_date_range(from: DateTime<Utc>, to: DateTime<Utc>) -> Result<(), ReportError> {
+    if from > to {
+        return Err(ReportError::InvalidDateRange);
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orders::{self, CreateOrderData};
+    use crate::transactions;
+    use crate::test_utils::{insert_test_listing, insert_test_member, test_config};
+    use chrono::{Duration, TimeZone};
+    
+    /// Place an order for `quantity` units at 2.99 and backdate it with the given status
+    async fn seed_order(
+        pool: &PgPool,
+        buyer: Uuid,
+        listing_id: Uuid,
+        quantity: i64,
+        status: OrderStatus,
+        created_at: DateTime<Utc>,
+    ) -> Uuid {
+        let order = orders::create_order(
+            pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(quantity, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap();
+        
+        sqlx::query("UPDATE orders SET status = $1, created_at = $2 WHERE id = $3")
+            .bind(status.to_string())
+            .bind(created_at)
+            .bind(order.id)
+            .execute(pool)
+            .await
+            .unwrap();
+        
+        order.id
+    }
+    
+    #[test]
+    fn test_validate_date_range() {
+        let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        
+        assert!(validate_date_range(from, from).is_ok());
+        assert!(validate_date_range(from, from + Duration::days(30)).is_ok());
+        assert!(matches!(
+            validate_date_range(from, from - Duration::seconds(1)),
+            Err(ReportError::InvalidDateRange)
+        ));
+    }
+    
+    #[test]
+    fn test_month_starts() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let at = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap();
+        
+        assert_eq!(month_starts(at(2024, 3, 15), at(2024, 3, 20)), [date(2024, 3, 1)]);
+        assert_eq!(
+            month_starts(at(2023, 11, 30), at(2024, 2, 1)),
+            [date(2023, 11, 1), date(2023, 12, 1), date(2024, 1, 1), date(2024, 2, 1)]
+        );
+    }
+    
+    #[test]
+    fn test_escape_csv_field() {
+        assert_eq!(escape_csv_field("Completed"), "Completed");
+        assert_eq!(escape_csv_field("Tomatoes, heirloom"), "\"Tomatoes, heirloom\"");
+        assert_eq!(escape_csv_field("12\" pots"), "\"12\"\" pots\"");
+        assert_eq!(escape_csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(
+            csv_row(&["a,b".to_string(), "c".to_string()]),
+            "\"a,b\",c\r\n"
+        );
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_sales_report_aggregates_completed_orders(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let from = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 3, 31, 23, 59, 59).unwrap();
+        
+        // 2 x 2.99 and 5 x 2.99 count towards the report
+        seed_order(&pool, buyer, listing_id, 2, OrderStatus::Completed, from).await;
+        seed_order(&pool, buyer, listing_id, 5, OrderStatus::Completed, from + Duration::days(10)).await;
+        // Orders outside the window or not completed are excluded
+        seed_order(&pool, buyer, listing_id, 3, OrderStatus::Completed, from - Duration::seconds(1)).await;
+        seed_order(&pool, buyer, listing_id, 3, OrderStatus::Completed, to + Duration::seconds(1)).await;
+        seed_order(&pool, buyer, listing_id, 4, OrderStatus::Delivered, from + Duration::days(5)).await;
+        // Other sellers' orders are excluded
+        let other_listing = insert_test_listing(&pool, buyer).await;
+        seed_order(&pool, seller, other_listing, 1, OrderStatus::Completed, from + Duration::days(5)).await;
+        
+        let report = sales_report(&pool, seller, from, to).await.unwrap();
+        
+        assert_eq!(report.seller_id, seller);
+        assert_eq!(report.order_count, 2);
+        assert_eq!(report.total_revenue, Decimal::new(2093, 2));
+        // 20.93 / 2 = 10.465 rounds to 10.47
+        assert_eq!(report.average_order_value, Some(Decimal::new(1047, 2)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_sales_report_without_orders(pool: PgPool) {
+        let seller = insert_test_member(&pool).await;
+        let to = Utc::now();
+        
+        let report = sales_report(&pool, seller, to - Duration::days(7), to).await.unwrap();
+        assert_eq!(report.order_count, 0);
+        assert_eq!(report.total_revenue, Decimal::ZERO);
+        assert_eq!(report.average_order_value, None);
+        
+        let result = sales_report(&pool, seller, to, to - Duration::days(7)).await;
+        assert!(matches!(result, Err(ReportError::InvalidDateRange)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_export_sales_csv(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let from = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 3, 31, 23, 59, 59).unwrap();
+        seed_order(&pool, buyer, listing_id, 2, OrderStatus::Completed, from).await;
+        seed_order(&pool, buyer, listing_id, 4, OrderStatus::Delivered, from + Duration::days(1)).await;
+        seed_order(&pool, buyer, listing_id, 3, OrderStatus::Completed, to + Duration::seconds(1)).await;
+        
+        let csv = export_sales_csv(&pool, seller, from, to).await.unwrap();
+        let lines: Vec<&str> = csv.split_terminator("\r\n").collect();
+        
+        assert_eq!(lines[0], SALES_CSV_HEADER);
+        assert_eq!(lines.len(), 3);
+        let fields: Vec<&str> = lines[1].split(',').collect();
+        assert_eq!(fields[1], buyer.to_string());
+        assert_eq!(&fields[2..5], ["2.00", "5.98", "Completed"]);
+        assert!(lines[2].contains(",Delivered,"));
+        
+        let result = export_sales_csv(&pool, seller, to, from).await;
+        assert!(matches!(result, Err(ReportError::InvalidDateRange)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_member_earnings_counts_completed_orders(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let from = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 3, 31, 23, 59, 59).unwrap();
+        let fee_percentage = test_config().cooperative_fee_percentage;
+        
+        // 2 x 2.99 = 5.98 with a 0.30 fee, and 5 x 2.99 = 14.95 with a 0.75 fee
+        for (quantity, status) in [
+            (2, OrderStatus::Completed),
+            (5, OrderStatus::Completed),
+            (4, OrderStatus::Cancelled),
+            (3, OrderStatus::Cancelled),
+        ] {
+            let order_id = seed_order(&pool, buyer, listing_id, quantity, status, from + Duration::days(quantity)).await;
+            transactions::create_transaction(&mut pool.acquire().await.unwrap(), order_id, fee_percentage).await.unwrap();
+        }
+        
+        let earnings = member_earnings(&pool, seller, from, to).await.unwrap();
+        
+        assert_eq!(earnings.member_id, seller);
+        assert_eq!(earnings.gross, Decimal::new(2093, 2));
+        assert_eq!(earnings.fees, Decimal::new(105, 2));
+        assert_eq!(earnings.net, Decimal::new(1988, 2));
+        
+        // The buyer earned nothing from these orders
+        let earnings = member_earnings(&pool, buyer, from, to).await.unwrap();
+        assert_eq!((earnings.gross, earnings.fees, earnings.net), (Decimal::ZERO, Decimal::ZERO, Decimal::ZERO));
+        
+        let result = member_earnings(&pool, seller, to, from).await;
+        assert!(matches!(result, Err(ReportError::InvalidDateRange)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_platform_revenue_buckets_by_month(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let listing_id = insert_test_listing(&pool, seller).await;
+        let from = Utc.with_ymd_and_hms(2024, 1, 10, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 4, 5, 0, 0, 0).unwrap();
+        let fee_percentage = test_config().cooperative_fee_percentage;
+        
+        // Fees of 0.30 and 0.75 completed in January, 0.60 in March, nothing in February
+        let settle = |quantity: i64, completed_at: DateTime<Utc>, reverse: bool| {
+            let pool = pool.clone();
+            async move {
+                let order_id = seed_order(&pool, buyer, listing_id, quantity, OrderStatus::Completed, completed_at).await;
+                let transaction = transactions::create_transaction(&mut pool.acquire().await.unwrap(), order_id, fee_percentage)
+                    .await
+                    .unwrap();
+                transactions::complete_transaction(&pool, transaction.id).await.unwrap();
+                sqlx::query("UPDATE transactions SET completed_at = $1 WHERE id = $2")
+                    .bind(completed_at)
+                    .bind(transaction.id)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+                if reverse {
+                    transactions::reverse_transaction(&pool, transaction.id, "Refunded").await.unwrap();
+                }
+            }
+        };
+        settle(2, from, false).await;
+        settle(5, from + Duration::days(20), false).await;
+        settle(4, Utc.with_ymd_and_hms(2024, 3, 31, 23, 59, 59).unwrap(), false).await;
+        // Reversed and out-of-range transactions don't count
+        settle(3, Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap(), true).await;
+        settle(3, from - Duration::seconds(1), false).await;
+        
+        let revenue = platform_revenue(&pool, from, to).await.unwrap();
+        let month = |m, fees, transaction_count| MonthlyRevenue {
+            month: NaiveDate::from_ymd_opt(2024, m, 1).unwrap(),
+            fees,
+            transaction_count,
+        };
+        
+        assert_eq!(
+            revenue.months,
+            [
+                month(1, Decimal::new(105, 2), 2),
+                month(2, Decimal::ZERO, 0),
+                month(3, Decimal::new(60, 2), 1),
+                month(4, Decimal::ZERO, 0),
+            ]
+        );
+        assert_eq!(revenue.total_fees, Decimal::new(165, 2));
+        
+        let result = platform_revenue(&pool, to, from).await;
+        assert!(matches!(result, Err(ReportError::InvalidDateRange)));
+    }
+}
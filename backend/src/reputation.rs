@@ -0,0 +1,276 @@
+use crate::error::ReputationError;
+use crate::models::{Rating, TransactionStatus};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Aggregated ratings received by a member
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberReputation {
+    pub member_id: Uuid,
+    /// Average score rounded to two decimals, or `None` when the member has no ratings
+    pub average_score: Option<Decimal>,
+    pub total_ratings: i64,
+    /// Number of ratings received for each score from 1 to 5
+    pub histogram: BTreeMap<i32, i64>,
+}
+
+/// Rate the other party to a completed transaction
+pub async fn create_rating(
+    pool: &PgPool,
+    transaction_id: Uuid,
+    rater_id: Uuid,
+    rated_id: Uuid,
+    score: i32,
+) -> Result<Rating, ReputationError> {
+    validate_score(score)?;
+    
+    let (status, buyer_id, seller_id): (String, Uuid, Uuid) = sqlx::query_as(
+        "SELECT t.status, o.buyer_id, o.seller_id
+         FROM transactions t
+         JOIN orders o ON o.id = t.order_id
+         WHERE t.id = $1"
+    )
+    .bind(transaction_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ReputationError::TransactionNotFound)?;
+    
+    if status != TransactionStatus::Completed.to_string() {
+        return Err(ReputationError::TransactionNotCompleted);
+    }
+    
+    let counterparty = if rater_id == buyer_id {
+        seller_id
+    } else if rater_id == seller_id {
+        buyer_id
+    } else {
+        return Err(ReputationError::NotParticipant);
+    };
+    
+    if rated_id != counterparty {
+        return Err(ReputationError::InvalidRating(
+            "Rated member must be the other party to the transaction".to_string(),
+        ));
+    }
+    
+    let rating = sqlx::query_as::<_, Rating>(
+        "INSERT INTO ratings (id, transaction_id, rater_id, rated_id, score, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, transaction_id, rater_id, rated_id, score, created_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(transaction_id)
+    .bind(rater_id)
+    .bind(rated_id)
+    .bind(score)
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(ref db) if db.is_unique_violation() => ReputationError::AlreadyRated,
+        e => ReputationError::Database(e),
+    })?;
+    
+    Ok(rating)
+}
+
+/// Summarize the ratings a member has received
+pub async fn get_member_reputation(
+    pool: &PgPool,
+    member_id: Uuid,
+) -> Result<MemberReputation, ReputationError> {
+    // ROLLUP adds a grand-total row (score NULL) alongside the per-score rows,
+    // and still yields that row with a NULL average when there are no ratings
+    let rows: Vec<(Option<i32>, i64, Option<Decimal>)> = sqlx::query_as(
+        "SELECT score, COUNT(*), ROUND(AVG(score), 2)
+         FROM ratings
+         WHERE rated_id = $1
+         GROUP BY ROLLUP(score)"
+    )
+    .bind(member_id)
+    .fetch_all(pool)
+    .await?;
+    
+    let mut reputation = MemberReputation {
+        member_id,
+        average_score: None,
+        total_ratings: 0,
+        histogram: (1..=5).map(|score| (score, 0)).collect(),
+    };
+    
+    for (score, count, average) in rows {
+        match score {
+            Some(score) => {
+                reputation.histogram.insert(score, count);
+            }
+            None => {
+                reputation.total_ratings = count;
+                reputation.average_score = average;
+            }
+        }
+    }
+    
+    Ok(reputation)
+}
+
+/// Validate that a rating score is between 1 and 5
+pub fn validate_score(score: i32) -> Result<(), ReputationError> {
+    if !(1..=5).contains(&score) {
+        return Err(ReputationError::InvalidRating(
+            "Score must be between 1 and 5".to_string(),
+        ));
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::test_utils::{insert_test_member, insert_test_transaction};
+    
+    async fn rate_member(pool: &PgPool, rated_id: Uuid, score: i32) {
+        let rater = insert_test_member(pool).await;
+        let transaction_id = insert_test_transaction(pool, rater, rated_id, TransactionStatus::Completed).await;
+        create_rating(pool, transaction_id, rater, rated_id, score).await.unwrap();
+    }
+    
+    #[test]
+    fn test_validate_score_bounds() {
+        assert!(validate_score(1).is_ok());
+        assert!(validate_score(5).is_ok());
+        assert!(matches!(validate_score(0), Err(ReputationError::InvalidRating(_))));
+        assert!(matches!(validate_score(6), Err(ReputationError::InvalidRating(_))));
+    }
+    
+    proptest! {
+        #[test]
+        fn test_validate_score_matches_range(score in -100i32..100) {
+            prop_assert_eq!(validate_score(score).is_ok(), (1..=5).contains(&score));
+        }
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_rating(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let transaction_id = insert_test_transaction(&pool, buyer, seller, TransactionStatus::Completed).await;
+        
+        let rating = create_rating(&pool, transaction_id, buyer, seller, 4).await.unwrap();
+        
+        assert_eq!(rating.transaction_id, transaction_id);
+        assert_eq!(rating.rater_id, buyer);
+        assert_eq!(rating.rated_id, seller);
+        assert_eq!(rating.score, 4);
+        
+        // The seller may rate the buyer for the same transaction
+        assert!(create_rating(&pool, transaction_id, seller, buyer, 5).await.is_ok());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_rating_invalid_score(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let transaction_id = insert_test_transaction(&pool, buyer, seller, TransactionStatus::Completed).await;
+        
+        let result = create_rating(&pool, transaction_id, buyer, seller, 6).await;
+        
+        assert!(matches!(result, Err(ReputationError::InvalidRating(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_rating_transaction_not_completed(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let transaction_id = insert_test_transaction(&pool, buyer, seller, TransactionStatus::Pending).await;
+        
+        let result = create_rating(&pool, transaction_id, buyer, seller, 4).await;
+        
+        assert!(matches!(result, Err(ReputationError::TransactionNotCompleted)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_rating_requires_participant(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let outsider = insert_test_member(&pool).await;
+        let transaction_id = insert_test_transaction(&pool, buyer, seller, TransactionStatus::Completed).await;
+        
+        let result = create_rating(&pool, transaction_id, outsider, seller, 4).await;
+        assert!(matches!(result, Err(ReputationError::NotParticipant)));
+        
+        // Participants cannot rate themselves or outsiders
+        let result = create_rating(&pool, transaction_id, buyer, buyer, 5).await;
+        assert!(matches!(result, Err(ReputationError::InvalidRating(_))));
+        let result = create_rating(&pool, transaction_id, buyer, outsider, 5).await;
+        assert!(matches!(result, Err(ReputationError::InvalidRating(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_rating_already_rated(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        let transaction_id = insert_test_transaction(&pool, buyer, seller, TransactionStatus::Completed).await;
+        
+        create_rating(&pool, transaction_id, buyer, seller, 4).await.unwrap();
+        let result = create_rating(&pool, transaction_id, buyer, seller, 2).await;
+        
+        assert!(matches!(result, Err(ReputationError::AlreadyRated)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_rating_unknown_transaction(pool: PgPool) {
+        let buyer = insert_test_member(&pool).await;
+        let seller = insert_test_member(&pool).await;
+        
+        let result = create_rating(&pool, Uuid::new_v4(), buyer, seller, 4).await;
+        
+        assert!(matches!(result, Err(ReputationError::TransactionNotFound)));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_get_member_reputation(pool: PgPool) {
+        let seller = insert_test_member(&pool).await;
+        for score in [5, 4, 4, 2] {
+            rate_member(&pool, seller, score).await;
+        }
+        // Ratings of other members are not counted
+        let other = insert_test_member(&pool).await;
+        rate_member(&pool, other, 1).await;
+        
+        let reputation = get_member_reputation(&pool, seller).await.unwrap();
+        
+        assert_eq!(reputation.member_id, seller);
+        assert_eq!(reputation.total_ratings, 4);
+        assert_eq!(reputation.average_score, Some(Decimal::new(375, 2)));
+        assert_eq!(
+            reputation.histogram,
+            BTreeMap::from([(1, 0), (2, 1), (3, 0), (4, 2), (5, 1)])
+        );
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_get_member_reputation_without_ratings(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        
+        let reputation = get_member_reputation(&pool, member).await.unwrap();
+        
+        assert_eq!(reputation.total_ratings, 0);
+        assert_eq!(reputation.average_score, None);
+        assert!(reputation.histogram.values().all(|&count| count == 0));
+        assert_eq!(reputation.histogram.len(), 5);
+    }
+}
@@ -1,45 +1,232 @@
 use axum::{
+    extract::FromRef,
+    http::{header, HeaderName, HeaderValue, Method},
     routing::{get, post, put, delete},
     Router,
 };
 use sqlx::PgPool;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
-use crate::handlers;
+use crate::{
+    config::Config,
+    handlers,
+    listings::ViewCounter,
+    middleware::{self, rate_limit::RateLimiter, request_id::REQUEST_ID_HEADER},
+};
+
+/// Shared state available to all handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: PgPool,
+    pub config: Config,
+    pub views: ViewCounter,
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Config {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
 
-pub fn create_router(pool: PgPool) -> Router {
-    // CORS configuration
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+impl FromRef<AppState> for ViewCounter {
+    fn from_ref(state: &AppState) -> Self {
+        state.views.clone()
+    }
+}
+
+/// Build the application router
+///
+/// Listing views are buffered in `views`; the caller is responsible for flushing it.
+pub fn create_router(pool: PgPool, config: Config, views: ViewCounter) -> Router {
+    let cors = cors_layer(&config);
+    let login_rate_limit = axum::middleware::from_fn_with_state(
+        RateLimiter::for_login(&config),
+        middleware::rate_limit::login_rate_limit,
+    );
 
     Router::new()
-        // Health check
-        .route("/health", get(health_check))
+        // Health checks
+        .route("/health", get(handlers::health::health))
+        .route("/health/live", get(handlers::health::live))
+        .route("/health/ready", get(handlers::health::ready))
+        
+        // Prometheus scrape endpoint
+        .route("/metrics", get(handlers::metrics::metrics))
         
         // Auth routes (public)
         .route("/api/auth/register", post(handlers::auth::register))
-        .route("/api/auth/login", post(handlers::auth::login))
+        .route("/api/auth/login", post(handlers::auth::login).layer(login_rate_limit))
+        .route("/api/auth/refresh", post(handlers::auth::refresh))
+        .route("/api/auth/logout", post(handlers::auth::logout))
         .route("/api/auth/profile", get(handlers::auth::get_profile))
         
+        // Member routes
+        .route("/api/members/me", delete(handlers::members::delete_me))
+        .route("/api/members/me/listings", get(handlers::listings::get_my_listings))
+        .route("/api/members/me/inventory", get(handlers::listings::get_my_inventory))
+        
         // Listing routes
         .route("/api/listings", get(handlers::listings::get_listings))
         .route("/api/listings", post(handlers::listings::create_listing))
+        .route("/api/listings/bulk", post(handlers::listings::create_listings_bulk))
+        .route("/api/listings/batch", post(handlers::listings::get_listings_batch))
         .route("/api/listings/:id", get(handlers::listings::get_listing))
         .route("/api/listings/:id", put(handlers::listings::update_listing))
         .route("/api/listings/:id", delete(handlers::listings::delete_listing))
+        .route("/api/listings/:id/restore", post(handlers::listings::restore_listing))
+        
+        // Category routes
+        .route("/api/categories", get(handlers::listings::get_categories))
+        
+        // Search routes
+        .route("/api/search", get(handlers::search::search))
+        .route("/api/search/suggest", get(handlers::search::suggest))
+        
+        // Saved search routes
+        .route("/api/saved-searches", get(handlers::listings::get_saved_searches))
+        .route("/api/saved-searches", post(handlers::listings::save_search))
+        .route("/api/saved-searches/:id", get(handlers::listings::get_saved_search))
+        .route("/api/saved-searches/:id", put(handlers::listings::update_saved_search))
+        .route("/api/saved-searches/:id", delete(handlers::listings::delete_saved_search))
+        .route("/api/saved-searches/:id/results", get(handlers::listings::run_saved_search))
         
         // Order routes
         .route("/api/orders", get(handlers::orders::get_my_orders))
         .route("/api/orders", post(handlers::orders::create_order))
+        .route("/api/orders/purchases", get(handlers::orders::get_my_purchases))
+        .route("/api/orders/sales", get(handlers::orders::get_my_sales))
         .route("/api/orders/:id", get(handlers::orders::get_order))
         .route("/api/orders/:id/status", put(handlers::orders::update_order_status))
+        .route("/api/orders/:id/history", get(handlers::orders::get_order_history))
+        .route("/api/orders/:id/messages", get(handlers::orders::get_order_messages))
+        .route("/api/orders/:id/messages", post(handlers::orders::send_order_message))
+        .route("/api/orders/:id/quote", get(handlers::orders::get_order_quote))
+        .route("/api/orders/:id/escrow", put(handlers::orders::set_escrow))
+        .route("/api/orders/:id/dispute", post(handlers::disputes::raise_dispute))
+        .route("/api/disputes/:id", get(handlers::disputes::get_dispute))
+        .route("/api/disputes/:id/resolve", post(handlers::disputes::resolve_dispute))
+        
+        // Governance routes
+        .route("/api/proposals", get(handlers::governance::get_proposals))
+        .route("/api/proposals", post(handlers::governance::create_proposal))
+        .route("/api/proposals/:id/vote", post(handlers::governance::cast_vote))
+        .route("/api/proposals/:id/finalize", post(handlers::governance::finalize_proposal))
+        .route("/api/proposals/:id/comments", get(handlers::governance::get_comments))
+        .route("/api/proposals/:id/comments", post(handlers::governance::add_comment))
+        
+        // Reputation routes
+        .route("/api/ratings", post(handlers::reputation::create_rating))
+        .route("/api/members/:id/reputation", get(handlers::reputation::get_member_reputation))
+        
+        // Notification routes
+        .route("/api/notifications", get(handlers::notifications::get_notifications))
+        .route("/api/notifications/stream", get(handlers::notifications::stream_notifications))
+        .route("/api/notifications/:id/read", put(handlers::notifications::mark_read))
+        
+        // Report routes
+        .route("/api/reports/sales", get(handlers::reports::sales_report))
+        .route("/api/reports/sales.csv", get(handlers::reports::export_sales_csv))
+        .route("/api/reports/earnings", get(handlers::reports::earnings))
+        .route("/api/reports/revenue", get(handlers::reports::platform_revenue))
+        
+        // Admin routes
+        .route("/api/admin/fees/:category", put(handlers::fees::set_category_fee))
+        .route("/api/admin/listings/:id/archive", post(handlers::listings::admin_archive_listing))
+        .route("/api/admin/audit", get(handlers::audit::get_audit_log))
         
         .layer(cors)
-        .with_state(pool)
+        .layer(axum::middleware::from_fn(middleware::metrics::track_metrics))
+        // Outermost, so every response (including CORS rejections) is tagged and logged
+        .layer(axum::middleware::from_fn(middleware::request_id::request_id))
+        .with_state(AppState { pool, config, views })
+}
+
+/// CORS policy for the configured origins
+///
+/// Without an allow-list every origin is accepted outside production, while
+/// production then rejects all cross-origin requests.
+fn cors_layer(config: &Config) -> CorsLayer {
+    if config.cors_allowed_origins.is_empty() && !config.is_production() {
+        return CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any);
+    }
+
+    // Invalid origins are rejected by Config::validate at startup
+    let origins: Vec<HeaderValue> = config
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| HeaderValue::from_str(origin).ok())
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static("last-event-id"),
+            REQUEST_ID_HEADER.clone(),
+        ])
+        .expose_headers([REQUEST_ID_HEADER.clone()])
+        .allow_credentials(true)
 }
 
-async fn health_check() -> &'static str {
-    "OK"
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_config;
+    use axum::{body::Body, http::Request};
+    use tower::{Layer, Service};
+
+    /// Origin reflected by the CORS layer for a request from `origin`, if any
+    async fn allowed_origin(config: &Config, origin: &str) -> Option<HeaderValue> {
+        let mut service = cors_layer(config).layer(Router::new().route("/health", get(handlers::health::live)));
+        let request = Request::builder()
+            .uri("/health")
+            .header(header::ORIGIN, origin)
+            .body(Body::empty())
+            .unwrap();
+
+        std::future::poll_fn(|cx| Service::<Request<Body>>::poll_ready(&mut service, cx)).await.unwrap();
+        let response = service.call(request).await.unwrap();
+
+        response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).cloned()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allow_list() {
+        let config = Config {
+            cors_allowed_origins: vec!["https://dofta.coop".to_string()],
+            ..test_config()
+        };
+
+        assert_eq!(
+            allowed_origin(&config, "https://dofta.coop").await,
+            Some(HeaderValue::from_static("https://dofta.coop"))
+        );
+        assert_eq!(allowed_origin(&config, "https://evil.example").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_cors_permissive_only_outside_production() {
+        let config = test_config();
+        assert_eq!(
+            allowed_origin(&config, "https://evil.example").await,
+            Some(HeaderValue::from_static("*"))
+        );
+
+        let config = Config {
+            app_env: "production".to_string(),
+            ..test_config()
+        };
+        assert_eq!(allowed_origin(&config, "https://evil.example").await, None);
+    }
 }
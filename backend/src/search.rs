@@ -0,0 +1,295 @@
+use crate::error::SearchError;
+use crate::models::AvailabilityStatus;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// Maximum length of a search query in characters
+pub const MAX_QUERY_LENGTH: usize = 200;
+
+/// Default number of hits returned per entity type
+pub const DEFAULT_RESULT_LIMIT: i64 = 20;
+
+/// Maximum number of hits returned per entity type
+pub const MAX_RESULT_LIMIT: i64 = 50;
+
+/// Default number of name suggestions returned
+pub const DEFAULT_SUGGESTION_LIMIT: i64 = 5;
+
+/// Maximum number of name suggestions returned
+pub const MAX_SUGGESTION_LIMIT: i64 = 10;
+
+/// Options narrowing a search
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilters {
+    /// Only match listings in this category
+    pub category: Option<String>,
+    /// Also search proposal titles
+    pub include_proposals: bool,
+    /// Hits per entity type, clamped to `1..=MAX_RESULT_LIMIT`
+    pub limit: Option<i64>,
+}
+
+/// A listing matching a search, with its relevance
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ListingHit {
+    pub id: Uuid,
+    pub name: String,
+    pub category: String,
+    pub unit_price: Decimal,
+    pub unit_of_measure: String,
+    pub rank: f32,
+}
+
+/// A proposal matching a search, with its relevance
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ProposalHit {
+    pub id: Uuid,
+    pub title: String,
+    pub status: String,
+    pub rank: f32,
+}
+
+/// Search hits grouped by entity type, each ordered by relevance
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResults {
+    pub query: String,
+    pub listings: Vec<ListingHit>,
+    /// Empty unless proposals were included in the search
+    pub proposals: Vec<ProposalHit>,
+}
+
+/// Search listings, and optionally proposals, ranked by relevance
+///
+/// Full-text matches rank by `ts_rank`; substring matches on the name or title
+/// are also returned so partial words still find something.
+pub async fn search(
+    pool: &PgPool,
+    query: &str,
+    filters: &SearchFilters,
+) -> Result<SearchResults, SearchError> {
+    validate_query(query)?;
+    
+    let query = query.trim();
+    let pattern = format!("%{}%", escape_like(query));
+    let limit = filters
+        .limit
+        .unwrap_or(DEFAULT_RESULT_LIMIT)
+        .clamp(1, MAX_RESULT_LIMIT);
+    
+    let listings = sqlx::query_as::<_, ListingHit>(
+        "SELECT id, name, category, unit_price, unit_of_measure,
+                ts_rank(search_vector, plainto_tsquery('english', $1)) AS rank
+         FROM product_listings
+         WHERE availability != $3
+           AND (expires_at IS NULL OR expires_at > NOW())
+           AND (search_vector @@ plainto_tsquery('english', $1) OR name ILIKE $2)
+           AND ($4::TEXT IS NULL OR category = $4)
+         ORDER BY rank DESC, name ILIKE $2 DESC, created_at DESC
+         LIMIT $5"
+    )
+    .bind(query)
+    .bind(&pattern)
+    .bind(AvailabilityStatus::Archived.to_string())
+    .bind(filters.category.as_deref())
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    
+    let proposals = if filters.include_proposals {
+        sqlx::query_as::<_, ProposalHit>(
+            "SELECT id, title, status,
+                    ts_rank(to_tsvector('english', title), plainto_tsquery('english', $1)) AS rank
+             FROM proposals
+             WHERE to_tsvector('english', title) @@ plainto_tsquery('english', $1) OR title ILIKE $2
+             ORDER BY rank DESC, created_at DESC
+             LIMIT $3"
+        )
+        .bind(query)
+        .bind(&pattern)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+    } else {
+        Vec::new()
+    };
+    
+    Ok(SearchResults {
+        query: query.to_string(),
+        listings,
+        proposals,
+    })
+}
+
+/// Suggest up to `limit` distinct names of non-archived listings starting with `prefix`
+pub async fn suggest(pool: &PgPool, prefix: &str, limit: i64) -> Result<Vec<String>, SearchError> {
+    validate_query(prefix)?;
+    
+    let pattern = format!("{}%", escape_like(prefix.trim()));
+    let limit = limit.clamp(1, MAX_SUGGESTION_LIMIT);
+    
+    let names: Vec<(String,)> = sqlx::query_as(
+        "SELECT DISTINCT name
+         FROM product_listings
+         WHERE availability != $1 AND (expires_at IS NULL OR expires_at > NOW()) AND name ILIKE $2
+         ORDER BY name
+         LIMIT $3"
+    )
+    .bind(AvailabilityStatus::Archived.to_string())
+    .bind(pattern)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(names.into_iter().map(|(name,)| name).collect())
+}
+
+/// Validate that a search query is non-empty and not too long
+pub fn validate_query(query: &str) -> Result<(), SearchError> {
+    if query.trim().is_empty() {
+        return Err(SearchError::InvalidQuery("Search query cannot be empty".to_string()));
+    }
+    
+    if query.trim().chars().count() > MAX_QUERY_LENGTH {
+        return Err(SearchError::InvalidQuery(format!(
+            "Search query cannot exceed {} characters",
+            MAX_QUERY_LENGTH
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Escape `LIKE` wildcards so user input matches literally
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::governance;
+    use crate::models::ProposalKind;
+    use crate::listings::{self, CreateListingData};
+    use crate::test_utils::{insert_test_listing, insert_test_member};
+    use chrono::Duration;
+    
+    async fn create_named_listing(pool: &PgPool, member_id: Uuid, name: &str) -> Uuid {
+        listings::create_listing(
+            pool,
+            member_id,
+            CreateListingData {
+                name: name.to_string(),
+                description: format!("Fresh {}", name),
+                category: "Vegetables".to_string(),
+                quantity_available: Decimal::new(50, 0),
+                unit_price: Decimal::new(50, 2),
+                unit_of_measure: "kg".to_string(),
+                expires_at: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+    }
+    
+    #[test]
+    fn test_validate_query() {
+        assert!(validate_query("tomatoes").is_ok());
+        assert!(matches!(validate_query(""), Err(SearchError::InvalidQuery(_))));
+        assert!(matches!(validate_query("   "), Err(SearchError::InvalidQuery(_))));
+        assert!(matches!(
+            validate_query(&"a".repeat(MAX_QUERY_LENGTH + 1)),
+            Err(SearchError::InvalidQuery(_))
+        ));
+    }
+    
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("tomatoes"), "tomatoes");
+        assert_eq!(escape_like("100%_pure\\"), "100\\%\\_pure\\\\");
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_search_rejects_empty_query(pool: PgPool) {
+        let result = search(&pool, "  ", &SearchFilters::default()).await;
+        
+        assert!(matches!(result, Err(SearchError::InvalidQuery(_))));
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_search_matches_listings_and_proposals(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        let tomatoes = insert_test_listing(&pool, member).await;
+        let archived = insert_test_listing(&pool, member).await;
+        listings::delete_listing(&pool, archived, member).await.unwrap();
+        create_named_listing(&pool, member, "Sweet Corn").await;
+        let proposal = governance::create_proposal(
+            &pool,
+            member,
+            "Shared tomato greenhouse",
+            "Build a greenhouse for early tomatoes",
+            Duration::days(7),
+            Decimal::ZERO,
+            false,
+            ProposalKind::General,
+        )
+        .await
+        .unwrap();
+        
+        let filters = SearchFilters {
+            include_proposals: true,
+            ..Default::default()
+        };
+        let results = search(&pool, "tomatoes", &filters).await.unwrap();
+        
+        // Archived listings and non-matching listings are excluded
+        let listing_ids: Vec<Uuid> = results.listings.iter().map(|hit| hit.id).collect();
+        assert_eq!(listing_ids, vec![tomatoes]);
+        assert!(results.listings[0].rank > 0.0);
+        // Stemming matches "tomato" in the proposal title
+        assert_eq!(results.proposals.len(), 1);
+        assert_eq!(results.proposals[0].id, proposal.id);
+        
+        // Proposals are only searched on request, and partial words still match
+        let results = search(&pool, "Swe", &SearchFilters::default()).await.unwrap();
+        assert_eq!(results.listings.len(), 1);
+        assert_eq!(results.listings[0].name, "Sweet Corn");
+        assert!(results.proposals.is_empty());
+        
+        let filters = SearchFilters {
+            category: Some("Fruit".to_string()),
+            ..Default::default()
+        };
+        assert!(search(&pool, "tomatoes", &filters).await.unwrap().listings.is_empty());
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_suggest_listing_names(pool: PgPool) {
+        let member = insert_test_member(&pool).await;
+        for name in ["Tomatillos", "Tomato Seedlings", "Tomato Seedlings", "Sweet Corn", "Tom Thumb Lettuce"] {
+            create_named_listing(&pool, member, name).await;
+        }
+        let archived = create_named_listing(&pool, member, "Tomato Paste").await;
+        listings::delete_listing(&pool, archived, member).await.unwrap();
+        
+        // Matching is case-insensitive and on the start of the name only
+        let names = suggest(&pool, "toma", 10).await.unwrap();
+        assert_eq!(names, vec!["Tomatillos", "Tomato Seedlings"]);
+        
+        let names = suggest(&pool, "Tom", 2).await.unwrap();
+        assert_eq!(names, vec!["Tom Thumb Lettuce", "Tomatillos"]);
+        
+        // The limit is clamped to the maximum
+        for i in 0..MAX_SUGGESTION_LIMIT + 2 {
+            create_named_listing(&pool, member, &format!("Pepper {:02}", i)).await;
+        }
+        assert_eq!(suggest(&pool, "pepper", 100).await.unwrap().len() as i64, MAX_SUGGESTION_LIMIT);
+        
+        assert!(matches!(suggest(&pool, "", 5).await, Err(SearchError::InvalidQuery(_))));
+    }
+}
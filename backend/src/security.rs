@@ -0,0 +1,190 @@
+use crate::config::Config;
+use crate::error::SecurityError;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::collections::HashMap;
+
+/// Length in bytes of a field encryption key
+pub const KEY_LENGTH: usize = 32;
+
+/// Keys for field-level encryption, looked up by the ID stored with each value
+///
+/// New values are always sealed with the current key; retired keys are only
+/// used to open values written before the last rotation.
+#[derive(Clone)]
+pub struct FieldKeys {
+    current_id: String,
+    keys: HashMap<String, [u8; KEY_LENGTH]>,
+}
+
+// Written by hand so key material never ends up in logs
+impl std::fmt::Debug for FieldKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldKeys")
+            .field("current_id", &self.current_id)
+            .field("keys", &"<redacted>")
+            .finish()
+    }
+}
+
+impl FieldKeys {
+    /// Build the key set from the configured current and retired keys
+    pub fn from_config(config: &Config) -> Result<Self, SecurityError> {
+        let mut keys = HashMap::new();
+        keys.insert(config.encryption_key_id.clone(), decode_key(&config.encryption_key)?);
+        
+        for (id, key) in &config.encryption_retired_keys {
+            if keys.insert(id.clone(), decode_key(key)?).is_some() {
+                return Err(SecurityError::EncryptionFailed(format!("Duplicate key ID {}", id)));
+            }
+        }
+        
+        Ok(Self {
+            current_id: config.encryption_key_id.clone(),
+            keys,
+        })
+    }
+    
+    /// ID of the key new values are encrypted with
+    pub fn current_id(&self) -> &str {
+        &self.current_id
+    }
+    
+    fn key(&self, key_id: &str) -> Option<LessSafeKey> {
+        let bytes = self.keys.get(key_id)?;
+        // A 32-byte key is always valid for AES-256-GCM
+        UnboundKey::new(&AES_256_GCM, bytes).ok().map(LessSafeKey::new)
+    }
+}
+
+/// An encrypted field value, with everything needed to decrypt it again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncryptedField {
+    pub key_id: String,
+    pub nonce: Vec<u8>,
+    /// Ciphertext followed by the authentication tag
+    pub ciphertext: Vec<u8>,
+}
+
+/// Encrypt a field with AES-256-GCM under the current key and a random nonce
+pub fn encrypt_field(keys: &FieldKeys, plaintext: &str) -> Result<EncryptedField, SecurityError> {
+    let key = keys
+        .key(&keys.current_id)
+        .ok_or_else(|| SecurityError::EncryptionFailed("Current key is missing".to_string()))?;
+    
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| SecurityError::EncryptionFailed("Could not generate a nonce".to_string()))?;
+    
+    // The key ID is authenticated too, so a value can't be relabelled
+    let mut ciphertext = plaintext.as_bytes().to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(keys.current_id.as_bytes()),
+        &mut ciphertext,
+    )
+    .map_err(|_| SecurityError::EncryptionFailed("Could not seal value".to_string()))?;
+    
+    Ok(EncryptedField {
+        key_id: keys.current_id.clone(),
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+/// Decrypt a field, failing with `DecryptionFailed` on an unknown key or tampered value
+pub fn decrypt_field(keys: &FieldKeys, field: &EncryptedField) -> Result<String, SecurityError> {
+    let key = keys
+        .key(&field.key_id)
+        .ok_or_else(|| SecurityError::DecryptionFailed(format!("Unknown key ID {}", field.key_id)))?;
+    let nonce = Nonce::try_assume_unique_for_key(&field.nonce)
+        .map_err(|_| SecurityError::DecryptionFailed("Invalid nonce".to_string()))?;
+    
+    let mut buffer = field.ciphertext.clone();
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(field.key_id.as_bytes()), &mut buffer)
+        .map_err(|_| SecurityError::DecryptionFailed("Value could not be authenticated".to_string()))?;
+    
+    String::from_utf8(plaintext.to_vec())
+        .map_err(|_| SecurityError::DecryptionFailed("Value is not valid UTF-8".to_string()))
+}
+
+/// Decode a base64 key, checking its length
+fn decode_key(key: &str) -> Result<[u8; KEY_LENGTH], SecurityError> {
+    STANDARD
+        .decode(key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SecurityError::EncryptionFailed(format!("Key must be {} base64-encoded bytes", KEY_LENGTH)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_config;
+    
+    const OTHER_KEY: &str = "cmV0aXJlZC1lbmNyeXB0aW9uLWtleS0zMi1ieXRlcyE=";
+    
+    fn test_keys() -> FieldKeys {
+        FieldKeys::from_config(&test_config()).unwrap()
+    }
+    
+    #[test]
+    fn test_encrypt_decrypt_round_trip() {
+        let keys = test_keys();
+        
+        let first = encrypt_field(&keys, "Green Acres, Nakuru").unwrap();
+        let second = encrypt_field(&keys, "Green Acres, Nakuru").unwrap();
+        
+        assert_eq!(first.key_id, keys.current_id());
+        assert_ne!(first.nonce, second.nonce);
+        assert!(!String::from_utf8_lossy(&first.ciphertext).contains("Green Acres"));
+        assert_eq!(decrypt_field(&keys, &first).unwrap(), "Green Acres, Nakuru");
+        assert_eq!(decrypt_field(&keys, &second).unwrap(), "Green Acres, Nakuru");
+        assert_eq!(decrypt_field(&keys, &encrypt_field(&keys, "").unwrap()).unwrap(), "");
+    }
+    
+    #[test]
+    fn test_wrong_key_fails_decryption() {
+        let field = encrypt_field(&test_keys(), "Nakuru").unwrap();
+        
+        // Same key ID, different key material
+        let wrong = FieldKeys::from_config(&Config {
+            encryption_key: OTHER_KEY.to_string(),
+            ..test_config()
+        })
+        .unwrap();
+        assert!(matches!(decrypt_field(&wrong, &field), Err(SecurityError::DecryptionFailed(_))));
+        
+        let unknown = EncryptedField {
+            key_id: "unknown".to_string(),
+            ..field.clone()
+        };
+        assert!(matches!(decrypt_field(&test_keys(), &unknown), Err(SecurityError::DecryptionFailed(_))));
+        
+        let mut tampered = field;
+        tampered.ciphertext[0] ^= 1;
+        assert!(matches!(decrypt_field(&test_keys(), &tampered), Err(SecurityError::DecryptionFailed(_))));
+    }
+    
+    #[test]
+    fn test_retired_key_still_decrypts() {
+        let old = FieldKeys::from_config(&Config {
+            encryption_key: OTHER_KEY.to_string(),
+            encryption_key_id: "old".to_string(),
+            ..test_config()
+        })
+        .unwrap();
+        let field = encrypt_field(&old, "Nakuru").unwrap();
+        
+        let rotated = FieldKeys::from_config(&Config {
+            encryption_retired_keys: vec![("old".to_string(), OTHER_KEY.to_string())],
+            ..test_config()
+        })
+        .unwrap();
+        assert_eq!(decrypt_field(&rotated, &field).unwrap(), "Nakuru");
+        assert_eq!(encrypt_field(&rotated, "Nakuru").unwrap().key_id, "test");
+    }
+}
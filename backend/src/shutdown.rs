@@ -0,0 +1,128 @@
+use axum::Router;
+use std::{future::Future, net::SocketAddr};
+use tokio::net::TcpListener;
+
+/// Resolve when the process receives SIGINT or SIGTERM
+pub async fn signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("🛑 SIGINT received, shutting down"),
+        _ = terminate => tracing::info!("🛑 SIGTERM received, shutting down"),
+    }
+}
+
+/// Serve `app` until `shutdown` resolves, then drain in-flight requests
+///
+/// Once the signal fires the listener is closed, so new connections are
+/// refused while requests already being handled run to completion.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    // Client addresses are needed to rate limit logins per IP
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            shutdown.await;
+            tracing::info!("Refusing new connections, draining in-flight requests");
+        })
+        .await?;
+    
+    tracing::info!("✅ In-flight requests drained");
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use std::{sync::Arc, time::Duration};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+        sync::{oneshot, Mutex, Notify},
+    };
+    
+    #[tokio::test]
+    async fn test_in_flight_request_completes_after_shutdown() {
+        let started = Arc::new(Notify::new());
+        let (release_tx, release_rx) = oneshot::channel::<()>();
+        let release_rx = Arc::new(Mutex::new(Some(release_rx)));
+        
+        let app = {
+            let started = started.clone();
+            Router::new().route(
+                "/slow",
+                get(move || async move {
+                    started.notify_one();
+                    let release = release_rx.lock().await.take().unwrap();
+                    release.await.unwrap();
+                    "done"
+                }),
+            )
+        };
+        
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve(listener, app, async move {
+            shutdown_rx.await.ok();
+        }));
+        
+        let mut in_flight = TcpStream::connect(addr).await.unwrap();
+        in_flight
+            .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        started.notified().await;
+        
+        shutdown_tx.send(()).unwrap();
+        
+        // The listener closes as soon as the signal is seen
+        let mut refused = false;
+        for _ in 0..50 {
+            if TcpStream::connect(addr).await.is_err() {
+                refused = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(refused, "new connections should be refused during shutdown");
+        assert!(!server.is_finished());
+        
+        release_tx.send(()).unwrap();
+        let mut response = String::new();
+        in_flight.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("done"));
+        
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+}
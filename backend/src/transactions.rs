@@ -0,0 +1,377 @@
+use crate::error::TransactionError;
+use crate::fees;
+use crate::models::{Order, Transaction, TransactionStatus};
+use chrono::Utc;
+use crate::money;
+use rust_decimal::Decimal;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Record a pending transaction for an order's total, withholding the cooperative fee
+///
+/// The fee is the override for the listing's category, or `default_fee_percentage`.
+/// Takes a connection so completing an order can record it in the same transaction.
+pub async fn create_transaction(
+    conn: &mut PgConnection,
+    order_id: Uuid,
+    default_fee_percentage: Decimal,
+) -> Result<Transaction, TransactionError> {
+    let order = sqlx::query_as::<_, Order>(
+        "SELECT id, buyer_id, seller_id, product_listing_id, quantity, total_amount, status, escrow_order_id, seller_note, created_at
+         FROM orders
+         WHERE id = $1"
+    )
+    .bind(order_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| TransactionError::Failed("Order not found".to_string()))?;
+    
+    if order.total_amount <= Decimal::ZERO {
+        return Err(TransactionError::InvalidAmount);
+    }
+    
+    let fee_percentage = fees::resolve_listing_fee(conn, order.product_listing_id, default_fee_percentage).await?;
+    let cooperative_fee = calculate_cooperative_fee(order.total_amount, fee_percentage);
+    
+    let transaction = sqlx::query_as::<_, Transaction>(
+        "INSERT INTO transactions (id, order_id, amount, cooperative_fee, status, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, order_id, amount, cooperative_fee, status, created_at, completed_at, reversal_reason, reversed_at"
+    )
+    .bind(Uuid::new_v4())
+    .bind(order_id)
+    .bind(order.total_amount)
+    .bind(cooperative_fee)
+    .bind(TransactionStatus::Pending.to_string())
+    .bind(Utc::now())
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    Ok(transaction)
+}
+
+/// Calculate the cooperative fee on an amount, rounded to the cent
+///
+/// Uses [`money::split_fee`], so the fee and the seller's share add up to the amount.
+pub fn calculate_cooperative_fee(amount: Decimal, fee_percentage: Decimal) -> Decimal {
+    money::split_fee(amount, fee_percentage).0
+}
+
+/// Get a transaction by ID
+pub async fn get_transaction(
+    pool: &PgPool,
+    transaction_id: Uuid,
+) -> Result<Transaction, TransactionError> {
+    let transaction = sqlx::query_as::<_, Transaction>(
+        "SELECT id, order_id, amount, cooperative_fee, status, created_at, completed_at, reversal_reason, reversed_at
+         FROM transactions
+         WHERE id = $1"
+    )
+    .bind(transaction_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(TransactionError::NotFound)?;
+    
+    Ok(transaction)
+}
+
+/// Mark a pending transaction as completed
+pub async fn complete_transaction(
+    pool: &PgPool,
+    transaction_id: Uuid,
+) -> Result<Transaction, TransactionError> {
+    let mut conn = pool.acquire().await?;
+    
+    mark_completed(&mut conn, transaction_id).await
+}
+
+/// Mark a pending transaction as completed on the caller's connection
+///
+/// Lets completing an order settle its transaction in the same database transaction.
+pub(crate) async fn mark_completed(
+    conn: &mut PgConnection,
+    transaction_id: Uuid,
+) -> Result<Transaction, TransactionError> {
+    let transaction = sqlx::query_as::<_, Transaction>(
+        "UPDATE transactions SET status = $1, completed_at = $2
+         WHERE id = $3 AND status = $4
+         RETURNING id, order_id, amount, cooperative_fee, status, created_at, completed_at, reversal_reason, reversed_at"
+    )
+    .bind(TransactionStatus::Completed.to_string())
+    .bind(Utc::now())
+    .bind(transaction_id)
+    .bind(TransactionStatus::Pending.to_string())
+    .fetch_optional(&mut *conn)
+    .await?;
+    
+    match transaction {
+        Some(transaction) => Ok(transaction),
+        None => {
+            // Distinguish a missing transaction from one that is no longer pending
+            let (status,): (String,) = sqlx::query_as("SELECT status FROM transactions WHERE id = $1")
+                .bind(transaction_id)
+                .fetch_optional(&mut *conn)
+                .await?
+                .ok_or(TransactionError::NotFound)?;
+            Err(TransactionError::Failed(format!(
+                "Cannot complete a {} transaction",
+                status
+            )))
+        }
+    }
+}
+
+/// Reverse a completed transaction, keeping it on record with the reason
+pub async fn reverse_transaction(
+    pool: &PgPool,
+    transaction_id: Uuid,
+    reason: &str,
+) -> Result<Transaction, TransactionError> {
+    let transaction = sqlx::query_as::<_, Transaction>(
+        "UPDATE transactions SET status = $1, reversal_reason = $2, reversed_at = $3
+         WHERE id = $4 AND status = $5
+         RETURNING id, order_id, amount, cooperative_fee, status, created_at, completed_at, reversal_reason, reversed_at"
+    )
+    .bind(TransactionStatus::Reversed.to_string())
+    .bind(reason)
+    .bind(Utc::now())
+    .bind(transaction_id)
+    .bind(TransactionStatus::Completed.to_string())
+    .fetch_optional(pool)
+    .await?;
+    
+    match transaction {
+        Some(transaction) => Ok(transaction),
+        None => {
+            let existing = get_transaction(pool, transaction_id).await?;
+            Err(TransactionError::RollbackFailed(format!(
+                "Only completed transactions can be reversed, this one is {}",
+                existing.status
+            )))
+        }
+    }
+}
+
+/// Total cooperative fees collected, excluding reversed transactions
+pub async fn get_cooperative_fee_total(pool: &PgPool) -> Result<Decimal, TransactionError> {
+    let (total,): (Option<Decimal>,) = sqlx::query_as(
+        "SELECT SUM(cooperative_fee) FROM transactions WHERE status = $1"
+    )
+    .bind(TransactionStatus::Completed.to_string())
+    .fetch_one(pool)
+    .await?;
+    
+    Ok(total.unwrap_or(Decimal::ZERO))
+}
+
+/// Get all transactions for an order, oldest first
+pub async fn get_transactions_by_order(
+    pool: &PgPool,
+    order_id: Uuid,
+) -> Result<Vec<Transaction>, TransactionError> {
+    let transactions = sqlx::query_as::<_, Transaction>(
+        "SELECT id, order_id, amount, cooperative_fee, status, created_at, completed_at, reversal_reason, reversed_at
+         FROM transactions
+         WHERE order_id = $1
+         ORDER BY created_at ASC"
+    )
+    .bind(order_id)
+    .fetch_all(pool)
+    .await?;
+    
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use crate::error::OrderError;
+    use crate::models::NotificationType;
+    use crate::orders::{self, CreateOrderData};
+    use crate::test_utils::{insert_test_listing, insert_test_member, test_config};
+    
+    async fn create_test_order(pool: &PgPool) -> Order {
+        let buyer = insert_test_member(pool).await;
+        let seller = insert_test_member(pool).await;
+        let listing_id = insert_test_listing(pool, seller).await;
+        
+        orders::create_order(
+            pool,
+            buyer,
+            CreateOrderData {
+                listing_id,
+                quantity: Decimal::new(4, 0),
+            },
+            test_config().max_order_quantity,
+            test_config().duplicate_order_window_secs,
+        )
+        .await
+        .unwrap()
+    }
+    
+    #[test]
+    fn test_calculate_cooperative_fee() {
+        assert_eq!(calculate_cooperative_fee(Decimal::new(10000, 2), Decimal::new(5, 2)), Decimal::new(500, 2));
+        // 11.96 * 0.05 = 0.598 rounds to 0.60
+        assert_eq!(calculate_cooperative_fee(Decimal::new(1196, 2), Decimal::new(5, 2)), Decimal::new(60, 2));
+        // Ties round to the even cent: 0.10 * 0.05 = 0.005 and 0.30 * 0.05 = 0.015
+        assert_eq!(calculate_cooperative_fee(Decimal::new(10, 2), Decimal::new(5, 2)), Decimal::ZERO);
+        assert_eq!(calculate_cooperative_fee(Decimal::new(30, 2), Decimal::new(5, 2)), Decimal::new(2, 2));
+        assert_eq!(calculate_cooperative_fee(Decimal::new(1196, 2), Decimal::ZERO), Decimal::ZERO);
+    }
+    
+    proptest! {
+        #[test]
+        fn test_cooperative_fee_never_exceeds_amount(
+            cents in 1i64..10_000_000,
+            basis_points in 0i64..=10_000,
+        ) {
+            let amount = Decimal::new(cents, 2);
+            let fee = calculate_cooperative_fee(amount, Decimal::new(basis_points, 4));
+            
+            prop_assert!(fee >= Decimal::ZERO);
+            prop_assert!(fee <= amount);
+            prop_assert!(fee.scale() <= 2);
+        }
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_create_and_complete_transaction(pool: PgPool) {
+        let order = create_test_order(&pool).await;
+        
+        let mut conn = pool.acquire().await.unwrap();
+        let transaction = create_transaction(&mut conn, order.id, Decimal::new(5, 2)).await.unwrap();
+        
+        // 4 x 2.99 = 11.96, with a 5% fee of 0.60
+        assert_eq!(transaction.amount, Decimal::new(1196, 2));
+        assert_eq!(transaction.cooperative_fee, Decimal::new(60, 2));
+        assert_eq!(transaction.status, "Pending");
+        assert!(transaction.completed_at.is_none());
+        
+        let completed = complete_transaction(&pool, transaction.id).await.unwrap();
+        assert_eq!(completed.status, "Completed");
+        assert!(completed.completed_at.is_some());
+        
+        // Only pending transactions can be completed
+        let result = complete_transaction(&pool, transaction.id).await;
+        assert!(matches!(result, Err(TransactionError::Failed(_))));
+        let result = complete_transaction(&pool, Uuid::new_v4()).await;
+        assert!(matches!(result, Err(TransactionError::NotFound)));
+    }
+    
+    async fn count_completion_notifications(pool: &PgPool, order: &Order) -> i64 {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM notifications
+             WHERE recipient_id IN ($1, $2) AND notification_type = $3"
+        )
+        .bind(order.buyer_id)
+        .bind(order.seller_id)
+        .bind(NotificationType::OrderStatusChanged.to_string())
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_completing_order_creates_one_transaction(pool: PgPool) {
+        let order = create_test_order(&pool).await;
+        assert!(get_transactions_by_order(&pool, order.id).await.unwrap().is_empty());
+        
+        orders::accept_order(&pool, order.id, order.seller_id, None).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        let completed = orders::complete_order(&mut tx, order.id, order.buyer_id, Decimal::new(5, 2)).await.unwrap();
+        tx.commit().await.unwrap();
+        
+        let transactions = get_transactions_by_order(&pool, order.id).await.unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].id, completed.transaction.id);
+        assert_eq!(transactions[0].amount, order.total_amount);
+        assert_eq!(transactions[0].status, "Completed");
+        assert!(transactions[0].completed_at.is_some());
+        assert_eq!(completed.transaction.status, "Completed");
+        
+        // Both parties are told, once each
+        let recipients: Vec<Uuid> = completed.notifications.iter().map(|n| n.recipient_id).collect();
+        assert_eq!(recipients, vec![order.buyer_id, order.seller_id]);
+        assert_eq!(count_completion_notifications(&pool, &order).await, 2);
+        
+        // A second completion is rejected and records nothing
+        let mut tx = pool.begin().await.unwrap();
+        let result = orders::complete_order(&mut tx, order.id, order.buyer_id, Decimal::new(5, 2)).await;
+        assert!(result.is_err());
+        drop(tx);
+        assert_eq!(get_transactions_by_order(&pool, order.id).await.unwrap().len(), 1);
+        assert_eq!(count_completion_notifications(&pool, &order).await, 2);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_failed_completion_rolls_back(pool: PgPool) {
+        let order = create_test_order(&pool).await;
+        orders::accept_order(&pool, order.id, order.seller_id, None).await.unwrap();
+        
+        // A zero total can't be recorded as a transaction, after the status has changed
+        sqlx::query("UPDATE orders SET total_amount = 0 WHERE id = $1")
+            .bind(order.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        
+        let mut tx = pool.begin().await.unwrap();
+        let result = orders::complete_order(&mut tx, order.id, order.buyer_id, Decimal::new(5, 2)).await;
+        assert!(matches!(result, Err(OrderError::Transaction(TransactionError::InvalidAmount))));
+        drop(tx);
+        
+        let reloaded = orders::get_order(&pool, order.id).await.unwrap();
+        assert_eq!(reloaded.status, "Accepted");
+        assert_eq!(orders::get_order_history(&pool, order.id).await.unwrap().len(), 1);
+        assert!(get_transactions_by_order(&pool, order.id).await.unwrap().is_empty());
+        assert_eq!(count_completion_notifications(&pool, &order).await, 0);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_reverse_completed_transaction(pool: PgPool) {
+        let order = create_test_order(&pool).await;
+        let mut conn = pool.acquire().await.unwrap();
+        let transaction = create_transaction(&mut conn, order.id, Decimal::new(5, 2)).await.unwrap();
+        complete_transaction(&pool, transaction.id).await.unwrap();
+        assert_eq!(get_cooperative_fee_total(&pool).await.unwrap(), Decimal::new(60, 2));
+        
+        let reversed = reverse_transaction(&pool, transaction.id, "Refunded after dispute").await.unwrap();
+        
+        assert_eq!(reversed.status, "Reversed");
+        assert_eq!(reversed.reversal_reason.as_deref(), Some("Refunded after dispute"));
+        assert!(reversed.reversed_at.is_some());
+        // The record is kept, but its fee no longer counts
+        assert_eq!(get_transactions_by_order(&pool, order.id).await.unwrap().len(), 1);
+        assert_eq!(get_cooperative_fee_total(&pool).await.unwrap(), Decimal::ZERO);
+    }
+    
+    #[sqlx::test]
+    #[ignore = "requires a Postgres DATABASE_URL"]
+    async fn test_reverse_transaction_rejects_other_states(pool: PgPool) {
+        for status in [TransactionStatus::Pending, TransactionStatus::Failed, TransactionStatus::Reversed] {
+            let order = create_test_order(&pool).await;
+            let mut conn = pool.acquire().await.unwrap();
+            let transaction = create_transaction(&mut conn, order.id, Decimal::new(5, 2)).await.unwrap();
+            sqlx::query("UPDATE transactions SET status = $1 WHERE id = $2")
+                .bind(status.to_string())
+                .bind(transaction.id)
+                .execute(&pool)
+                .await
+                .unwrap();
+            
+            let result = reverse_transaction(&pool, transaction.id, "Refund").await;
+            
+            assert!(matches!(result, Err(TransactionError::RollbackFailed(_))), "{} was reversed", status);
+            assert_eq!(get_transaction(&pool, transaction.id).await.unwrap().status, status.to_string());
+        }
+        
+        let result = reverse_transaction(&pool, Uuid::new_v4(), "Refund").await;
+        assert!(matches!(result, Err(TransactionError::NotFound)));
+    }
+}
@@ -0,0 +1,134 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Canonical unit a listing is sold by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitOfMeasure {
+    Kg,
+    Gram,
+    Litre,
+    Piece,
+    Dozen,
+}
+
+/// What a unit measures; only quantities of the same dimension convert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Mass,
+    Volume,
+    Count,
+}
+
+impl UnitOfMeasure {
+    /// Every unit, in display order
+    pub const ALL: [UnitOfMeasure; 5] = [
+        UnitOfMeasure::Kg,
+        UnitOfMeasure::Gram,
+        UnitOfMeasure::Litre,
+        UnitOfMeasure::Piece,
+        UnitOfMeasure::Dozen,
+    ];
+    
+    /// What this unit measures
+    pub fn dimension(self) -> Dimension {
+        match self {
+            UnitOfMeasure::Kg | UnitOfMeasure::Gram => Dimension::Mass,
+            UnitOfMeasure::Litre => Dimension::Volume,
+            UnitOfMeasure::Piece | UnitOfMeasure::Dozen => Dimension::Count,
+        }
+    }
+    
+    /// Whether the unit is sold whole, so quantities of it must be whole numbers
+    pub fn is_discrete(self) -> bool {
+        self.dimension() == Dimension::Count
+    }
+    
+    /// Size of one unit in its dimension's base unit (grams, litres or pieces)
+    fn base_factor(self) -> Decimal {
+        match self {
+            UnitOfMeasure::Kg => Decimal::from(1000),
+            UnitOfMeasure::Dozen => Decimal::from(12),
+            UnitOfMeasure::Gram | UnitOfMeasure::Litre | UnitOfMeasure::Piece => Decimal::ONE,
+        }
+    }
+}
+
+impl std::fmt::Display for UnitOfMeasure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnitOfMeasure::Kg => write!(f, "kg"),
+            UnitOfMeasure::Gram => write!(f, "g"),
+            UnitOfMeasure::Litre => write!(f, "litre"),
+            UnitOfMeasure::Piece => write!(f, "piece"),
+            UnitOfMeasure::Dozen => write!(f, "dozen"),
+        }
+    }
+}
+
+impl std::str::FromStr for UnitOfMeasure {
+    type Err = String;
+    
+    /// Parse a unit case-insensitively, accepting common spellings and plurals
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "kg" | "kgs" | "kilo" | "kilos" | "kilogram" | "kilograms" => Ok(UnitOfMeasure::Kg),
+            "g" | "gram" | "grams" => Ok(UnitOfMeasure::Gram),
+            "l" | "litre" | "litres" | "liter" | "liters" => Ok(UnitOfMeasure::Litre),
+            "piece" | "pieces" | "pc" | "pcs" | "each" | "ea" => Ok(UnitOfMeasure::Piece),
+            "dozen" | "dozens" | "doz" => Ok(UnitOfMeasure::Dozen),
+            _ => Err(format!("Invalid unit of measure: {}", s)),
+        }
+    }
+}
+
+/// Convert a quantity between two units of the same dimension
+///
+/// Mass converts to mass, volume to volume and counts to counts; anything else
+/// is an error since, say, kilograms and litres can't be compared.
+pub fn convert(quantity: Decimal, from: UnitOfMeasure, to: UnitOfMeasure) -> Result<Decimal, String> {
+    if from.dimension() != to.dimension() {
+        return Err(format!("Cannot convert {} to {}", from, to));
+    }
+    
+    Ok(quantity * from.base_factor() / to.base_factor())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_unit_aliases() {
+        assert_eq!("Kg".parse::<UnitOfMeasure>(), Ok(UnitOfMeasure::Kg));
+        assert_eq!(" kilogram ".parse::<UnitOfMeasure>(), Ok(UnitOfMeasure::Kg));
+        assert_eq!("Liters".parse::<UnitOfMeasure>(), Ok(UnitOfMeasure::Litre));
+        assert_eq!("each".parse::<UnitOfMeasure>(), Ok(UnitOfMeasure::Piece));
+        assert!("bushel".parse::<UnitOfMeasure>().is_err());
+        
+        for unit in UnitOfMeasure::ALL {
+            assert_eq!(unit.to_string().parse::<UnitOfMeasure>(), Ok(unit));
+        }
+    }
+    
+    #[test]
+    fn test_is_discrete() {
+        assert!(UnitOfMeasure::Piece.is_discrete());
+        assert!(UnitOfMeasure::Dozen.is_discrete());
+        assert!(!UnitOfMeasure::Kg.is_discrete());
+        assert!(!UnitOfMeasure::Litre.is_discrete());
+    }
+    
+    #[test]
+    fn test_convert_compatible_units() {
+        assert_eq!(convert(Decimal::new(25, 1), UnitOfMeasure::Kg, UnitOfMeasure::Gram), Ok(Decimal::from(2500)));
+        assert_eq!(convert(Decimal::from(250), UnitOfMeasure::Gram, UnitOfMeasure::Kg), Ok(Decimal::new(25, 2)));
+        assert_eq!(convert(Decimal::from(3), UnitOfMeasure::Dozen, UnitOfMeasure::Piece), Ok(Decimal::from(36)));
+        assert_eq!(convert(Decimal::from(4), UnitOfMeasure::Litre, UnitOfMeasure::Litre), Ok(Decimal::from(4)));
+    }
+    
+    #[test]
+    fn test_convert_incompatible_units() {
+        assert!(convert(Decimal::ONE, UnitOfMeasure::Kg, UnitOfMeasure::Litre).is_err());
+        assert!(convert(Decimal::ONE, UnitOfMeasure::Piece, UnitOfMeasure::Gram).is_err());
+    }
+}
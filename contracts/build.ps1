@@ -16,4 +16,4 @@ Write-Host "📦 WASM file: res/dofta_marketplace.wasm" -ForegroundColor Yellow
 Write-Host ""
 Write-Host "Next steps:" -ForegroundColor Cyan
 Write-Host "1. Deploy to testnet: near deploy --accountId YOUR_ACCOUNT.testnet --wasmFile res/dofta_marketplace.wasm"
-Write-Host "2. Initialize: near call YOUR_ACCOUNT.testnet new '{`"owner`": `"YOUR_ACCOUNT.testnet`", `"platform_fee_percentage`": 2}' --accountId YOUR_ACCOUNT.testnet"
+Write-Host "2. Initialize: near call YOUR_ACCOUNT.testnet new '{`"owner`": `"YOUR_ACCOUNT.testnet`", `"platform_fee_percentage`": 2, `"order_timeout_ns`": 604800000000000}' --accountId YOUR_ACCOUNT.testnet"
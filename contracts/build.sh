@@ -18,4 +18,4 @@ echo "📦 WASM file: res/dofta_marketplace.wasm"
 echo ""
 echo "Next steps:"
 echo "1. Deploy to testnet: near deploy --accountId YOUR_ACCOUNT.testnet --wasmFile res/dofta_marketplace.wasm"
-echo "2. Initialize: near call YOUR_ACCOUNT.testnet new '{\"owner\": \"YOUR_ACCOUNT.testnet\", \"platform_fee_percentage\": 2}' --accountId YOUR_ACCOUNT.testnet"
+echo "2. Initialize: near call YOUR_ACCOUNT.testnet new '{\"owner\": \"YOUR_ACCOUNT.testnet\", \"platform_fee_percentage\": 2, \"order_timeout_ns\": 604800000000000}' --accountId YOUR_ACCOUNT.testnet"
@@ -1,7 +1,37 @@
 use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
-use near_sdk::collections::UnorderedMap;
+use near_sdk::collections::{UnorderedMap, UnorderedSet};
 use near_sdk::json_types::U128;
-use near_sdk::{env, near, AccountId, Balance, PanicOnDefault, Promise};
+use near_sdk::{
+    env, ext_contract, near, AccountId, Balance, Gas, NearToken, PanicOnDefault, Promise,
+    PromiseError, PromiseOrValue,
+};
+use serde_json::json;
+
+/// NEP-297 standard name and version used for all contract events
+const EVENT_STANDARD: &str = "dofta_marketplace";
+const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+/// Page size used by the unpaged order views
+const DEFAULT_PAGE_LIMIT: u64 = 50;
+/// Maximum length (in characters) of a dispute reason
+const MAX_DISPUTE_REASON_LEN: usize = 500;
+/// Upper bound on any single page to keep view calls within gas limits
+const MAX_PAGE_LIMIT: u64 = 100;
+/// Most disputes `resolve_disputes` settles in one call, each scheduling up to three transfers
+const MAX_DISPUTE_BATCH: usize = 20;
+/// Expiry given to pending orders by `migrate`, since the first deployment had none
+const DEFAULT_ORDER_TIMEOUT_NS: u64 = 7 * 24 * 60 * 60 * 1_000_000_000; // 7 days
+/// Inspection window set up by `new` and `migrate`, capped at half the order timeout
+const DEFAULT_INSPECTION_NS: u64 = 3 * 24 * 60 * 60 * 1_000_000_000; // 3 days
+
+/// Gas attached to `ft_transfer` calls on the token contract
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+
+/// NEP-141 interface used to pay out fungible-token escrows
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
 
 /// Status of an escrow order
 #[near(serializers = [json, borsh])]
@@ -20,6 +50,9 @@ pub enum OrderStatus {
 pub enum Resolution {
     RefundBuyer,
     PaySeller,
+    /// Split the escrow: `buyer_bps` basis points (0-10000) go back to the buyer,
+    /// the remainder goes to the seller minus the platform fee
+    Split { buyer_bps: u16 },
 }
 
 /// Escrow order structure
@@ -35,6 +68,81 @@ pub struct EscrowOrder {
     pub status: OrderStatus,
     pub created_at: u64,
     pub completed_at: Option<u64>,
+    pub token: Option<AccountId>, // NEP-141 token contract, `None` for native NEAR
+    pub dispute_reason: Option<String>,
+    pub seller_confirmed: bool,
+    /// Units the seller reported as delivered, `None` until confirmed (a full delivery)
+    pub delivered_quantity: Option<u32>,
+    /// Until this timestamp the buyer can only complete a seller-confirmed order
+    pub inspection_ends_at: u64,
+}
+
+/// Aggregate escrow statistics for operator dashboards
+#[near(serializers = [json])]
+#[derive(Debug, Default)]
+pub struct EscrowStats {
+    pub total_orders: u64,
+    pub pending: u64,
+    pub completed: u64,
+    pub refunded: u64,
+    pub disputed: u64,
+    pub resolved: u64,
+    /// Native NEAR still locked in pending or disputed orders (token escrows excluded)
+    pub total_escrowed: U128,
+}
+
+/// Outcome of a `resolve_disputes` batch
+#[near(serializers = [json])]
+#[derive(Debug, Default)]
+pub struct BatchResolution {
+    pub resolved: Vec<String>,
+    /// Orders that were missing or no longer disputed
+    pub skipped: Vec<String>,
+}
+
+/// Settled order counts for a seller, a trust signal for buyers
+///
+/// Buyers can never be their own seller, so these can't be inflated by self-dealing.
+#[near(serializers = [json, borsh])]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SellerStats {
+    /// Orders paid out in full to the seller, by the buyer or by dispute resolution
+    pub completed_count: u64,
+    /// Orders whose funds went back to the buyer in whole or in part, including
+    /// partial deliveries and split dispute resolutions
+    pub refunded_count: u64,
+}
+
+/// Order details carried in the `msg` of an `ft_transfer_call`
+#[near(serializers = [json])]
+pub struct FtOrderMessage {
+    pub order_id: String,
+    pub seller: AccountId,
+    pub listing_id: String,
+    pub quantity: u32,
+}
+
+/// Escrow order as stored by the first deployment, before tokens, dispute
+/// reasons and shipment confirmation were added
+#[near(serializers = [borsh])]
+pub struct OldEscrowOrder {
+    pub order_id: String,
+    pub buyer: AccountId,
+    pub seller: AccountId,
+    pub amount: Balance,
+    pub listing_id: String,
+    pub quantity: u32,
+    pub status: OrderStatus,
+    pub created_at: u64,
+    pub completed_at: Option<u64>,
+}
+
+/// Contract state as stored by the first deployment, read once by `migrate`
+#[near(serializers = [borsh])]
+pub struct OldMarketplaceContract {
+    pub owner: AccountId,
+    pub orders: UnorderedMap<String, OldEscrowOrder>,
+    pub platform_fee_percentage: u8,
 }
 
 /// Main marketplace contract
@@ -42,26 +150,135 @@ pub struct EscrowOrder {
 #[derive(PanicOnDefault)]
 pub struct MarketplaceContract {
     pub owner: AccountId,
+    pub pending_owner: Option<AccountId>,
+    pub arbitrator: AccountId,
+    pub fee_recipient: AccountId, // receives platform fees, defaults to the owner
     pub orders: UnorderedMap<String, EscrowOrder>,
+    pub disputed_orders: UnorderedSet<String>, // ids of orders currently `Disputed`
+    pub seller_stats: UnorderedMap<AccountId, SellerStats>,
+    pub accepted_tokens: UnorderedSet<AccountId>, // NEP-141 contracts allowed to fund orders
     pub platform_fee_percentage: u8, // e.g., 2 for 2%
+    pub order_timeout_ns: u64, // pending orders older than this can be reclaimed by the buyer
+    pub min_inspection_ns: u64, // unconfirmed orders can be completed only once this has passed
 }
 
 #[near]
 impl MarketplaceContract {
-    /// Initialize the contract
+    /// Initialize the contract (the owner also acts as arbitrator)
     #[init]
-    pub fn new(owner: AccountId, platform_fee_percentage: u8) -> Self {
+    pub fn new(
+        owner: AccountId,
+        platform_fee_percentage: u8,
+        order_timeout_ns: u64,
+        fee_recipient: Option<AccountId>,
+    ) -> Self {
+        Self::new_with_arbitrator(
+            owner.clone(),
+            platform_fee_percentage,
+            order_timeout_ns,
+            owner,
+            fee_recipient,
+        )
+    }
+
+    /// Initialize the contract with a dedicated dispute arbitrator
+    #[init]
+    pub fn new_with_arbitrator(
+        owner: AccountId,
+        platform_fee_percentage: u8,
+        order_timeout_ns: u64,
+        arbitrator: AccountId,
+        fee_recipient: Option<AccountId>,
+    ) -> Self {
         assert!(!env::state_exists(), "Already initialized");
         assert!(
             platform_fee_percentage <= 10,
             "Platform fee cannot exceed 10%"
         );
+        assert!(order_timeout_ns > 0, "Order timeout must be greater than 0");
         
         Self {
+            fee_recipient: fee_recipient.unwrap_or_else(|| owner.clone()),
             owner,
+            pending_owner: None,
+            arbitrator,
             orders: UnorderedMap::new(b"o"),
+            disputed_orders: UnorderedSet::new(b"d"),
+            seller_stats: UnorderedMap::new(b"s"),
+            accepted_tokens: UnorderedSet::new(b"t"),
             platform_fee_percentage,
+            order_timeout_ns,
+            // Unconfirmed orders become completable well before the buyer can reclaim them
+            min_inspection_ns: DEFAULT_INSPECTION_NS.min(order_timeout_ns / 2),
+        }
+    }
+
+    /// Upgrade state left by the first deployment to the current layout
+    ///
+    /// Deploy the new code and call this in the same transaction. New fields get
+    /// the defaults `new` would pick: the owner arbitrates and receives fees, and
+    /// pending orders can be reclaimed after seven days. Every order is rewritten
+    /// here and the dispute and seller stat indexes are rebuilt from them, so the
+    /// cost grows with the number of orders.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let mut old: OldMarketplaceContract =
+            env::state_read().expect("No contract state to migrate");
+        let old_orders = old.orders.to_vec();
+        old.orders.clear();
+
+        let mut contract = Self {
+            fee_recipient: old.owner.clone(),
+            arbitrator: old.owner.clone(),
+            owner: old.owner,
+            pending_owner: None,
+            orders: UnorderedMap::new(b"o"),
+            disputed_orders: UnorderedSet::new(b"d"),
+            seller_stats: UnorderedMap::new(b"s"),
+            accepted_tokens: UnorderedSet::new(b"t"),
+            platform_fee_percentage: old.platform_fee_percentage,
+            order_timeout_ns: DEFAULT_ORDER_TIMEOUT_NS,
+            min_inspection_ns: DEFAULT_INSPECTION_NS,
+        };
+
+        for (order_id, old_order) in old_orders {
+            let order = EscrowOrder {
+                order_id: old_order.order_id,
+                buyer: old_order.buyer,
+                seller: old_order.seller,
+                amount: old_order.amount,
+                listing_id: old_order.listing_id,
+                quantity: old_order.quantity,
+                status: old_order.status,
+                created_at: old_order.created_at,
+                completed_at: old_order.completed_at,
+                token: None,
+                dispute_reason: None,
+                seller_confirmed: false,
+                delivered_quantity: None,
+                inspection_ends_at: old_order.created_at + contract.min_inspection_ns,
+            };
+
+            // Resolved orders are left out of the stats since the outcome wasn't stored
+            match order.status {
+                OrderStatus::Disputed => {
+                    contract.disputed_orders.insert(&order_id);
+                }
+                OrderStatus::Completed => {
+                    contract.update_seller_stats(&order.seller, |stats| stats.completed_count += 1)
+                }
+                OrderStatus::Refunded => {
+                    contract.update_seller_stats(&order.seller, |stats| stats.refunded_count += 1)
+                }
+                OrderStatus::Pending | OrderStatus::Resolved => {}
+            }
+
+            contract.orders.insert(&order_id, &order);
         }
+
+        env::log_str(&format!("Migrated {} orders", contract.orders.len()));
+        contract
     }
 
     /// Create an escrow order (buyer deposits funds)
@@ -76,40 +293,112 @@ impl MarketplaceContract {
         let buyer = env::predecessor_account_id();
         let amount = env::attached_deposit();
 
-        // Validate inputs
         assert!(amount > 0, "Must attach NEAR tokens");
-        assert!(quantity > 0, "Quantity must be greater than 0");
-        assert!(
-            !self.orders.get(&order_id).is_some(),
-            "Order ID already exists"
+
+        self.internal_create_order(order_id, buyer, seller, amount, listing_id, quantity, None)
+    }
+
+    /// Create an escrow order funded with a NEP-141 token (called by the token contract)
+    ///
+    /// `msg` is a JSON-encoded `FtOrderMessage`. The whole amount is kept in escrow.
+    /// Only tokens the owner has accepted can fund orders, since any account can
+    /// call this and claim to have transferred tokens.
+    pub fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let token = env::predecessor_account_id();
+        assert!(self.accepted_tokens.contains(&token), "Token not accepted");
+        let message: FtOrderMessage =
+            serde_json::from_str(&msg).expect("Invalid order message");
+
+        assert!(amount.0 > 0, "Must transfer tokens");
+
+        self.internal_create_order(
+            message.order_id,
+            sender_id,
+            message.seller,
+            amount.0,
+            message.listing_id,
+            message.quantity,
+            Some(token),
         );
-        assert!(buyer != seller, "Buyer and seller must be different");
 
-        // Create escrow order
-        let order = EscrowOrder {
-            order_id: order_id.clone(),
-            buyer: buyer.clone(),
-            seller,
-            amount,
-            listing_id,
-            quantity,
-            status: OrderStatus::Pending,
-            created_at: env::block_timestamp(),
-            completed_at: None,
-        };
+        PromiseOrValue::Value(U128(0))
+    }
+
+    /// Confirm the goods have been shipped (called by seller)
+    pub fn confirm_shipment(&mut self, order_id: String) {
+        let caller = env::predecessor_account_id();
+        let mut order = self
+            .orders
+            .get(&order_id)
+            .expect("Order not found");
+
+        // Validate
+        assert_eq!(order.seller, caller, "Only seller can confirm shipment");
+        assert_eq!(order.status, OrderStatus::Pending, "Order not pending");
+        assert!(!order.seller_confirmed, "Shipment already confirmed");
 
+        order.seller_confirmed = true;
+        order.delivered_quantity = Some(order.quantity);
         self.orders.insert(&order_id, &order);
 
-        env::log_str(&format!(
-            "Escrow created: {} - Buyer: {} - Amount: {} yoctoNEAR",
-            order_id, buyer, amount
-        ));
+        emit_event(
+            "shipment_confirmed",
+            json!({
+                "order_id": order_id,
+                "buyer": order.buyer,
+                "seller": order.seller,
+                "delivered_quantity": order.quantity,
+            }),
+        );
+    }
 
-        order
+    /// Confirm only part of the order was shipped (called by seller)
+    ///
+    /// On completion the seller is paid for `delivered_qty` units and the buyer is
+    /// refunded for the rest.
+    pub fn confirm_partial(&mut self, order_id: String, delivered_qty: u32) {
+        let caller = env::predecessor_account_id();
+        let mut order = self
+            .orders
+            .get(&order_id)
+            .expect("Order not found");
+
+        // Validate
+        assert_eq!(order.seller, caller, "Only seller can confirm shipment");
+        assert_eq!(order.status, OrderStatus::Pending, "Order not pending");
+        assert!(!order.seller_confirmed, "Shipment already confirmed");
+        assert!(delivered_qty > 0, "Delivered quantity must be greater than 0");
+        assert!(
+            delivered_qty <= order.quantity,
+            "Delivered quantity cannot exceed the ordered quantity"
+        );
+
+        order.seller_confirmed = true;
+        order.delivered_quantity = Some(delivered_qty);
+        self.orders.insert(&order_id, &order);
+
+        emit_event(
+            "shipment_confirmed",
+            json!({
+                "order_id": order_id,
+                "buyer": order.buyer,
+                "seller": order.seller,
+                "delivered_quantity": delivered_qty,
+            }),
+        );
     }
 
-    /// Complete order and release funds to seller (called by buyer)
-    pub fn complete_order(&mut self, order_id: String) {
+    /// Complete order and release funds to seller (called by buyer once the
+    /// seller confirmed shipment, or after the order's inspection window)
+    ///
+    /// After a partial shipment the seller is paid pro rata and the buyer is
+    /// refunded for the undelivered units.
+    pub fn complete_order(&mut self, order_id: String) -> Promise {
         let caller = env::predecessor_account_id();
         let mut order = self
             .orders
@@ -119,26 +408,95 @@ impl MarketplaceContract {
         // Validate
         assert_eq!(order.buyer, caller, "Only buyer can complete order");
         assert_eq!(order.status, OrderStatus::Pending, "Order not pending");
+        assert!(
+            order.seller_confirmed || env::block_timestamp() > order.inspection_ends_at,
+            "Seller has not confirmed shipment and the inspection window is still open"
+        );
+
+        // Only delivered units are paid for; rounding dust goes back to the buyer
+        let delivered = order.delivered_quantity.unwrap_or(order.quantity);
+        let delivered_amount = mul_div_floor(
+            order.amount,
+            delivered as u128,
+            order.quantity as u128,
+        );
+        let buyer_refund = order.amount - delivered_amount;
 
         // Calculate platform fee and seller amount
-        let platform_fee = (order.amount * self.platform_fee_percentage as u128) / 100;
-        let seller_amount = order.amount - platform_fee;
+        let platform_fee = mul_div_floor(
+            delivered_amount,
+            self.platform_fee_percentage as u128,
+            100,
+        );
+        let seller_amount = delivered_amount - platform_fee;
 
-        // Update order status
+        // Update order status (reverted in the callback if the transfer fails)
         order.status = OrderStatus::Completed;
         order.completed_at = Some(env::block_timestamp());
         self.orders.insert(&order_id, &order);
 
-        // Transfer funds
-        if platform_fee > 0 {
-            Promise::new(self.owner.clone()).transfer(platform_fee);
+        // Pay the seller first; the fee and any refund are only settled once that succeeds
+        self.transfer_funds(&order.token, order.seller.clone(), seller_amount)
+            .then(Self::ext(env::current_account_id()).on_complete_transfer(
+                order_id,
+                U128(seller_amount),
+                U128(platform_fee),
+                U128(buyer_refund),
+            ))
+    }
+
+    /// Callback for `complete_order`: settle the fee and refund, or revert the order to `Pending`
+    #[private]
+    pub fn on_complete_transfer(
+        &mut self,
+        #[callback_result] seller_transfer: Result<(), PromiseError>,
+        order_id: String,
+        seller_amount: U128,
+        platform_fee: U128,
+        buyer_refund: U128,
+    ) -> bool {
+        let mut order = self
+            .orders
+            .get(&order_id)
+            .expect("Order not found");
+
+        if seller_transfer.is_err() {
+            // Seller account could not receive funds (e.g. deleted), keep them in escrow
+            order.status = OrderStatus::Pending;
+            order.completed_at = None;
+            self.orders.insert(&order_id, &order);
+
+            env::log_str(&format!(
+                "Transfer of {} yoctoNEAR to {} failed for order {}: reverted to Pending",
+                seller_amount.0, order.seller, order_id
+            ));
+            return false;
         }
-        Promise::new(order.seller.clone()).transfer(seller_amount);
 
-        env::log_str(&format!(
-            "Order completed: {} - Seller received: {} yoctoNEAR - Platform fee: {} yoctoNEAR",
-            order_id, seller_amount, platform_fee
-        ));
+        if platform_fee.0 > 0 {
+            self.transfer_funds(&order.token, self.fee_recipient.clone(), platform_fee.0);
+        }
+        // A partial delivery refunds the buyer, so it counts like a split resolution
+        if buyer_refund.0 > 0 {
+            self.transfer_funds(&order.token, order.buyer.clone(), buyer_refund.0);
+            self.update_seller_stats(&order.seller, |stats| stats.refunded_count += 1);
+        } else {
+            self.update_seller_stats(&order.seller, |stats| stats.completed_count += 1);
+        }
+
+        emit_event(
+            "order_completed",
+            json!({
+                "order_id": order_id,
+                "buyer": order.buyer,
+                "seller": order.seller,
+                "seller_amount": seller_amount.0.to_string(),
+                "platform_fee": platform_fee.0.to_string(),
+                "buyer_refund": buyer_refund.0.to_string(),
+            }),
+        );
+
+        true
     }
 
     /// Refund order (called by seller or owner in case of dispute)
@@ -162,15 +520,59 @@ impl MarketplaceContract {
         self.orders.insert(&order_id, &order);
 
         // Refund buyer
-        Promise::new(order.buyer.clone()).transfer(order.amount);
+        self.transfer_funds(&order.token, order.buyer.clone(), order.amount);
+        self.update_seller_stats(&order.seller, |stats| stats.refunded_count += 1);
 
-        env::log_str(&format!(
-            "Order refunded: {} - Buyer refunded: {} yoctoNEAR",
-            order_id, order.amount
-        ));
+        emit_event(
+            "order_refunded",
+            json!({
+                "order_id": order_id,
+                "buyer": order.buyer,
+                "seller": order.seller,
+                "refunded_by": caller,
+                "amount": order.amount.to_string(),
+            }),
+        );
+    }
+
+    /// Reclaim funds from a pending order the seller never acted on (buyer only)
+    pub fn claim_expired_refund(&mut self, order_id: String) {
+        let caller = env::predecessor_account_id();
+        let mut order = self
+            .orders
+            .get(&order_id)
+            .expect("Order not found");
+
+        // Validate
+        assert_eq!(order.buyer, caller, "Only buyer can claim an expired refund");
+        assert_eq!(order.status, OrderStatus::Pending, "Order not pending");
+        assert!(!order.seller_confirmed, "Seller has confirmed shipment");
+        assert!(self.is_expired(&order), "Order has not expired yet");
+
+        // Update order status
+        order.status = OrderStatus::Refunded;
+        order.completed_at = Some(env::block_timestamp());
+        self.orders.insert(&order_id, &order);
+
+        // Refund buyer
+        self.transfer_funds(&order.token, order.buyer.clone(), order.amount);
+        self.update_seller_stats(&order.seller, |stats| stats.refunded_count += 1);
+
+        emit_event(
+            "order_refunded",
+            json!({
+                "order_id": order_id,
+                "buyer": order.buyer,
+                "seller": order.seller,
+                "refunded_by": caller,
+                "amount": order.amount.to_string(),
+                "expired": true,
+            }),
+        );
     }
 
-    pub fn dispute_order(&mut self, order_id: String) {
+    /// Raise a dispute with a short explanation (buyer or seller)
+    pub fn dispute_order(&mut self, order_id: String, reason: String) {
         let caller = env::predecessor_account_id();
         let mut order = self
             .orders
@@ -183,23 +585,36 @@ impl MarketplaceContract {
             caller == order.buyer || caller == order.seller,
             "Only buyer or seller can dispute"
         );
+        assert!(
+            !reason.trim().is_empty() && reason.chars().count() <= MAX_DISPUTE_REASON_LEN,
+            "Dispute reason must be between 1 and 500 characters"
+        );
 
         // Update order status
         order.status = OrderStatus::Disputed;
+        order.dispute_reason = Some(reason);
         self.orders.insert(&order_id, &order);
+        self.disputed_orders.insert(&order_id);
 
-        env::log_str(&format!(
-            "Order disputed: {} - Awaiting owner resolution",
-            order_id
-        ));
+        emit_event(
+            "order_disputed",
+            json!({
+                "order_id": order_id,
+                "buyer": order.buyer,
+                "seller": order.seller,
+                "disputed_by": caller,
+                "amount": order.amount.to_string(),
+                "reason": order.dispute_reason,
+            }),
+        );
     }
 
-    /// Resolve a disputed order (owner only)
+    /// Resolve a disputed order (owner or arbitrator only)
     pub fn resolve_dispute(&mut self, order_id: String, resolution: Resolution) {
         let caller = env::predecessor_account_id();
-        assert_eq!(caller, self.owner, "Only owner can resolve disputes");
+        self.assert_can_resolve(&caller);
 
-        let mut order = self
+        let order = self
             .orders
             .get(&order_id)
             .expect("Order not found");
@@ -210,35 +625,73 @@ impl MarketplaceContract {
             "Order is not disputed"
         );
 
-        match resolution {
-            Resolution::RefundBuyer => {
-                // Refund buyer in full
-                Promise::new(order.buyer.clone()).transfer(order.amount);
-                env::log_str(&format!(
-                    "Dispute resolved for {}: Buyer refunded {} yoctoNEAR",
-                    order_id, order.amount
-                ));
-            }
-            Resolution::PaySeller => {
-                // Pay seller (minus platform fee)
-                let platform_fee = (order.amount * self.platform_fee_percentage as u128) / 100;
-                let seller_amount = order.amount - platform_fee;
+        self.internal_resolve_dispute(&caller, order_id, order, resolution);
+    }
 
-                if platform_fee > 0 {
-                    Promise::new(self.owner.clone()).transfer(platform_fee);
-                }
-                Promise::new(order.seller.clone()).transfer(seller_amount);
+    /// Resolve several disputes in one call (owner or arbitrator only)
+    ///
+    /// Orders that are missing or not `Disputed` are logged and skipped instead of
+    /// aborting the batch. At most `MAX_DISPUTE_BATCH` entries are accepted.
+    pub fn resolve_disputes(&mut self, resolutions: Vec<(String, Resolution)>) -> BatchResolution {
+        let caller = env::predecessor_account_id();
+        self.assert_can_resolve(&caller);
+        assert!(
+            resolutions.len() <= MAX_DISPUTE_BATCH,
+            "Cannot resolve more than {} disputes at once",
+            MAX_DISPUTE_BATCH
+        );
 
-                env::log_str(&format!(
-                    "Dispute resolved for {}: Seller paid {} yoctoNEAR",
-                    order_id, seller_amount
-                ));
+        let mut summary = BatchResolution::default();
+        for (order_id, resolution) in resolutions {
+            match self.orders.get(&order_id) {
+                Some(order) if order.status == OrderStatus::Disputed => {
+                    self.internal_resolve_dispute(&caller, order_id.clone(), order, resolution);
+                    summary.resolved.push(order_id);
+                }
+                _ => {
+                    env::log_str(&format!("Skipping order {}: not disputed", order_id));
+                    summary.skipped.push(order_id);
+                }
             }
         }
 
-        order.status = OrderStatus::Resolved;
-        order.completed_at = Some(env::block_timestamp());
-        self.orders.insert(&order_id, &order);
+        summary
+    }
+
+    /// Remove a settled order from state to reclaim storage (owner only)
+    pub fn archive_order(&mut self, order_id: String) -> EscrowOrder {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can archive orders"
+        );
+
+        let order = self
+            .orders
+            .get(&order_id)
+            .expect("Order not found");
+
+        // Never delete a live escrow
+        assert!(
+            matches!(
+                order.status,
+                OrderStatus::Completed | OrderStatus::Refunded | OrderStatus::Resolved
+            ),
+            "Only completed, refunded or resolved orders can be archived"
+        );
+
+        self.orders.remove(&order_id);
+
+        emit_event(
+            "order_archived",
+            json!({
+                "order_id": order_id,
+                "buyer": order.buyer,
+                "seller": order.seller,
+            }),
+        );
+
+        order
     }
 
     /// Get order details
@@ -246,98 +699,527 @@ impl MarketplaceContract {
         self.orders.get(&order_id)
     }
 
-    /// Get all orders for a buyer
+    /// Get orders for a buyer (first page, see `get_buyer_orders_paged`)
     pub fn get_buyer_orders(&self, buyer: AccountId) -> Vec<EscrowOrder> {
+        self.get_buyer_orders_paged(buyer, 0, DEFAULT_PAGE_LIMIT)
+    }
+
+    /// Get a window of orders for a buyer
+    pub fn get_buyer_orders_paged(
+        &self,
+        buyer: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<EscrowOrder> {
         self.orders
             .values()
             .filter(|order| order.buyer == buyer)
+            .skip(from_index as usize)
+            .take(limit.min(MAX_PAGE_LIMIT) as usize)
             .collect()
     }
 
-    /// Get all orders for a seller
+    /// Get orders for a seller (first page, see `get_seller_orders_paged`)
     pub fn get_seller_orders(&self, seller: AccountId) -> Vec<EscrowOrder> {
+        self.get_seller_orders_paged(seller, 0, DEFAULT_PAGE_LIMIT)
+    }
+
+    /// Get a window of orders for a seller
+    pub fn get_seller_orders_paged(
+        &self,
+        seller: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<EscrowOrder> {
         self.orders
             .values()
             .filter(|order| order.seller == seller)
+            .skip(from_index as usize)
+            .take(limit.min(MAX_PAGE_LIMIT) as usize)
             .collect()
     }
 
-    /// Get platform fee percentage
-    pub fn get_platform_fee(&self) -> u8 {
-        self.platform_fee_percentage
+    /// Get a window of orders awaiting dispute resolution
+    ///
+    /// Reads the `disputed_orders` index, so the cost grows with the page size
+    /// rather than with the total number of orders.
+    pub fn get_disputed_orders(&self, from_index: u64, limit: u64) -> Vec<EscrowOrder> {
+        self.disputed_orders
+            .iter()
+            .skip(from_index as usize)
+            .take(limit.min(MAX_PAGE_LIMIT) as usize)
+            .filter_map(|order_id| self.orders.get(&order_id))
+            .collect()
     }
 
-    /// Update platform fee (owner only)
-    pub fn update_platform_fee(&mut self, new_fee: u8) {
+    /// Get completed and refunded order counts for a seller
+    pub fn get_seller_stats(&self, seller: AccountId) -> SellerStats {
+        self.seller_stats.get(&seller).unwrap_or_default()
+    }
+
+    /// Start an ownership handover (owner only); the new owner must call `accept_ownership`
+    pub fn propose_new_owner(&mut self, new_owner: AccountId) {
         assert_eq!(
             env::predecessor_account_id(),
             self.owner,
-            "Only owner can update fee"
+            "Only owner can propose a new owner"
         );
-        assert!(new_fee <= 10, "Fee cannot exceed 10%");
-        
-        self.platform_fee_percentage = new_fee;
-        env::log_str(&format!("Platform fee updated to {}%", new_fee));
+
+        env::log_str(&format!("Ownership transfer proposed to {}", new_owner));
+        self.pending_owner = Some(new_owner);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::testing_env;
+    /// Complete an ownership handover (pending owner only)
+    pub fn accept_ownership(&mut self) {
+        let caller = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_owner.as_ref(),
+            Some(&caller),
+            "Only the pending owner can accept ownership"
+        );
 
-    fn get_context(predecessor: AccountId) -> VMContextBuilder {
-        let mut builder = VMContextBuilder::new();
-        builder
-            .predecessor_account_id(predecessor)
-            .block_timestamp(1_000_000_000);
-        builder
+        env::log_str(&format!("Ownership transferred from {} to {}", self.owner, caller));
+        self.owner = caller;
+        self.pending_owner = None;
     }
 
-    #[test]
-    fn test_contract_initialization() {
-        let context = get_context(accounts(0));
-        testing_env!(context.build());
-        
-        let contract = MarketplaceContract::new(accounts(0), 2);
-        assert_eq!(contract.owner, accounts(0));
-        assert_eq!(contract.platform_fee_percentage, 2);
+    /// Get the dispute arbitrator
+    pub fn get_arbitrator(&self) -> AccountId {
+        self.arbitrator.clone()
     }
 
-    #[test]
-    fn test_create_order() {
-        let mut context = get_context(accounts(1));
-        context.attached_deposit(1_000_000_000_000_000_000_000_000); // 1 NEAR
-        testing_env!(context.build());
-
-        let mut contract = MarketplaceContract::new(accounts(0), 2);
-        
-        let order = contract.create_order(
-            "order_1".to_string(),
-            accounts(2),
-            "listing_1".to_string(),
-            5,
+    /// Update the dispute arbitrator (owner only)
+    pub fn set_arbitrator(&mut self, new_arbitrator: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set arbitrator"
         );
 
-        assert_eq!(order.buyer, accounts(1));
-        assert_eq!(order.seller, accounts(2));
-        assert_eq!(order.status, OrderStatus::Pending);
+        env::log_str(&format!("Arbitrator updated to {}", new_arbitrator));
+        self.arbitrator = new_arbitrator;
     }
 
-    #[test]
-    #[should_panic(expected = "Must attach NEAR tokens")]
-    fn test_create_order_no_deposit() {
-        let context = get_context(accounts(1));
-        testing_env!(context.build());
+    /// Get the account receiving platform fees
+    pub fn get_fee_recipient(&self) -> AccountId {
+        self.fee_recipient.clone()
+    }
 
-        let mut contract = MarketplaceContract::new(accounts(0), 2);
-        
-        contract.create_order(
-            "order_1".to_string(),
-            accounts(2),
-            "listing_1".to_string(),
-            5,
+    /// Update the account receiving platform fees (owner only)
+    pub fn set_fee_recipient(&mut self, new_fee_recipient: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set fee recipient"
+        );
+
+        env::log_str(&format!("Fee recipient updated to {}", new_fee_recipient));
+        self.fee_recipient = new_fee_recipient;
+    }
+
+    /// Get the NEP-141 token contracts that can fund orders
+    pub fn get_accepted_tokens(&self) -> Vec<AccountId> {
+        self.accepted_tokens.to_vec()
+    }
+
+    /// Allow a NEP-141 token contract to fund orders (owner only)
+    pub fn add_accepted_token(&mut self, token: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can accept tokens"
+        );
+
+        env::log_str(&format!("Token {} accepted", token));
+        self.accepted_tokens.insert(&token);
+    }
+
+    /// Stop a NEP-141 token contract from funding new orders (owner only)
+    ///
+    /// Orders already funded with the token still pay out in it.
+    pub fn remove_accepted_token(&mut self, token: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can remove tokens"
+        );
+
+        env::log_str(&format!("Token {} no longer accepted", token));
+        self.accepted_tokens.remove(&token);
+    }
+
+    /// Get order counts per status and the amount currently held in escrow
+    pub fn get_stats(&self) -> EscrowStats {
+        let mut stats = EscrowStats::default();
+        let mut total_escrowed: Balance = 0;
+
+        for order in self.orders.values() {
+            stats.total_orders += 1;
+            match order.status {
+                OrderStatus::Pending => stats.pending += 1,
+                OrderStatus::Completed => stats.completed += 1,
+                OrderStatus::Refunded => stats.refunded += 1,
+                OrderStatus::Disputed => stats.disputed += 1,
+                OrderStatus::Resolved => stats.resolved += 1,
+            }
+
+            let locked = matches!(order.status, OrderStatus::Pending | OrderStatus::Disputed);
+            if locked && order.token.is_none() {
+                total_escrowed += order.amount;
+            }
+        }
+
+        stats.total_escrowed = U128(total_escrowed);
+        stats
+    }
+
+    /// Get the inspection window applied to new orders
+    pub fn get_min_inspection_ns(&self) -> u64 {
+        self.min_inspection_ns
+    }
+
+    /// Update the inspection window for orders created from now on (owner only)
+    ///
+    /// The window must close before `order_timeout_ns`, or completing an unconfirmed
+    /// order and reclaiming it as expired would open at the same time.
+    pub fn set_min_inspection_ns(&mut self, min_inspection_ns: u64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can set the inspection window"
+        );
+        assert!(
+            min_inspection_ns < self.order_timeout_ns,
+            "Inspection window must end before the order timeout"
+        );
+
+        env::log_str(&format!("Inspection window updated to {} ns", min_inspection_ns));
+        self.min_inspection_ns = min_inspection_ns;
+    }
+
+    /// Get platform fee percentage
+    pub fn get_platform_fee(&self) -> u8 {
+        self.platform_fee_percentage
+    }
+
+    /// Update platform fee (owner only)
+    pub fn update_platform_fee(&mut self, new_fee: u8) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner,
+            "Only owner can update fee"
+        );
+        assert!(new_fee <= 10, "Fee cannot exceed 10%");
+        
+        self.platform_fee_percentage = new_fee;
+        env::log_str(&format!("Platform fee updated to {}%", new_fee));
+    }
+}
+
+impl MarketplaceContract {
+    /// Whether a pending order has outlived `order_timeout_ns`
+    fn is_expired(&self, order: &EscrowOrder) -> bool {
+        env::block_timestamp().saturating_sub(order.created_at) > self.order_timeout_ns
+    }
+
+    /// Panic unless `caller` may settle disputes
+    fn assert_can_resolve(&self, caller: &AccountId) {
+        assert!(
+            *caller == self.owner || *caller == self.arbitrator,
+            "Only owner or arbitrator can resolve disputes"
+        );
+    }
+
+    /// Pay out a disputed order according to `resolution` and mark it `Resolved`
+    fn internal_resolve_dispute(
+        &mut self,
+        caller: &AccountId,
+        order_id: String,
+        mut order: EscrowOrder,
+        resolution: Resolution,
+    ) {
+        let (buyer_amount, seller_amount, platform_fee) = match resolution {
+            Resolution::RefundBuyer => {
+                // Refund buyer in full
+                self.transfer_funds(&order.token, order.buyer.clone(), order.amount);
+                (order.amount, 0, 0)
+            }
+            Resolution::PaySeller => {
+                // Pay seller (minus platform fee)
+                let platform_fee = mul_div_floor(
+                    order.amount,
+                    self.platform_fee_percentage as u128,
+                    100,
+                );
+                let seller_amount = order.amount - platform_fee;
+
+                if platform_fee > 0 {
+                    self.transfer_funds(&order.token, self.fee_recipient.clone(), platform_fee);
+                }
+                self.transfer_funds(&order.token, order.seller.clone(), seller_amount);
+                (0, seller_amount, platform_fee)
+            }
+            Resolution::Split { buyer_bps } => {
+                assert!(buyer_bps <= 10_000, "buyer_bps cannot exceed 10000");
+
+                // Buyer share is rounded down so the seller side absorbs any dust
+                let buyer_amount = mul_div_floor(order.amount, buyer_bps as u128, 10_000);
+                let seller_share = order.amount - buyer_amount;
+                let platform_fee = mul_div_floor(
+                    seller_share,
+                    self.platform_fee_percentage as u128,
+                    100,
+                );
+                let seller_amount = seller_share - platform_fee;
+
+                if buyer_amount > 0 {
+                    self.transfer_funds(&order.token, order.buyer.clone(), buyer_amount);
+                }
+                if platform_fee > 0 {
+                    self.transfer_funds(&order.token, self.fee_recipient.clone(), platform_fee);
+                }
+                if seller_amount > 0 {
+                    self.transfer_funds(&order.token, order.seller.clone(), seller_amount);
+                }
+                (buyer_amount, seller_amount, platform_fee)
+            }
+        };
+
+        // Any money back to the buyer counts against the seller
+        if buyer_amount > 0 {
+            self.update_seller_stats(&order.seller, |stats| stats.refunded_count += 1);
+        } else {
+            self.update_seller_stats(&order.seller, |stats| stats.completed_count += 1);
+        }
+
+        emit_event(
+            "dispute_resolved",
+            json!({
+                "order_id": order_id,
+                "buyer": order.buyer,
+                "seller": order.seller,
+                "resolved_by": caller,
+                "buyer_amount": buyer_amount.to_string(),
+                "seller_amount": seller_amount.to_string(),
+                "platform_fee": platform_fee.to_string(),
+            }),
+        );
+
+        order.status = OrderStatus::Resolved;
+        order.completed_at = Some(env::block_timestamp());
+        self.orders.insert(&order_id, &order);
+        self.disputed_orders.remove(&order_id);
+    }
+
+    /// Validate and store a new pending escrow order
+    #[allow(clippy::too_many_arguments)]
+    fn internal_create_order(
+        &mut self,
+        order_id: String,
+        buyer: AccountId,
+        seller: AccountId,
+        amount: Balance,
+        listing_id: String,
+        quantity: u32,
+        token: Option<AccountId>,
+    ) -> EscrowOrder {
+        // Validate inputs
+        assert!(quantity > 0, "Quantity must be greater than 0");
+        assert!(
+            !self.orders.get(&order_id).is_some(),
+            "Order ID already exists"
+        );
+        assert!(buyer != seller, "Buyer and seller must be different");
+
+        // Create escrow order
+        let order = EscrowOrder {
+            order_id: order_id.clone(),
+            buyer: buyer.clone(),
+            seller,
+            amount,
+            listing_id,
+            quantity,
+            status: OrderStatus::Pending,
+            created_at: env::block_timestamp(),
+            completed_at: None,
+            token,
+            dispute_reason: None,
+            seller_confirmed: false,
+            delivered_quantity: None,
+            inspection_ends_at: env::block_timestamp() + self.min_inspection_ns,
+        };
+
+        self.orders.insert(&order_id, &order);
+
+        emit_event(
+            "order_created",
+            json!({
+                "order_id": order_id,
+                "buyer": buyer,
+                "seller": order.seller,
+                "listing_id": order.listing_id,
+                "quantity": quantity,
+                "amount": amount.to_string(),
+                "token": order.token,
+            }),
+        );
+
+        order
+    }
+
+    /// Apply `update` to a seller's settled order counts
+    fn update_seller_stats(&mut self, seller: &AccountId, update: impl FnOnce(&mut SellerStats)) {
+        let mut stats = self.seller_stats.get(seller).unwrap_or_default();
+        update(&mut stats);
+        self.seller_stats.insert(seller, &stats);
+    }
+
+    /// Pay out escrowed funds in native NEAR or via `ft_transfer` on the order's token
+    fn transfer_funds(
+        &self,
+        token: &Option<AccountId>,
+        receiver: AccountId,
+        amount: Balance,
+    ) -> Promise {
+        match token {
+            None => Promise::new(receiver).transfer(amount),
+            Some(token) => ext_ft::ext(token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(receiver, U128(amount), None),
+        }
+    }
+}
+
+/// `amount * numerator / denominator` rounded down, without overflowing on
+/// large token amounts. `numerator` must not exceed `denominator`.
+fn mul_div_floor(amount: Balance, numerator: u128, denominator: u128) -> Balance {
+    // The remainder is below `denominator`, so multiplying it cannot overflow
+    amount / denominator * numerator + amount % denominator * numerator / denominator
+}
+
+/// Log a NEP-297 event (`EVENT_JSON:{...}`) so off-chain indexers can pick it up
+fn emit_event(event: &str, data: serde_json::Value) {
+    let event = json!({
+        "standard": EVENT_STANDARD,
+        "version": EVENT_STANDARD_VERSION,
+        "event": event,
+        "data": [data],
+    });
+
+    env::log_str(&format!("EVENT_JSON:{}", event));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::mock::MockAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const ORDER_TIMEOUT_NS: u64 = 7 * 24 * 60 * 60 * 1_000_000_000; // 7 days
+
+    fn get_context(predecessor: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .predecessor_account_id(predecessor)
+            .block_timestamp(1_000_000_000);
+        builder
+    }
+
+    /// Collect (receiver, amount) pairs for every transfer scheduled so far
+    fn get_transfers() -> Vec<(AccountId, Balance)> {
+        get_created_receipts()
+            .into_iter()
+            .flat_map(|receipt| {
+                let receiver = receipt.receiver_id.clone();
+                receipt.actions.into_iter().filter_map(move |action| match action {
+                    MockAction::Transfer { deposit, .. } => Some((receiver.clone(), deposit)),
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    /// Collect (receiver, amount) pairs for every `ft_transfer` call scheduled so far
+    fn get_ft_transfers(token: AccountId) -> Vec<(AccountId, Balance)> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id == token)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                MockAction::FunctionCallWeight { method_name, args, .. }
+                    if method_name == b"ft_transfer" =>
+                {
+                    let args: serde_json::Value = serde_json::from_slice(&args).unwrap();
+                    let receiver: AccountId = args["receiver_id"].as_str().unwrap().parse().unwrap();
+                    let amount: Balance = args["amount"].as_str().unwrap().parse().unwrap();
+                    Some((receiver, amount))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Parse every NEP-297 event logged so far
+    fn get_events() -> Vec<serde_json::Value> {
+        get_logs()
+            .iter()
+            .map(|log| {
+                let payload = log
+                    .strip_prefix("EVENT_JSON:")
+                    .expect("Log is not a NEP-297 event");
+                serde_json::from_str(payload).expect("Event is not valid JSON")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_contract_initialization() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        
+        let contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+        assert_eq!(contract.owner, accounts(0));
+        assert_eq!(contract.arbitrator, accounts(0));
+        assert_eq!(contract.fee_recipient, accounts(0));
+        assert_eq!(contract.platform_fee_percentage, 2);
+        assert_eq!(contract.order_timeout_ns, ORDER_TIMEOUT_NS);
+    }
+
+    #[test]
+    fn test_create_order() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000); // 1 NEAR
+        testing_env!(context.build());
+
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+        
+        let order = contract.create_order(
+            "order_1".to_string(),
+            accounts(2),
+            "listing_1".to_string(),
+            5,
+        );
+
+        assert_eq!(order.buyer, accounts(1));
+        assert_eq!(order.seller, accounts(2));
+        assert_eq!(order.status, OrderStatus::Pending);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must attach NEAR tokens")]
+    fn test_create_order_no_deposit() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+        
+        contract.create_order(
+            "order_1".to_string(),
+            accounts(2),
+            "listing_1".to_string(),
+            5,
         );
     }
 
@@ -345,7 +1227,7 @@ mod tests {
     fn test_resolve_dispute_refund_buyer() {
         let mut context = get_context(accounts(0)); // Owner
         testing_env!(context.build());
-        let mut contract = MarketplaceContract::new(accounts(0), 2);
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
 
         // Setup: Create order and dispute it
         // Buyer creates order
@@ -355,7 +1237,7 @@ mod tests {
         contract.create_order("o1".to_string(), accounts(2), "l1".to_string(), 1);
 
         // Buyer disputes order (can be buyer or seller)
-        contract.dispute_order("o1".to_string());
+        contract.dispute_order("o1".to_string(), "Damaged produce".to_string());
 
         // Test: Owner resolves dispute (Refund Buyer)
         context.predecessor_account_id(accounts(0)); // Back to owner
@@ -372,7 +1254,7 @@ mod tests {
     fn test_resolve_dispute_pay_seller() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        let mut contract = MarketplaceContract::new(accounts(0), 2);
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
 
         // Setup
         context.predecessor_account_id(accounts(1));
@@ -383,7 +1265,7 @@ mod tests {
         // Seller disputes
         context.predecessor_account_id(accounts(2));
         testing_env!(context.build());
-        contract.dispute_order("o2".to_string());
+        contract.dispute_order("o2".to_string(), "Damaged produce".to_string());
 
         // Owner resolves
         context.predecessor_account_id(accounts(0));
@@ -395,11 +1277,11 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Only owner can resolve disputes")]
+    #[should_panic(expected = "Only owner or arbitrator can resolve disputes")]
     fn test_resolve_dispute_unauthorized() {
         let mut context = get_context(accounts(0));
         testing_env!(context.build());
-        let mut contract = MarketplaceContract::new(accounts(0), 2);
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
 
         context.predecessor_account_id(accounts(1));
         context.attached_deposit(1_000_000_000_000_000_000_000_000);
@@ -407,11 +1289,1193 @@ mod tests {
         contract.create_order("o3".to_string(), accounts(2), "l3".to_string(), 1);
         
         // Dispute
-        contract.dispute_order("o3".to_string());
+        contract.dispute_order("o3".to_string(), "Damaged produce".to_string());
 
         // Attacker tries to resolve
         context.predecessor_account_id(accounts(3));
         testing_env!(context.build());
         contract.resolve_dispute("o3".to_string(), Resolution::RefundBuyer);
     }
+
+    #[test]
+    fn test_resolve_dispute_split() {
+        let mut context = get_context(accounts(0)); // Owner
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        // Buyer creates and disputes the order
+        let escrow: Balance = 1_000_000_000_000_000_000_000_000;
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(escrow);
+        testing_env!(context.build());
+        contract.create_order("o4".to_string(), accounts(2), "l4".to_string(), 1);
+        contract.dispute_order("o4".to_string(), "Damaged produce".to_string());
+
+        // Owner splits 40% back to the buyer
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.resolve_dispute("o4".to_string(), Resolution::Split { buyer_bps: 4_000 });
+
+        let transfers = get_transfers();
+        let paid_to = |account: AccountId| -> Balance {
+            transfers
+                .iter()
+                .filter(|(receiver, _)| *receiver == account)
+                .map(|(_, amount)| *amount)
+                .sum()
+        };
+
+        let buyer_amount = paid_to(accounts(1));
+        let seller_amount = paid_to(accounts(2));
+        let platform_fee = paid_to(accounts(0));
+
+        assert_eq!(buyer_amount, escrow * 4 / 10);
+        assert_eq!(platform_fee, (escrow - buyer_amount) * 2 / 100);
+        assert_eq!(buyer_amount + seller_amount + platform_fee, escrow);
+
+        let order = contract.get_order("o4".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Resolved);
+        assert!(order.completed_at.is_some());
+
+        // Money went back to the buyer, so it counts as a refund for the seller
+        assert_eq!(
+            contract.get_seller_stats(accounts(2)),
+            SellerStats { completed_count: 0, refunded_count: 1 }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "buyer_bps cannot exceed 10000")]
+    fn test_resolve_dispute_split_invalid_bps() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order("o5".to_string(), accounts(2), "l5".to_string(), 1);
+        contract.dispute_order("o5".to_string(), "Damaged produce".to_string());
+
+        context.predecessor_account_id(accounts(0));
+        testing_env!(context.build());
+        contract.resolve_dispute("o5".to_string(), Resolution::Split { buyer_bps: 10_001 });
+    }
+
+    #[test]
+    fn test_claim_expired_refund() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order("o6".to_string(), accounts(2), "l6".to_string(), 1);
+
+        // Move past the timeout
+        context.attached_deposit(0);
+        context.block_timestamp(1_000_000_000 + ORDER_TIMEOUT_NS + 1);
+        testing_env!(context.build());
+        contract.claim_expired_refund("o6".to_string());
+
+        let order = contract.get_order("o6".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Refunded);
+
+        let transfers = get_transfers();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0], (accounts(1), 1_000_000_000_000_000_000_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Order has not expired yet")]
+    fn test_claim_expired_refund_before_timeout() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order("o7".to_string(), accounts(2), "l7".to_string(), 1);
+
+        // Exactly at the threshold is not enough
+        context.attached_deposit(0);
+        context.block_timestamp(1_000_000_000 + ORDER_TIMEOUT_NS);
+        testing_env!(context.build());
+        contract.claim_expired_refund("o7".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only buyer can claim an expired refund")]
+    fn test_claim_expired_refund_not_buyer() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order("o8".to_string(), accounts(2), "l8".to_string(), 1);
+
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        context.block_timestamp(1_000_000_000 + ORDER_TIMEOUT_NS + 1);
+        testing_env!(context.build());
+        contract.claim_expired_refund("o8".to_string());
+    }
+
+    #[test]
+    fn test_arbitrator_resolves_dispute() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new_with_arbitrator(
+            accounts(0),
+            2,
+            ORDER_TIMEOUT_NS,
+            accounts(4),
+            None,
+        );
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order("o9".to_string(), accounts(2), "l9".to_string(), 1);
+        contract.dispute_order("o9".to_string(), "Damaged produce".to_string());
+
+        // Arbitrator (not the owner) resolves in favour of the seller
+        context.predecessor_account_id(accounts(4));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.resolve_dispute("o9".to_string(), Resolution::PaySeller);
+
+        let order = contract.get_order("o9".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Resolved);
+
+        // Platform fee goes to the fee recipient (the owner by default), never the arbitrator
+        let transfers = get_transfers();
+        assert!(transfers.iter().any(|(receiver, _)| *receiver == accounts(0)));
+        assert!(!transfers.iter().any(|(receiver, _)| *receiver == accounts(4)));
+    }
+
+    #[test]
+    fn test_set_arbitrator() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.set_arbitrator(accounts(4));
+        assert_eq!(contract.get_arbitrator(), accounts(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set arbitrator")]
+    fn test_set_arbitrator_unauthorized() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(3));
+        testing_env!(context.build());
+        contract.set_arbitrator(accounts(3));
+    }
+
+    #[test]
+    fn test_order_created_event() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o10".to_string(), accounts(2), "l10".to_string(), 3);
+
+        let events = get_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["standard"], EVENT_STANDARD);
+        assert_eq!(events[0]["version"], EVENT_STANDARD_VERSION);
+        assert_eq!(events[0]["event"], "order_created");
+        assert_eq!(events[0]["data"][0]["order_id"], "o10");
+        assert_eq!(events[0]["data"][0]["buyer"], accounts(1).to_string());
+        assert_eq!(events[0]["data"][0]["amount"], "1000000000000000000000000");
+    }
+
+    #[test]
+    fn test_dispute_events() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order("o11".to_string(), accounts(2), "l11".to_string(), 1);
+        contract.dispute_order("o11".to_string(), "Damaged produce".to_string());
+
+        let events = get_events();
+        assert_eq!(events[1]["event"], "order_disputed");
+        assert_eq!(events[1]["data"][0]["disputed_by"], accounts(1).to_string());
+
+        // Rebuilding the context clears the log buffer
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.resolve_dispute("o11".to_string(), Resolution::RefundBuyer);
+
+        let events = get_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["event"], "dispute_resolved");
+
+        let resolved = &events[0]["data"][0];
+        assert_eq!(resolved["buyer_amount"], "1000000000000000000000000");
+        assert_eq!(resolved["seller_amount"], "0");
+    }
+
+    #[test]
+    fn test_get_orders_paged() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        for i in 0..50 {
+            contract.create_order(format!("p{}", i), accounts(2), "l12".to_string(), 1);
+        }
+
+        let buyer_page = contract.get_buyer_orders_paged(accounts(1), 20, 10);
+        let ids: Vec<_> = buyer_page.iter().map(|order| order.order_id.clone()).collect();
+        let expected: Vec<_> = (20..30).map(|i| format!("p{}", i)).collect();
+        assert_eq!(ids, expected);
+
+        let seller_page = contract.get_seller_orders_paged(accounts(2), 20, 10);
+        assert_eq!(seller_page.len(), 10);
+        assert_eq!(seller_page[0].order_id, "p20");
+
+        // Windows past the end are empty, unpaged views return the default page
+        assert!(contract.get_buyer_orders_paged(accounts(1), 50, 10).is_empty());
+        assert_eq!(contract.get_buyer_orders(accounts(1)).len(), 50);
+        assert!(contract.get_buyer_orders(accounts(3)).is_empty());
+    }
+
+    #[test]
+    fn test_archive_completed_order() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o13".to_string(), accounts(2), "l13".to_string(), 1);
+        // Seller confirms shipment before the buyer releases funds
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.confirm_shipment("o13".to_string());
+
+        context.predecessor_account_id(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.complete_order("o13".to_string());
+
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        let archived = contract.archive_order("o13".to_string());
+
+        assert_eq!(archived.status, OrderStatus::Completed);
+        assert!(contract.get_order("o13".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only completed, refunded or resolved orders can be archived")]
+    fn test_archive_pending_order() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o14".to_string(), accounts(2), "l14".to_string(), 1);
+
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.archive_order("o14".to_string());
+    }
+
+    #[test]
+    fn test_complete_order_transfer_succeeds() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o15".to_string(), accounts(2), "l15".to_string(), 1);
+        // Seller confirms shipment before the buyer releases funds
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.confirm_shipment("o15".to_string());
+
+        context.predecessor_account_id(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.complete_order("o15".to_string());
+
+        // Callback runs as the contract itself with a successful transfer
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        assert!(contract.on_complete_transfer(
+            Ok(()),
+            "o15".to_string(),
+            U128(980_000_000_000_000_000_000_000),
+            U128(20_000_000_000_000_000_000_000),
+            U128(0),
+        ));
+
+        let order = contract.get_order("o15".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Completed);
+        assert_eq!(get_transfers(), vec![(accounts(0), 20_000_000_000_000_000_000_000)]);
+    }
+
+    #[test]
+    fn test_complete_order_transfer_fails() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o16".to_string(), accounts(2), "l16".to_string(), 1);
+        // Seller confirms shipment before the buyer releases funds
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.confirm_shipment("o16".to_string());
+
+        context.predecessor_account_id(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.complete_order("o16".to_string());
+
+        // Seller account was deleted, so the transfer failed
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        assert!(!contract.on_complete_transfer(
+            Err(PromiseError::Failed),
+            "o16".to_string(),
+            U128(980_000_000_000_000_000_000_000),
+            U128(20_000_000_000_000_000_000_000),
+            U128(0),
+        ));
+
+        let order = contract.get_order("o16".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Pending);
+        assert!(order.completed_at.is_none());
+        assert!(get_transfers().is_empty());
+    }
+
+    /// Contract owned by accounts(0) that accepts `token`, with `token` as the caller
+    fn setup_token_contract(context: &mut VMContextBuilder, token: AccountId) -> MarketplaceContract {
+        context.predecessor_account_id(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+        contract.add_accepted_token(token.clone());
+
+        context.predecessor_account_id(token);
+        testing_env!(context.build());
+        contract
+    }
+
+    #[test]
+    fn test_ft_on_transfer_creates_order() {
+        // Token contract forwards the buyer's deposit
+        let mut context = get_context(accounts(3));
+        let mut contract = setup_token_contract(&mut context, accounts(3));
+
+        let msg = json!({
+            "order_id": "ft1",
+            "seller": accounts(2),
+            "listing_id": "l17",
+            "quantity": 4,
+        })
+        .to_string();
+        let unused = contract.ft_on_transfer(accounts(1), U128(1_000_000), msg);
+        assert!(matches!(unused, PromiseOrValue::Value(U128(0))));
+
+        let order = contract.get_order("ft1".to_string()).unwrap();
+        assert_eq!(order.buyer, accounts(1));
+        assert_eq!(order.seller, accounts(2));
+        assert_eq!(order.amount, 1_000_000);
+        assert_eq!(order.quantity, 4);
+        assert_eq!(order.token, Some(accounts(3)));
+        assert_eq!(order.status, OrderStatus::Pending);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token not accepted")]
+    fn test_ft_on_transfer_rejects_unlisted_token() {
+        // Any account can call ft_on_transfer, e.g. a worthless self-deployed token
+        let mut context = get_context(accounts(3));
+        let mut contract = setup_token_contract(&mut context, accounts(3));
+        context.predecessor_account_id(accounts(4));
+        testing_env!(context.build());
+
+        let msg = json!({
+            "order_id": "ft3",
+            "seller": accounts(2),
+            "listing_id": "l20",
+            "quantity": 1,
+        })
+        .to_string();
+        let _ = contract.ft_on_transfer(accounts(1), U128(1_000_000), msg);
+    }
+
+    #[test]
+    fn test_accepted_tokens_managed_by_owner() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_token_contract(&mut context, accounts(3));
+        assert_eq!(contract.get_accepted_tokens(), vec![accounts(3)]);
+
+        context.predecessor_account_id(accounts(0));
+        testing_env!(context.build());
+        contract.remove_accepted_token(accounts(3));
+        assert!(contract.get_accepted_tokens().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can accept tokens")]
+    fn test_add_accepted_token_not_owner() {
+        let context = get_context(accounts(4));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.add_accepted_token(accounts(4));
+    }
+
+    #[test]
+    fn test_ft_order_refund_uses_ft_transfer() {
+        let mut context = get_context(accounts(3));
+        let mut contract = setup_token_contract(&mut context, accounts(3));
+
+        let msg = json!({
+            "order_id": "ft2",
+            "seller": accounts(2),
+            "listing_id": "l18",
+            "quantity": 1,
+        })
+        .to_string();
+        let _ = contract.ft_on_transfer(accounts(1), U128(1_000_000), msg);
+
+        // Seller refunds, which must go back through the token contract
+        context.predecessor_account_id(accounts(2));
+        testing_env!(context.build());
+        contract.refund_order("ft2".to_string());
+
+        assert!(get_transfers().is_empty());
+        assert_eq!(get_ft_transfers(accounts(3)), vec![(accounts(1), 1_000_000)]);
+    }
+
+    #[test]
+    fn test_native_order_has_no_token() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        let order = contract.create_order("o19".to_string(), accounts(2), "l19".to_string(), 1);
+        assert!(order.token.is_none());
+
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.refund_order("o19".to_string());
+
+        assert_eq!(get_transfers(), vec![(accounts(1), 1_000_000_000_000_000_000_000_000)]);
+        assert!(get_ft_transfers(accounts(3)).is_empty());
+    }
+
+    #[test]
+    fn test_ownership_handover() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.propose_new_owner(accounts(4));
+        assert_eq!(contract.owner, accounts(0));
+        assert_eq!(contract.pending_owner, Some(accounts(4)));
+
+        context.predecessor_account_id(accounts(4));
+        testing_env!(context.build());
+        contract.accept_ownership();
+
+        assert_eq!(contract.owner, accounts(4));
+        assert!(contract.pending_owner.is_none());
+
+        // New owner can use owner-only methods
+        contract.update_platform_fee(3);
+        assert_eq!(contract.get_platform_fee(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the pending owner can accept ownership")]
+    fn test_accept_ownership_wrong_account() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.propose_new_owner(accounts(4));
+
+        context.predecessor_account_id(accounts(3));
+        testing_env!(context.build());
+        contract.accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can propose a new owner")]
+    fn test_propose_new_owner_unauthorized() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(3));
+        testing_env!(context.build());
+        contract.propose_new_owner(accounts(3));
+    }
+
+    #[test]
+    fn test_dispute_reason_persisted() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o20".to_string(), accounts(2), "l20".to_string(), 1);
+        contract.dispute_order("o20".to_string(), "Half the crates arrived spoiled".to_string());
+
+        let order = contract.get_order("o20".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Disputed);
+        assert_eq!(
+            order.dispute_reason.as_deref(),
+            Some("Half the crates arrived spoiled")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Dispute reason must be between 1 and 500 characters")]
+    fn test_dispute_empty_reason() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o21".to_string(), accounts(2), "l21".to_string(), 1);
+        contract.dispute_order("o21".to_string(), "".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Dispute reason must be between 1 and 500 characters")]
+    fn test_dispute_reason_too_long() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o22".to_string(), accounts(2), "l22".to_string(), 1);
+        contract.dispute_order("o22".to_string(), "x".repeat(501));
+    }
+
+    #[test]
+    fn test_get_stats() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("s1".to_string(), accounts(2), "l23".to_string(), 1);
+        contract.create_order("s2".to_string(), accounts(2), "l23".to_string(), 1);
+        contract.create_order("s3".to_string(), accounts(2), "l23".to_string(), 1);
+        contract.create_order("s4".to_string(), accounts(2), "l23".to_string(), 1);
+        // Seller confirms shipment before the buyer releases funds
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.confirm_shipment("s2".to_string());
+
+        context.predecessor_account_id(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.complete_order("s2".to_string());
+        contract.dispute_order("s3".to_string(), "Wrong variety delivered".to_string());
+
+        let stats = contract.get_stats();
+        assert_eq!(stats.total_orders, 4);
+        assert_eq!(stats.pending, 2);
+        assert_eq!(stats.completed, 1);
+        assert_eq!(stats.disputed, 1);
+        assert_eq!(stats.refunded, 0);
+        assert_eq!(stats.resolved, 0);
+        // Two pending plus one disputed order remain locked
+        assert_eq!(stats.total_escrowed, U128(3_000_000_000_000_000_000_000_000));
+    }
+
+    #[test]
+    fn test_fee_goes_to_custom_recipient() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract =
+            MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, Some(accounts(5)));
+        assert_eq!(contract.get_fee_recipient(), accounts(5));
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order("o24".to_string(), accounts(2), "l24".to_string(), 1);
+        contract.dispute_order("o24".to_string(), "Never delivered".to_string());
+
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.resolve_dispute("o24".to_string(), Resolution::PaySeller);
+
+        let transfers = get_transfers();
+        assert!(transfers.contains(&(accounts(5), 20_000_000_000_000_000_000_000)));
+        assert!(!transfers.iter().any(|(receiver, _)| *receiver == accounts(0)));
+    }
+
+    #[test]
+    fn test_set_fee_recipient() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.set_fee_recipient(accounts(5));
+        assert_eq!(contract.get_fee_recipient(), accounts(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set fee recipient")]
+    fn test_set_fee_recipient_unauthorized() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(3));
+        testing_env!(context.build());
+        contract.set_fee_recipient(accounts(3));
+    }
+
+    #[test]
+    fn test_confirm_shipment_then_complete() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o25".to_string(), accounts(2), "l25".to_string(), 1);
+
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.confirm_shipment("o25".to_string());
+        assert!(contract.get_order("o25".to_string()).unwrap().seller_confirmed);
+
+        context.predecessor_account_id(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.complete_order("o25".to_string());
+
+        let order = contract.get_order("o25".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Completed);
+        assert_eq!(get_transfers(), vec![(accounts(2), 980_000_000_000_000_000_000_000)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Seller has not confirmed shipment")]
+    fn test_complete_order_before_confirmation() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o26".to_string(), accounts(2), "l26".to_string(), 1);
+        let _ = contract.complete_order("o26".to_string());
+    }
+
+    #[test]
+    fn test_complete_order_after_timeout_without_confirmation() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o27".to_string(), accounts(2), "l27".to_string(), 1);
+
+        context.attached_deposit(0);
+        context.block_timestamp(1_000_000_000 + ORDER_TIMEOUT_NS + 1);
+        testing_env!(context.build());
+        let _ = contract.complete_order("o27".to_string());
+
+        let order = contract.get_order("o27".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Completed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only seller can confirm shipment")]
+    fn test_confirm_shipment_not_seller() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("o28".to_string(), accounts(2), "l28".to_string(), 1);
+        contract.confirm_shipment("o28".to_string());
+    }
+
+    #[test]
+    fn test_resolve_disputes_mixed_batch() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new_with_arbitrator(
+            accounts(0),
+            2,
+            ORDER_TIMEOUT_NS,
+            accounts(4),
+            None,
+        );
+
+        contract.create_order("b1".to_string(), accounts(2), "l29".to_string(), 1);
+        contract.create_order("b2".to_string(), accounts(2), "l29".to_string(), 1);
+        contract.create_order("b3".to_string(), accounts(2), "l29".to_string(), 1);
+        contract.dispute_order("b1".to_string(), "Never delivered".to_string());
+        contract.dispute_order("b3".to_string(), "Wrong variety delivered".to_string());
+
+        context.predecessor_account_id(accounts(4));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        let summary = contract.resolve_disputes(vec![
+            ("b1".to_string(), Resolution::RefundBuyer),
+            ("b2".to_string(), Resolution::RefundBuyer),
+            ("missing".to_string(), Resolution::PaySeller),
+            ("b3".to_string(), Resolution::PaySeller),
+        ]);
+
+        assert_eq!(summary.resolved, vec!["b1".to_string(), "b3".to_string()]);
+        assert_eq!(summary.skipped, vec!["b2".to_string(), "missing".to_string()]);
+        assert_eq!(contract.get_order("b1".to_string()).unwrap().status, OrderStatus::Resolved);
+        assert_eq!(contract.get_order("b2".to_string()).unwrap().status, OrderStatus::Pending);
+        assert_eq!(contract.get_order("b3".to_string()).unwrap().status, OrderStatus::Resolved);
+
+        let transfers = get_transfers();
+        assert!(transfers.contains(&(accounts(1), 1_000_000_000_000_000_000_000_000)));
+        assert!(transfers.contains(&(accounts(2), 980_000_000_000_000_000_000_000)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot resolve more than 20 disputes at once")]
+    fn test_resolve_disputes_batch_too_large() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        let resolutions = (0..=MAX_DISPUTE_BATCH)
+            .map(|i| (format!("b{}", i), Resolution::RefundBuyer))
+            .collect();
+        contract.resolve_disputes(resolutions);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner or arbitrator can resolve disputes")]
+    fn test_resolve_disputes_unauthorized() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(3));
+        testing_env!(context.build());
+        contract.resolve_disputes(vec![]);
+    }
+
+    #[test]
+    fn test_partial_delivery_splits_escrow() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("q1".to_string(), accounts(2), "l30".to_string(), 4);
+
+        // Only three of the four crates made it
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.confirm_partial("q1".to_string(), 3);
+        assert_eq!(contract.get_order("q1".to_string()).unwrap().delivered_quantity, Some(3));
+
+        context.predecessor_account_id(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.complete_order("q1".to_string());
+
+        // Seller is paid for 0.75 NEAR minus the 2% fee
+        assert_eq!(get_transfers(), vec![(accounts(2), 735_000_000_000_000_000_000_000)]);
+
+        context.predecessor_account_id(accounts(0));
+        testing_env!(context.build());
+        assert!(contract.on_complete_transfer(
+            Ok(()),
+            "q1".to_string(),
+            U128(735_000_000_000_000_000_000_000),
+            U128(15_000_000_000_000_000_000_000),
+            U128(250_000_000_000_000_000_000_000),
+        ));
+
+        assert_eq!(
+            get_transfers(),
+            vec![
+                (accounts(0), 15_000_000_000_000_000_000_000),
+                (accounts(1), 250_000_000_000_000_000_000_000),
+            ]
+        );
+        let events = get_events();
+        assert_eq!(events[0]["data"][0]["buyer_refund"], "250000000000000000000000");
+
+        // Same rule as a split dispute: the buyer got money back
+        assert_eq!(
+            contract.get_seller_stats(accounts(2)),
+            SellerStats { completed_count: 0, refunded_count: 1 }
+        );
+    }
+
+    #[test]
+    fn test_partial_delivery_of_max_token_amount() {
+        // amount * delivered would overflow u128 for the largest possible deposit
+        let mut context = get_context(accounts(3));
+        let mut contract = setup_token_contract(&mut context, accounts(3));
+
+        let msg = json!({
+            "order_id": "q3",
+            "seller": accounts(2),
+            "listing_id": "l32",
+            "quantity": 3,
+        })
+        .to_string();
+        let _ = contract.ft_on_transfer(accounts(1), U128(u128::MAX), msg);
+
+        context.predecessor_account_id(accounts(2));
+        testing_env!(context.build());
+        contract.confirm_partial("q3".to_string(), 2);
+
+        context.predecessor_account_id(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.complete_order("q3".to_string());
+
+        // Two thirds of u128::MAX minus the 2% fee
+        assert_eq!(
+            get_ft_transfers(accounts(3)),
+            vec![(accounts(2), 222_317_813_055_013_129_462_738_076_855_421_898_151)]
+        );
+
+        context.predecessor_account_id(accounts(0));
+        testing_env!(context.build());
+        assert!(contract.on_complete_transfer(
+            Ok(()),
+            "q3".to_string(),
+            U128(222_317_813_055_013_129_462_738_076_855_421_898_151),
+            U128(4_537_098_225_612_512_846_178_328_099_090_242_819),
+            U128(113_427_455_640_312_821_154_458_202_477_256_070_485),
+        ));
+
+        assert_eq!(
+            get_ft_transfers(accounts(3)),
+            vec![
+                (accounts(0), 4_537_098_225_612_512_846_178_328_099_090_242_819),
+                (accounts(1), 113_427_455_640_312_821_154_458_202_477_256_070_485),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Delivered quantity cannot exceed the ordered quantity")]
+    fn test_confirm_partial_over_quantity() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("q2".to_string(), accounts(2), "l31".to_string(), 4);
+
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.confirm_partial("q2".to_string(), 5);
+    }
+
+    #[test]
+    fn test_disputed_orders_index() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.create_order("d1".to_string(), accounts(2), "l32".to_string(), 1);
+        contract.create_order("d2".to_string(), accounts(2), "l32".to_string(), 1);
+        contract.create_order("d3".to_string(), accounts(2), "l32".to_string(), 1);
+        contract.dispute_order("d1".to_string(), "Never delivered".to_string());
+        contract.dispute_order("d3".to_string(), "Wrong variety delivered".to_string());
+
+        let ids: Vec<_> = contract
+            .get_disputed_orders(0, 10)
+            .into_iter()
+            .map(|order| order.order_id)
+            .collect();
+        assert_eq!(ids, vec!["d1".to_string(), "d3".to_string()]);
+        assert_eq!(contract.get_disputed_orders(1, 10)[0].order_id, "d3");
+
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.resolve_dispute("d1".to_string(), Resolution::RefundBuyer);
+
+        assert!(!contract.disputed_orders.contains(&"d1".to_string()));
+        let remaining = contract.get_disputed_orders(0, 10);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].order_id, "d3");
+
+        // Batch resolution keeps the index in sync too
+        contract.resolve_disputes(vec![("d3".to_string(), Resolution::PaySeller)]);
+        assert!(contract.get_disputed_orders(0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_seller_stats_count_completions_and_refunds() {
+        let mut context = get_context(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+        assert_eq!(contract.get_seller_stats(accounts(2)), SellerStats::default());
+
+        contract.create_order("r1".to_string(), accounts(2), "l33".to_string(), 1);
+        contract.create_order("r2".to_string(), accounts(2), "l33".to_string(), 1);
+        contract.create_order("r3".to_string(), accounts(2), "l33".to_string(), 1);
+        contract.dispute_order("r3".to_string(), "Never delivered".to_string());
+
+        context.predecessor_account_id(accounts(2));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.confirm_shipment("r1".to_string());
+        contract.refund_order("r2".to_string());
+
+        context.predecessor_account_id(accounts(1));
+        testing_env!(context.build());
+        let _ = contract.complete_order("r1".to_string());
+
+        // Completion only counts once the seller has actually been paid
+        assert_eq!(contract.get_seller_stats(accounts(2)).completed_count, 0);
+
+        context.predecessor_account_id(accounts(0));
+        testing_env!(context.build());
+        contract.on_complete_transfer(
+            Ok(()),
+            "r1".to_string(),
+            U128(980_000_000_000_000_000_000_000),
+            U128(20_000_000_000_000_000_000_000),
+            U128(0),
+        );
+        contract.resolve_dispute("r3".to_string(), Resolution::RefundBuyer);
+
+        let stats = contract.get_seller_stats(accounts(2));
+        assert_eq!(stats.completed_count, 1);
+        assert_eq!(stats.refunded_count, 2);
+        assert_eq!(contract.get_seller_stats(accounts(1)), SellerStats::default());
+    }
+
+    const INSPECTION_NS: u64 = 60 * 60 * 1_000_000_000; // 1 hour
+
+    /// Contract with a one-hour inspection window and one unconfirmed pending order
+    fn setup_inspection_order(context: &mut VMContextBuilder, order_id: &str) -> MarketplaceContract {
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+        contract.set_min_inspection_ns(INSPECTION_NS);
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order(order_id.to_string(), accounts(2), "l34".to_string(), 1);
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "Seller has not confirmed shipment and the inspection window is still open")]
+    fn test_complete_order_during_inspection_window() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_inspection_order(&mut context, "i1");
+
+        context.attached_deposit(0);
+        context.block_timestamp(1_000_000_000 + INSPECTION_NS);
+        testing_env!(context.build());
+        let _ = contract.complete_order("i1".to_string());
+    }
+
+    #[test]
+    fn test_complete_order_after_inspection_window() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_inspection_order(&mut context, "i2");
+        assert_eq!(
+            contract.get_order("i2".to_string()).unwrap().inspection_ends_at,
+            1_000_000_000 + INSPECTION_NS
+        );
+
+        // Changing the window later does not move an existing order's deadline
+        context.predecessor_account_id(accounts(0));
+        context.attached_deposit(0);
+        testing_env!(context.build());
+        contract.set_min_inspection_ns(2 * INSPECTION_NS);
+
+        context.predecessor_account_id(accounts(1));
+        context.block_timestamp(1_000_000_000 + INSPECTION_NS + 1);
+        testing_env!(context.build());
+        let _ = contract.complete_order("i2".to_string());
+
+        let order = contract.get_order("i2".to_string()).unwrap();
+        assert_eq!(order.status, OrderStatus::Completed);
+    }
+
+    /// Contract with the default windows and an unconfirmed order, just after its inspection window
+    fn setup_default_inspection_order(context: &mut VMContextBuilder, order_id: &str) -> MarketplaceContract {
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+        assert_eq!(contract.get_min_inspection_ns(), DEFAULT_INSPECTION_NS);
+
+        context.predecessor_account_id(accounts(1));
+        context.attached_deposit(1_000_000_000_000_000_000_000_000);
+        testing_env!(context.build());
+        contract.create_order(order_id.to_string(), accounts(2), "l35".to_string(), 1);
+
+        context.attached_deposit(0);
+        context.block_timestamp(1_000_000_000 + DEFAULT_INSPECTION_NS + 1);
+        testing_env!(context.build());
+        contract
+    }
+
+    #[test]
+    fn test_default_inspection_window_ends_before_timeout() {
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_default_inspection_order(&mut context, "i3");
+
+        let _ = contract.complete_order("i3".to_string());
+        assert_eq!(contract.get_order("i3".to_string()).unwrap().status, OrderStatus::Completed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Order has not expired yet")]
+    fn test_no_expired_refund_right_after_default_inspection_window() {
+        // The buyer can complete the order here, but not yet reclaim it
+        let mut context = get_context(accounts(0));
+        let mut contract = setup_default_inspection_order(&mut context, "i4");
+
+        contract.claim_expired_refund("i4".to_string());
+    }
+
+    #[test]
+    fn test_default_inspection_window_capped_by_short_timeout() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = MarketplaceContract::new(accounts(0), 2, 1_000, None);
+
+        assert_eq!(contract.get_min_inspection_ns(), 500);
+    }
+
+    #[test]
+    #[should_panic(expected = "Inspection window must end before the order timeout")]
+    fn test_set_min_inspection_not_before_timeout() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        contract.set_min_inspection_ns(ORDER_TIMEOUT_NS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can set the inspection window")]
+    fn test_set_min_inspection_unauthorized() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = MarketplaceContract::new(accounts(0), 2, ORDER_TIMEOUT_NS, None);
+
+        context.predecessor_account_id(accounts(3));
+        testing_env!(context.build());
+        contract.set_min_inspection_ns(0);
+    }
+
+    fn old_order(order_id: &str, seller: AccountId, status: OrderStatus) -> OldEscrowOrder {
+        OldEscrowOrder {
+            order_id: order_id.to_string(),
+            buyer: accounts(1),
+            seller,
+            amount: 1_000,
+            listing_id: "l1".to_string(),
+            quantity: 2,
+            status,
+            created_at: 500,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_migrate_from_old_state() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        // State as the first deployment wrote it
+        let mut old = OldMarketplaceContract {
+            owner: accounts(0),
+            orders: UnorderedMap::new(b"o"),
+            platform_fee_percentage: 3,
+        };
+        for (order_id, status) in [
+            ("m1", OrderStatus::Pending),
+            ("m2", OrderStatus::Completed),
+            ("m3", OrderStatus::Refunded),
+            ("m4", OrderStatus::Disputed),
+            ("m5", OrderStatus::Resolved),
+        ] {
+            old.orders.insert(&order_id.to_string(), &old_order(order_id, accounts(2), status));
+        }
+        env::state_write(&old);
+
+        let contract = MarketplaceContract::migrate();
+
+        assert_eq!(contract.owner, accounts(0));
+        assert_eq!(contract.arbitrator, accounts(0));
+        assert_eq!(contract.get_fee_recipient(), accounts(0));
+        assert_eq!(contract.pending_owner, None);
+        assert_eq!(contract.get_platform_fee(), 3);
+        assert_eq!(contract.order_timeout_ns, DEFAULT_ORDER_TIMEOUT_NS);
+        assert_eq!(contract.get_min_inspection_ns(), DEFAULT_INSPECTION_NS);
+
+        // Orders read back in the new layout
+        let pending = contract.get_order("m1".to_string()).unwrap();
+        assert_eq!(pending.status, OrderStatus::Pending);
+        assert_eq!(pending.amount, 1_000);
+        assert_eq!(pending.token, None);
+        assert!(!pending.seller_confirmed);
+        assert_eq!(pending.inspection_ends_at, 500 + DEFAULT_INSPECTION_NS);
+        assert_eq!(contract.get_stats().total_orders, 5);
+
+        // Indexes are rebuilt from the migrated orders
+        let disputed: Vec<String> = contract
+            .get_disputed_orders(0, 10)
+            .into_iter()
+            .map(|order| order.order_id)
+            .collect();
+        assert_eq!(disputed, vec!["m4".to_string()]);
+        assert_eq!(
+            contract.get_seller_stats(accounts(2)),
+            SellerStats { completed_count: 1, refunded_count: 1 }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "No contract state to migrate")]
+    fn test_migrate_without_state() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        MarketplaceContract::migrate();
+    }
 }
@@ -24,6 +24,7 @@ export interface Member {
     name: string;
     farm_name?: string;
     location?: string;
+    role: 'Member' | 'Admin';
     created_at: string;
 }
 
@@ -37,6 +38,40 @@ export interface ProductListing {
     quantity_available: number;
     unit_of_measure: string;
     availability_status: 'Available' | 'OutOfStock' | 'Archived';
+    version: number;
+    expires_at: string | null;
+    // Only present on the owner's own listings
+    view_count?: number;
+    created_at: string;
+    updated_at: string;
+}
+
+export interface Paginated<T> {
+    items: T[];
+    total: number;
+    limit: number;
+    offset: number;
+    has_more: boolean;
+}
+
+export interface ListingFilters {
+    search_term?: string | null;
+    category?: string | null;
+    min_price?: string | null;
+    max_price?: string | null;
+    availability?: ProductListing['availability_status'] | null;
+    min_quantity?: string | null;
+    max_quantity?: string | null;
+    sort?: 'newest' | 'reputation_desc';
+    limit?: number | null;
+    offset?: number | null;
+}
+
+export interface SavedSearch {
+    id: string;
+    member_id: string;
+    name: string;
+    filters: ListingFilters;
     created_at: string;
     updated_at: string;
 }
@@ -49,29 +84,59 @@ export interface Order {
     quantity: number;
     unit_price: string;
     total_amount: string;
-    status: 'Pending' | 'Accepted' | 'Rejected' | 'Completed' | 'Cancelled';
+    status: 'Pending' | 'Accepted' | 'Rejected' | 'Shipped' | 'Delivered' | 'Completed' | 'Cancelled' | 'Disputed' | 'Resolved';
+    escrow_order_id: string | null;
+    seller_note: string | null;
     created_at: string;
     updated_at: string;
 }
 
+export interface CancelledWithRefund {
+    order: Order;
+    // The seller or contract owner must call `refund_order` for this escrow on chain
+    refund_required: { escrow_order_id: string };
+}
+
+export interface OrderWithListing extends Order {
+    product_name: string | null;
+    unit_of_measure: string | null;
+}
+
+export interface OrderMessage {
+    id: string;
+    order_id: string;
+    sender_id: string;
+    body: string;
+    created_at: string;
+}
+
 // Auth API
 export const authAPI = {
     register: async (data: { email: string; password: string; name: string; farm_name?: string }) => {
-        const response = await apiClient.post<{ member: Member; token: string }>('/auth/register', data);
+        const response = await apiClient.post<{ member: Member; token: string; refresh_token: string }>('/auth/register', data);
         return response.data;
     },
 
     login: async (data: { email: string; password: string }) => {
-        const response = await apiClient.post<{ member: Member; token: string }>('/auth/login', data);
+        const response = await apiClient.post<{ member: Member; token: string; refresh_token: string }>('/auth/login', data);
         return response.data;
     },
+
+    refresh: async (refresh_token: string) => {
+        const response = await apiClient.post<{ token: string; refresh_token: string }>('/auth/refresh', { refresh_token });
+        return response.data;
+    },
+
+    logout: async () => {
+        await apiClient.post('/auth/logout');
+    },
 };
 
 // Listings API
 export const listingsAPI = {
     getAll: async () => {
-        const response = await apiClient.get<ProductListing[]>('/listings');
-        return response.data;
+        const response = await apiClient.get<Paginated<ProductListing>>('/listings');
+        return response.data.items;
     },
 
     getById: async (id: string) => {
@@ -79,6 +144,11 @@ export const listingsAPI = {
         return response.data;
     },
 
+    getByIds: async (ids: string[]) => {
+        const response = await apiClient.post<ProductListing[]>('/listings/batch', { ids });
+        return response.data;
+    },
+
     create: async (data: Partial<ProductListing>) => {
         const response = await apiClient.post<ProductListing>('/listings', data);
         return response.data;
@@ -94,15 +164,42 @@ export const listingsAPI = {
     },
 };
 
+// Saved searches API
+export const savedSearchesAPI = {
+    getAll: async () => {
+        const response = await apiClient.get<SavedSearch[]>('/saved-searches');
+        return response.data;
+    },
+
+    create: async (name: string, filters: ListingFilters) => {
+        const response = await apiClient.post<SavedSearch>('/saved-searches', { name, filters });
+        return response.data;
+    },
+
+    update: async (id: string, name: string, filters: ListingFilters) => {
+        const response = await apiClient.put<SavedSearch>(`/saved-searches/${id}`, { name, filters });
+        return response.data;
+    },
+
+    delete: async (id: string) => {
+        await apiClient.delete(`/saved-searches/${id}`);
+    },
+
+    run: async (id: string) => {
+        const response = await apiClient.get<ProductListing[]>(`/saved-searches/${id}/results`);
+        return response.data;
+    },
+};
+
 // Orders API
 export const ordersAPI = {
     getAll: async () => {
-        const response = await apiClient.get<Order[]>('/orders');
+        const response = await apiClient.get<OrderWithListing[]>('/orders');
         return response.data;
     },
 
     getById: async (id: string) => {
-        const response = await apiClient.get<Order>(`/orders/${id}`);
+        const response = await apiClient.get<OrderWithListing>(`/orders/${id}`);
         return response.data;
     },
 
@@ -111,8 +208,24 @@ export const ordersAPI = {
         return response.data;
     },
 
+    // Cancelling an escrow-backed order answers 202 with the escrow still to refund
     updateStatus: async (id: string, status: Order['status']) => {
-        const response = await apiClient.put<Order>(`/orders/${id}/status`, { status });
+        const response = await apiClient.put<Order | CancelledWithRefund>(`/orders/${id}/status`, { status });
+        return response.data;
+    },
+
+    setEscrow: async (id: string, escrow_order_id: string) => {
+        const response = await apiClient.put<Order>(`/orders/${id}/escrow`, { escrow_order_id });
+        return response.data;
+    },
+
+    getMessages: async (id: string) => {
+        const response = await apiClient.get<OrderMessage[]>(`/orders/${id}/messages`);
+        return response.data;
+    },
+
+    sendMessage: async (id: string, body: string) => {
+        const response = await apiClient.post<OrderMessage>(`/orders/${id}/messages`, { body });
         return response.data;
     },
 };
@@ -29,6 +29,8 @@ export interface EscrowOrder {
     status: 'Pending' | 'Completed' | 'Refunded' | 'Disputed';
     created_at: string;
     completed_at?: string;
+    dispute_reason?: string;
+    seller_confirmed: boolean;
 }
 
 // NEAR contract methods
@@ -38,7 +40,7 @@ export class MarketplaceContract {
     constructor(wallet: WalletConnection) {
         this.contract = new Contract(wallet.account(), CONTRACT_ID, {
             viewMethods: ['get_order', 'get_buyer_orders', 'get_seller_orders', 'get_platform_fee'],
-            changeMethods: ['create_order', 'complete_order', 'refund_order', 'dispute_order'],
+            changeMethods: ['create_order', 'confirm_shipment', 'complete_order', 'refund_order', 'dispute_order'],
         }) as any;
     }
 
@@ -62,6 +64,14 @@ export class MarketplaceContract {
         );
     }
 
+    // Confirm shipment (seller)
+    async confirmShipment(orderId: string): Promise<void> {
+        return await (this.contract as any).confirm_shipment(
+            { order_id: orderId },
+            '300000000000000'
+        );
+    }
+
     // Complete order (buyer confirms delivery)
     async completeOrder(orderId: string): Promise<void> {
         return await (this.contract as any).complete_order(
@@ -78,10 +88,10 @@ export class MarketplaceContract {
         );
     }
 
-    // Dispute order
-    async disputeOrder(orderId: string): Promise<void> {
+    // Dispute order (reason must be 1-500 characters)
+    async disputeOrder(orderId: string, reason: string): Promise<void> {
         return await (this.contract as any).dispute_order(
-            { order_id: orderId },
+            { order_id: orderId, reason },
             '300000000000000'
         );
     }